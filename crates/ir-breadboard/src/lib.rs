@@ -3,7 +3,7 @@ use std::str::FromStr;
 use come::ir::{
     self,
     analyzer::{self, control_flow::structural::FoldedCFG, ControlFlowGraph, IsAnalyzer},
-    optimize::{optimize as optimize_ir, pass::Pass},
+    optimize::{optimize as optimize_ir, OptimizePass},
     IR,
 };
 use wasm_bindgen::prelude::*;
@@ -18,7 +18,7 @@ pub fn parse(code: &str) -> JsValue {
 #[wasm_bindgen]
 pub fn optimize(code: &str, pass: &str) -> String {
     let ir_code = ir::parse(code).unwrap().1;
-    let pass = Pass::from_str(pass).unwrap();
+    let pass = OptimizePass::from_str(pass).unwrap();
     let result = optimize_ir(vec![ir_code], vec![pass])
         .into_iter()
         .next()
@@ -13,10 +13,10 @@ loop_0_success:
     lw a0, 0(a0)
     mv t2, a0
     li t1, 0
-    sub t3, t2, t1
-    seqz t3, t3
+    sub t2, t2, t1
+    seqz t2, t2
     li t1, 0
-    bne t3, t1, if_0_success
+    bne t2, t1, if_0_success
     j if_0_fail
 if_0_success:
     li a1, 1
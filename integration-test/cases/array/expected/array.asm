@@ -0,0 +1,55 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -64
+main_entry:
+    li t3, 0
+    j loop_0_condition
+loop_0_condition:
+    li t1, 8
+    slt t2, t3, t1
+    li t1, 0
+    bne t2, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    mul t4, t3, t3
+    slli t0, t3, 2
+    add t2, sp, t0
+    sw t4, 0(t2)
+    li t1, 1
+    add t3, t3, t1
+    j loop_0_condition
+loop_0_fail:
+    addi t2, sp, 37
+    li t0, 255
+    sb t0, 0(t2)
+    addi t2, sp, 28
+    lw t4, 0(t2)
+    addi t2, sp, 8
+    lw t2, 0(t2)
+    add t4, t4, t2
+    addi t2, sp, 52
+    addi t2, t2, 8
+    sw t4, 0(t2)
+    addi t2, sp, 37
+    lbu t5, 0(t2)
+    addi t4, sp, 40
+    li t1, 8
+    sub t2, t3, t1
+    slli t0, t2, 2
+    add t2, t4, t0
+    sw t5, 0(t2)
+    addi t2, sp, 52
+    addi t2, t2, 8
+    lw t2, 0(t2)
+    li t1, 1000
+    mul t3, t2, t1
+    addi t2, sp, 40
+    addi t2, t2, 0
+    lw t2, 0(t2)
+    add t2, t3, t2
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 64
+    ret
@@ -1,26 +1,26 @@
 .section .text
 .global test_condition
 test_condition:
-    addi sp, sp, -8
+    addi sp, sp, -16
 test_condition_entry:
     sw a0, 0(sp)
     sw a1, 4(sp)
-    lw t2, 0(sp)
-    lw t3, 4(sp)
-    slt t4, t2, t3
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    slt t2, t3, t2
     li t1, 0
-    bne t4, t1, if_0_success
+    bne t2, t1, if_0_success
     j if_0_fail
 if_0_success:
-    lw t4, 0(sp)
-    mv a0, t4
+    lw t2, 0(sp)
+    mv a0, t2
     j test_condition_end
 if_0_fail:
-    lw t4, 4(sp)
-    mv a0, t4
+    lw t2, 4(sp)
+    mv a0, t2
     j test_condition_end
 if_0_end:
     j test_condition_end
 test_condition_end:
-    addi sp, sp, 8
+    addi sp, sp, 16
     ret
@@ -0,0 +1,215 @@
+.section .text
+.global add3
+add3:
+    addi sp, sp, -16
+add3_entry:
+    sw a0, 0(sp)
+    sw a1, 4(sp)
+    sw a2, 8(sp)
+    lw t4, 0(sp)
+    lw t3, 4(sp)
+    lw t2, 8(sp)
+    mul t2, t3, t2
+    add t2, t4, t2
+    mv a0, t2
+    j add3_end
+add3_end:
+    addi sp, sp, 16
+    ret
+.global sum9
+sum9:
+    addi sp, sp, -48
+sum9_entry:
+    sw a0, 0(sp)
+    sw a1, 4(sp)
+    sw a2, 8(sp)
+    sw a3, 12(sp)
+    sw a4, 16(sp)
+    sw a5, 20(sp)
+    sw a6, 24(sp)
+    sw a7, 28(sp)
+    lw t0, 48(sp)
+    sw t0, 32(sp)
+    lw t0, 52(sp)
+    sw t0, 36(sp)
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    add t3, t3, t2
+    lw t2, 8(sp)
+    add t3, t3, t2
+    lw t2, 12(sp)
+    add t3, t3, t2
+    lw t2, 16(sp)
+    add t3, t3, t2
+    lw t2, 20(sp)
+    add t2, t3, t2
+    lw t3, 24(sp)
+    add t2, t2, t3
+    lw t3, 28(sp)
+    add t2, t2, t3
+    lw t3, 32(sp)
+    li t1, 100
+    mul t3, t3, t1
+    add t2, t2, t3
+    lw t3, 36(sp)
+    li t1, 1000
+    mul t3, t3, t1
+    add t2, t2, t3
+    mv a0, t2
+    j sum9_end
+sum9_end:
+    addi sp, sp, 48
+    ret
+.global dot
+dot:
+    addi sp, sp, -16
+dot_entry:
+    sw a0, 0(sp)
+    sw a1, 4(sp)
+    sw a2, 8(sp)
+    sw a3, 12(sp)
+    lw t2, 0(sp)
+    lw t3, 4(sp)
+    mv t4, t2
+    lw t2, 8(sp)
+    lw t3, 12(sp)
+    mv t2, t2
+    mul t5, t4, t2
+    lw t2, 0(sp)
+    lw t3, 4(sp)
+    mv t4, t3
+    lw t2, 8(sp)
+    lw t3, 12(sp)
+    mv t2, t3
+    mul t2, t4, t2
+    add t2, t5, t2
+    mv a0, t2
+    j dot_end
+dot_end:
+    addi sp, sp, 16
+    ret
+.global fib
+fib:
+    addi sp, sp, -32
+    sw ra, 8(sp)
+    sw s0, 12(sp)
+    sw s1, 16(sp)
+fib_entry:
+    sw a0, 0(sp)
+    lw s0, 0(sp)
+    li t1, 2
+    slt s0, s0, t1
+    li t1, 0
+    bne s0, t1, if_0_success
+    j if_0_fail
+if_0_success:
+    lw s0, 0(sp)
+    mv a0, s0
+    j fib_end
+if_0_fail:
+    j if_0_end
+if_0_end:
+    lw s0, 0(sp)
+    li t1, 1
+    sub s0, s0, t1
+    sw a0, 4(sp)
+    mv a0, s0
+    jal ra, fib
+    mv s1, a0
+    lw a0, 4(sp)
+    lw s0, 0(sp)
+    li t1, 2
+    sub s0, s0, t1
+    sw a0, 4(sp)
+    mv a0, s0
+    jal ra, fib
+    mv s0, a0
+    lw a0, 4(sp)
+    add s0, s1, s0
+    mv a0, s0
+    j fib_end
+fib_end:
+    lw ra, 8(sp)
+    lw s0, 12(sp)
+    lw s1, 16(sp)
+    addi sp, sp, 32
+    ret
+.global main
+main:
+    addi sp, sp, -48
+    sw ra, 16(sp)
+    sw s0, 20(sp)
+    sw s1, 24(sp)
+    sw s2, 28(sp)
+    sw s3, 32(sp)
+    sw s4, 36(sp)
+main_entry:
+    lw s2, 0(sp)
+    lw s3, 4(sp)
+    li t1, 3
+    mv s0, t1
+    mv s1, s3
+    sw s0, 0(sp)
+    sw s1, 4(sp)
+    lw s2, 0(sp)
+    lw s3, 4(sp)
+    li t1, 4
+    mv s0, s2
+    mv s1, t1
+    sw s0, 0(sp)
+    sw s1, 4(sp)
+    li a0, 1
+    li a1, 2
+    li a2, 3
+    jal ra, add3
+    mv s0, a0
+    sw s0, 8(sp)
+    lw s0, 8(sp)
+    addi sp, sp, -16
+    li a0, 1
+    li a1, 2
+    li a2, 3
+    li a3, 4
+    li a4, 5
+    li a5, 6
+    li a6, 7
+    li a7, 8
+    li t0, 9
+    sw t0, 0(sp)
+    mv t0, s0
+    sw t0, 4(sp)
+    jal ra, sum9
+    addi sp, sp, 16
+    mv s0, a0
+    sw s0, 12(sp)
+    lw s4, 12(sp)
+    lw s2, 0(sp)
+    lw s3, 4(sp)
+    lw s0, 0(sp)
+    lw s1, 4(sp)
+    mv a0, s2
+    mv a1, s3
+    mv a2, s0
+    mv a3, s1
+    jal ra, dot
+    mv s0, a0
+    add s1, s4, s0
+    lw s0, 8(sp)
+    add s1, s1, s0
+    li a0, 10
+    jal ra, fib
+    mv s0, a0
+    li t1, 10000
+    mul s0, s0, t1
+    add s0, s1, s0
+    mv a0, s0
+    j main_end
+main_end:
+    lw ra, 16(sp)
+    lw s0, 20(sp)
+    lw s1, 24(sp)
+    lw s2, 28(sp)
+    lw s3, 32(sp)
+    lw s4, 36(sp)
+    addi sp, sp, 48
+    ret
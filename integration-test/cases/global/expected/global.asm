@@ -0,0 +1,105 @@
+.section .text
+.global bump
+bump:
+bump_entry:
+    la t1, counter
+    lw t2, 0(t1)
+    add t2, t2, a0
+    la t1, counter
+    sw t2, 0(t1)
+    la t1, counter
+    lw t2, 0(t1)
+    mv a0, t2
+    ret
+.global main
+main:
+    addi sp, sp, -32
+    sw ra, 0(sp)
+    sw s0, 4(sp)
+    sw s1, 8(sp)
+    sw s2, 12(sp)
+    sw s3, 16(sp)
+    sw s4, 20(sp)
+main_entry:
+    li s0, 0
+    j loop_0_condition
+loop_0_condition:
+    li t1, 10
+    slt s1, s0, t1
+    li t1, 0
+    bne s1, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    mv a0, s0
+    jal ra, bump
+    mv s1, a0
+    li t1, 1
+    add s0, s0, t1
+    j loop_0_condition
+loop_0_fail:
+    la t1, counter
+    lw s4, 0(t1)
+    la t1, p
+    lw s2, 0(t1)
+    lw s3, 4(t1)
+    mv s0, s4
+    mv s1, s3
+    la t1, p
+    sw s0, 0(t1)
+    sw s1, 4(t1)
+    la t1, small
+    lbu s4, 0(t1)
+    la t1, p
+    lw s2, 0(t1)
+    lw s3, 4(t1)
+    mv s0, s2
+    mv s1, s4
+    la t1, p
+    sw s0, 0(t1)
+    sw s1, 4(t1)
+    la t1, small
+    lbu s0, 0(t1)
+    li t1, 1
+    add s0, s0, t1
+    la t1, small
+    sb s0, 0(t1)
+    la t1, p
+    lw s0, 0(t1)
+    lw s1, 4(t1)
+    mv s2, s0
+    la t1, p
+    lw s0, 0(t1)
+    lw s1, 4(t1)
+    mv s0, s1
+    add s1, s2, s0
+    la t1, small
+    lbu s0, 0(t1)
+    add s0, s1, s0
+    li t1, 1000
+    add s0, s0, t1
+    mv a0, s0
+    j main_end
+main_end:
+    lw ra, 0(sp)
+    lw s0, 4(sp)
+    lw s1, 8(sp)
+    lw s2, 12(sp)
+    lw s3, 16(sp)
+    lw s4, 20(sp)
+    addi sp, sp, 32
+    ret
+.section .data
+.global counter
+counter:
+    .word 5
+.global small
+small:
+    .byte 7
+    .zero 3
+.section .bss
+.global big
+big:
+    .zero 8
+.global p
+p:
+    .zero 8
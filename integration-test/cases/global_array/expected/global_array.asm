@@ -0,0 +1,71 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -16
+main_entry:
+    li t0, 0
+    sw t0, 0(sp)
+    j loop_0_condition
+loop_0_condition:
+    lw t2, 0(sp)
+    li t1, 6
+    slt t2, t2, t1
+    li t1, 0
+    bne t2, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    lw t2, 0(sp)
+    li t1, 3
+    mul t3, t2, t1
+    lw t2, 0(sp)
+    mv t0, t2
+    la t1, buf
+    add t2, t1, t0
+    sb t3, 0(t2)
+    lw t2, 0(sp)
+    li t1, 1
+    add t2, t2, t1
+    sw t2, 0(sp)
+    j loop_0_condition
+loop_0_fail:
+    la t0, buf
+    addi t2, t0, 5
+    lbu t2, 0(t2)
+    la t0, table
+    addi t3, t0, 12
+    addi t3, t3, 8
+    sw t2, 0(t3)
+    la t0, buf
+    addi t2, t0, 1
+    lbu t4, 0(t2)
+    la t0, table
+    addi t3, t0, 0
+    lw t2, 0(sp)
+    li t1, 6
+    sub t2, t2, t1
+    slli t0, t2, 2
+    add t2, t3, t0
+    sw t4, 0(t2)
+    la t0, table
+    addi t2, t0, 12
+    addi t2, t2, 8
+    lw t2, 0(t2)
+    li t1, 100
+    mul t3, t2, t1
+    la t0, table
+    addi t2, t0, 0
+    addi t2, t2, 0
+    lw t2, 0(t2)
+    add t2, t3, t2
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 16
+    ret
+.section .bss
+.global buf
+buf:
+    .zero 8
+.global table
+table:
+    .zero 24
@@ -0,0 +1,30 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -16
+main_entry:
+    li t0, 17
+    neg t2, t0
+    sw t2, 0(sp)
+    li t0, 5
+    sw t0, 4(sp)
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    mul t4, t3, t2
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    div t2, t3, t2
+    li t1, 100
+    mul t2, t2, t1
+    add t2, t4, t2
+    lw t4, 0(sp)
+    lw t3, 4(sp)
+    rem t3, t4, t3
+    li t1, 10000
+    mul t3, t3, t1
+    add t2, t2, t3
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 16
+    ret
@@ -0,0 +1,38 @@
+.section .text
+.global main
+main:
+main_entry:
+    li t4, 0
+    li t5, 0
+    j loop_0_condition
+loop_0_condition:
+    li t1, 4
+    slt t2, t5, t1
+    li t1, 0
+    bne t2, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    li t2, 0
+    j loop_1_condition
+loop_1_condition:
+    li t1, 3
+    slt t3, t2, t1
+    li t1, 0
+    bne t3, t1, loop_1_success
+    j loop_1_fail
+loop_1_success:
+    li t1, 2
+    mul t3, t2, t1
+    add t3, t4, t3
+    li t1, 1
+    add t4, t3, t1
+    li t1, 1
+    add t2, t2, t1
+    j loop_1_condition
+loop_1_fail:
+    li t1, 1
+    add t5, t5, t1
+    j loop_0_condition
+loop_0_fail:
+    mv a0, t4
+    ret
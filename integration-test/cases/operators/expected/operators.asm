@@ -0,0 +1,94 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -32
+main_entry:
+    li t0, 5
+    sw t0, 0(sp)
+    li t0, 3
+    sw t0, 4(sp)
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    add t3, t3, t2
+    lw t2, 4(sp)
+    not t2, t2
+    xor t2, t3, t2
+    sw t2, 8(sp)
+    li t0, 1
+    sw t0, 12(sp)
+    li t0, 0
+    sw t0, 16(sp)
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    sub t2, t3, t2
+    snez t2, t2
+    li t1, 0
+    bne t2, t1, logic_1_rhs
+    j logic_1_end
+logic_1_rhs:
+    lw t3, 4(sp)
+    lw t2, 0(sp)
+    slt t2, t2, t3
+    xori t2, t2, 1
+    li t1, 0
+    sub t2, t2, t1
+    snez t2, t2
+    sw t2, 16(sp)
+    j logic_1_end
+logic_1_end:
+    lw t2, 16(sp)
+    li t1, 0
+    bne t2, t1, logic_0_end
+    j logic_0_rhs
+logic_0_rhs:
+    lw t2, 0(sp)
+    li t1, 10
+    slt t2, t1, t2
+    li t1, 0
+    sub t2, t2, t1
+    snez t2, t2
+    sw t2, 12(sp)
+    j logic_0_end
+logic_0_end:
+    lw t2, 12(sp)
+    li t1, 0
+    bne t2, t1, if_0_success
+    j if_0_fail
+if_0_success:
+    lw t4, 8(sp)
+    lw t2, 0(sp)
+    li t1, 2
+    sll t3, t2, t1
+    lw t2, 4(sp)
+    li t1, 1
+    sra t2, t2, t1
+    and t2, t3, t2
+    or t2, t4, t2
+    sw t2, 8(sp)
+    j if_0_end
+if_0_fail:
+    j if_0_end
+if_0_end:
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    slt t2, t3, t2
+    xori t2, t2, 1
+    li t1, 0
+    sub t2, t2, t1
+    seqz t2, t2
+    li t1, 0
+    bne t2, t1, if_1_success
+    j if_1_fail
+if_1_success:
+    li t0, 0
+    sw t0, 8(sp)
+    j if_1_end
+if_1_fail:
+    j if_1_end
+if_1_end:
+    lw t2, 8(sp)
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 32
+    ret
@@ -0,0 +1,69 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -32
+main_entry:
+    li t0, 0
+    sw t0, 20(sp)
+    j loop_0_condition
+loop_0_condition:
+    lw t2, 20(sp)
+    li t1, 4
+    slt t2, t2, t1
+    li t1, 0
+    bne t2, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    lw t2, 20(sp)
+    li t1, 3
+    mul t2, t2, t1
+    li t1, 1
+    add t4, t2, t1
+    addi t3, sp, 4
+    lw t2, 20(sp)
+    slli t0, t2, 2
+    add t2, t3, t0
+    sw t4, 0(t2)
+    lw t2, 20(sp)
+    li t1, 1
+    add t2, t2, t1
+    sw t2, 20(sp)
+    j loop_0_condition
+loop_0_fail:
+    lw a2, 0(sp)
+    lw a3, 4(sp)
+    lw a4, 8(sp)
+    lw a5, 12(sp)
+    lw a6, 16(sp)
+    li t1, 4
+    mv t2, t1
+    mv t3, a3
+    mv t4, a4
+    mv t5, a5
+    mv t6, a6
+    sw t2, 0(sp)
+    sw t3, 4(sp)
+    sw t4, 8(sp)
+    sw t5, 12(sp)
+    sw t6, 16(sp)
+    addi t2, sp, 4
+    addi t2, t2, 8
+    lw t2, 0(t2)
+    li t1, 100
+    mul t3, t2, t1
+    addi t2, sp, 4
+    addi t2, t2, 12
+    lw t2, 0(t2)
+    add a2, t3, t2
+    lw t2, 0(sp)
+    lw t3, 4(sp)
+    lw t4, 8(sp)
+    lw t5, 12(sp)
+    lw t6, 16(sp)
+    mv t2, t2
+    add t2, a2, t2
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 32
+    ret
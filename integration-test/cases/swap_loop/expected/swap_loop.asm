@@ -0,0 +1,27 @@
+.section .text
+.global main
+main:
+main_entry:
+    li t4, 0
+    li t2, 2
+    li t3, 1
+    j loop_0_condition
+loop_0_condition:
+    li t1, 3
+    slt t5, t4, t1
+    li t1, 0
+    bne t5, t1, loop_0_success
+    j loop_0_fail
+loop_0_success:
+    li t1, 1
+    add t4, t4, t1
+    mv t1, t2
+    mv t2, t3
+    mv t3, t1
+    j loop_0_condition
+loop_0_fail:
+    li t1, 10
+    mul t3, t3, t1
+    add t2, t3, t2
+    mv a0, t2
+    ret
@@ -0,0 +1,47 @@
+.section .text
+.global main
+main:
+    addi sp, sp, -16
+main_entry:
+    li t0, 4294967295
+    sw t0, 0(sp)
+    li t0, 1
+    sw t0, 4(sp)
+    li t0, 0
+    sw t0, 8(sp)
+    lw t3, 0(sp)
+    lw t2, 4(sp)
+    sltu t2, t2, t3
+    li t1, 0
+    bne t2, t1, if_0_success
+    j if_0_fail
+if_0_success:
+    lw t2, 8(sp)
+    li t1, 1
+    add t2, t2, t1
+    sw t2, 8(sp)
+    j if_0_end
+if_0_fail:
+    j if_0_end
+if_0_end:
+    lw t3, 4(sp)
+    lw t2, 0(sp)
+    sltu t2, t3, t2
+    li t1, 0
+    bne t2, t1, if_1_success
+    j if_1_fail
+if_1_success:
+    lw t2, 8(sp)
+    li t1, 10
+    add t2, t2, t1
+    sw t2, 8(sp)
+    j if_1_end
+if_1_fail:
+    j if_1_end
+if_1_end:
+    lw t2, 8(sp)
+    mv a0, t2
+    j main_end
+main_end:
+    addi sp, sp, 16
+    ret
@@ -0,0 +1,110 @@
+use std::fmt::{self, Display};
+
+/// [`CompileError`] represents an error found in the source code, with the position it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    /// Path of the source file, if known.
+    pub file: Option<String>,
+    /// Line where the error happened, starts from 1.
+    pub line: usize,
+    /// Column where the error happened, starts from 1, counted in chars.
+    pub column: usize,
+    /// Content of the line where the error happened.
+    pub source_line: String,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl CompileError {
+    /// Create a [`CompileError`] which happens at `offset` bytes in `source`.
+    pub fn at_offset(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|it| it + 1).unwrap_or(0);
+        let line_end = source[offset..]
+            .find('\n')
+            .map(|it| it + offset)
+            .unwrap_or(source.len());
+        Self {
+            file: None,
+            line: before.matches('\n').count() + 1,
+            column: source[line_start..offset].chars().count() + 1,
+            source_line: source[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Create a [`CompileError`] which happens at the beginning of `rest`, `rest` must be a suffix of `source`.
+    pub fn at_rest(source: &str, rest: &str, message: impl Into<String>) -> Self {
+        Self::at_offset(source, source.len() - rest.len(), message)
+    }
+
+    /// Attach the source file path to this error.
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter_width = self.line.to_string().len();
+        let gutter = " ".repeat(gutter_width);
+        // keep tabs in the caret line, so the caret is aligned with the source line
+        let caret_padding: String = self
+            .source_line
+            .chars()
+            .take(self.column - 1)
+            .map(|it| if it == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(f, "error: {}", self.message)?;
+        writeln!(
+            f,
+            "{gutter}--> {}:{}:{}",
+            self.file.as_deref().unwrap_or("<source>"),
+            self.line,
+            self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source_line)?;
+        write!(f, "{gutter} | {caret_padding}^")
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position() {
+        let source = "fn main() -> () {\n    let a: i32 = 1;\n    a = ;\n}";
+        let offset = source.find(";\n}").unwrap();
+        let error = CompileError::at_offset(source, offset, "unexpected `;`");
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 9);
+        assert_eq!(error.source_line, "    a = ;");
+        let error = CompileError::at_offset(source, 0, "unexpected `fn`");
+        assert_eq!(error.line, 1);
+        assert_eq!(error.column, 1);
+        let error = CompileError::at_rest(source, "}", "unexpected `}`");
+        assert_eq!(error.line, 4);
+        assert_eq!(error.column, 1);
+        assert_eq!(error.source_line, "}");
+    }
+
+    #[test]
+    fn render() {
+        let source = "fn main() -> () {\n    a = ;\n}";
+        let offset = source.find(';').unwrap();
+        let error =
+            CompileError::at_offset(source, offset, "unexpected `;`").with_file("main.come");
+        assert_eq!(
+            format!("{error}"),
+            "error: unexpected `;`\n --> main.come:2:9\n  |\n2 |     a = ;\n  |         ^"
+        );
+    }
+}
@@ -8,8 +8,11 @@ use self::{
     function_definition::FunctionDefinition, global_definition::VariableDefinition,
     type_definition::TypeDefinition,
 };
+pub use error::CompileError;
 pub use statement::expression;
 
+/// Error type and diagnostic rendering for the source code.
+pub mod error;
 /// Data structure and parser for a function definition.
 pub mod function_definition;
 /// Data structure and parser for a global variable definition.
@@ -40,8 +43,76 @@ pub fn parse(code: &str) -> IResult<&str, ASTNode> {
 /// `Ast` is the root node of the ast.
 pub type Ast = Vec<ASTNode>;
 
+/// Find where parsing a top level node starting from `rest` fails.
+/// Each kind of node is tried separately, and the one which goes furthest wins, since
+/// it is most likely to be what the user intended to write.
+fn furthest_failure(rest: &str) -> &str {
+    fn failed_at<T>(result: IResult<&str, T>) -> Option<&str> {
+        match result {
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => Some(error.input),
+            _ => None,
+        }
+    }
+    [
+        failed_at(type_definition::parse(rest)),
+        failed_at(function_definition::parse(rest)),
+        failed_at(global_definition::parse(rest)),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|it| it.trim_start())
+    .min_by_key(|it| it.len())
+    .unwrap_or(rest)
+}
+
 /// Parse source code to get a [`Ast`].
-pub fn from_source(source: &str) -> IResult<&str, Ast> {
-    // todo: Maybe we should use our own error type, and handle the situation that the remain code is not empty.
-    many0(delimited(multispace0, parse, multispace0))(source)
+pub fn from_source(source: &str) -> Result<Ast, CompileError> {
+    let (rest, ast) =
+        many0(delimited(multispace0, parse, multispace0))(source).map_err(|error| {
+            let rest = match error {
+                nom::Err::Error(error) | nom::Err::Failure(error) => error.input,
+                nom::Err::Incomplete(_) => "",
+            };
+            CompileError::at_rest(source, rest, "failed to parse")
+        })?;
+    if rest.is_empty() {
+        return Ok(ast);
+    }
+    let failed_at = furthest_failure(rest);
+    let message = match failed_at.split_whitespace().next() {
+        Some(token) => format!(
+            "unexpected `{}`",
+            token.chars().take(16).collect::<String>()
+        ),
+        None => "unexpected end of file".to_string(),
+    };
+    Err(CompileError::at_rest(source, failed_at, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_source_ok() {
+        let ast =
+            from_source("struct S {\n    a: i32\n}\nfn main() -> () {\n    let a: i32 = 1;\n}\n")
+                .unwrap();
+        assert_eq!(ast.len(), 2);
+    }
+
+    #[test]
+    fn from_source_remaining() {
+        let source = "fn main() -> () {\n    let a: i32 = 1;\n    a = ;\n}\n";
+        let error = from_source(source).unwrap_err();
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 5);
+        assert_eq!(error.message, "unexpected `a`");
+
+        let source = "fn main() -> () {}\n@@@";
+        let error = from_source(source).unwrap_err();
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 1);
+        assert_eq!(error.message, "unexpected `@@@`");
+    }
 }
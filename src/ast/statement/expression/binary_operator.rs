@@ -1,5 +1,5 @@
 use super::rvalue::RValue;
-use nom::{branch::alt, IResult};
+use nom::{bytes::complete::tag, combinator::not, sequence::terminated, IResult};
 use paste::paste;
 
 /// [`BinaryOperatorResult`] represents result of a binary operator.
@@ -13,6 +13,12 @@ pub struct BinaryOperatorResult {
     pub rhs: Box<RValue>,
 }
 
+/// Parse an operator which is a single char, and is not the prefix of the doubled one,
+/// eg. `&` but not the first char of `&&`.
+fn single<'a>(op: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(op), not(tag(op)))
+}
+
 mod level3 {
     use super::*;
     use crate::{
@@ -34,24 +40,21 @@ mod level3 {
         ))(code)
     }
 
-    pub fn parse(code: &str) -> IResult<&str, BinaryOperatorResult> {
+    /// Parse an expression which contains operators of this level or higher.
+    pub fn parse(code: &str) -> IResult<&str, RValue> {
         let (rest, lhs) = higher_than_level3(code)?;
-        let (rest, operator) = parsing::in_multispace(alt((tag("*"), tag("/"))))(rest)?;
-        let (rest, rhs) = higher_than_level3(rest)?;
         fold_many0(
             pair(
-                parsing::in_multispace(alt((tag("*"), tag("/")))),
+                parsing::in_multispace(alt((tag("*"), tag("/"), tag("%")))),
                 higher_than_level3,
             ),
-            move || BinaryOperatorResult {
-                operator: operator.to_string(),
-                lhs: Box::new(lhs.clone()),
-                rhs: Box::new(rhs.clone()),
-            },
-            |lhs, (operator, rhs)| BinaryOperatorResult {
-                operator: operator.to_string(),
-                lhs: Box::new(RValue::BinaryOperatorResult(lhs)),
-                rhs: Box::new(rhs),
+            move || lhs.clone(),
+            |lhs, (operator, rhs)| {
+                RValue::BinaryOperatorResult(BinaryOperatorResult {
+                    operator: operator.to_string(),
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                })
             },
         )(rest)
     }
@@ -60,46 +63,39 @@ mod level3 {
 /// A macro for generating binary operator parsers.
 /// The `level` here are from C's operator precedence.
 macro_rules! bin_op_level {
-    ($n: expr, $n_minus_1: expr, $($op: expr)*) => {
+    ($n: expr, $n_minus_1: expr, $($op: expr),*) => {
         paste! {
         mod [<level $n>] {
             use super::*;
             use nom::{
-                branch::alt, bytes::complete::tag, combinator::map, multi::fold_many0,
-                sequence::pair, IResult,
+                branch::alt, multi::fold_many0, sequence::pair, IResult,
             };
             use crate::{
-                ast::expression::{rvalue::RValue, binary_operator::[<level $n_minus_1>]::{self, [<higher_than_level $n_minus_1>]}},
+                ast::expression::{rvalue::RValue, binary_operator::[<level $n_minus_1>]},
                 utility::parsing,
             };
 
             pub(in crate::ast::expression) fn [<higher_than_level $n>](
                 code: &str,
             ) -> IResult<&str, RValue> {
-                alt((
-                    map([<level $n_minus_1>]::parse, RValue::BinaryOperatorResult),
-                    [<higher_than_level $n_minus_1>],
-                ))(code)
+                [<level $n_minus_1>]::parse(code)
             }
 
-            pub fn parse(code: &str) -> IResult<&str, BinaryOperatorResult> {
+            /// Parse an expression which contains operators of this level or higher.
+            pub fn parse(code: &str) -> IResult<&str, RValue> {
                 let (rest, lhs) = [<higher_than_level $n>](code)?;
-                let (rest, operator) = parsing::in_multispace(alt(($(tag($op),)*)))(rest)?;
-                let (rest, rhs) = [<higher_than_level $n>](rest)?;
                 fold_many0(
                     pair(
-                        parsing::in_multispace(alt(($(tag($op),)*))),
+                        parsing::in_multispace(alt(($($op,)*))),
                         [<higher_than_level $n>],
                     ),
-                    move || BinaryOperatorResult {
-                        operator: operator.to_string(),
-                        lhs: Box::new(lhs.clone()),
-                        rhs: Box::new(rhs.clone()),
-                    },
-                    |lhs, (operator, rhs)| BinaryOperatorResult {
-                        operator: operator.to_string(),
-                        lhs: Box::new(RValue::BinaryOperatorResult(lhs)),
-                        rhs: Box::new(rhs),
+                    move || lhs.clone(),
+                    |lhs, (operator, rhs)| {
+                        RValue::BinaryOperatorResult(BinaryOperatorResult {
+                            operator: operator.to_string(),
+                            lhs: Box::new(lhs),
+                            rhs: Box::new(rhs),
+                        })
                     },
                 )(rest)
             }
@@ -108,41 +104,79 @@ macro_rules! bin_op_level {
     };
 }
 
-bin_op_level!(4, 3, "+" "-");
-bin_op_level!(5, 4, "<<" ">>");
-bin_op_level!(6, 5, "<=" "<" ">=" ">");
-bin_op_level!(7, 6, "==" "!=");
-bin_op_level!(8, 7, "&" "&");
-bin_op_level!(9, 8, "^" "^");
-bin_op_level!(10, 9, "|" "|");
+bin_op_level!(4, 3, tag("+"), tag("-"));
+bin_op_level!(5, 4, tag("<<"), tag(">>"));
+bin_op_level!(6, 5, tag("<="), tag("<"), tag(">="), tag(">"));
+bin_op_level!(7, 6, tag("=="), tag("!="));
+bin_op_level!(8, 7, single("&"));
+bin_op_level!(9, 8, tag("^"));
+bin_op_level!(10, 9, single("|"));
+bin_op_level!(11, 10, tag("&&"));
+bin_op_level!(12, 11, tag("||"));
 
-/// Parse source code to get a [`BinaryOperatorResult`].
-pub fn parse(code: &str) -> IResult<&str, BinaryOperatorResult> {
-    alt((
-        level10::parse,
-        level9::parse,
-        level8::parse,
-        level7::parse,
-        level6::parse,
-        level5::parse,
-        level4::parse,
-        level3::parse,
-    ))(code)
+/// Parse source code to get an [`RValue`], which is a [`BinaryOperatorResult`] if there are
+/// binary operators at the top level.
+///
+/// Returning the operand itself when there is no operator, instead of failing, saves the caller
+/// from parsing it again.
+pub fn parse(code: &str) -> IResult<&str, RValue> {
+    level12::parse(code)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse_binary(code: &str) -> BinaryOperatorResult {
+        match parse(code).unwrap().1 {
+            RValue::BinaryOperatorResult(it) => it,
+            other => panic!("expected a binary operator result, got {other:?}"),
+        }
+    }
+
     #[test]
     pub fn can_parse() {
-        let bin_op = parse("s.a + s.b").unwrap().1;
+        let bin_op = parse_binary("s.a + s.b");
         assert_eq!(bin_op.operator, "+");
-        let bin_op = parse("a+b*c").unwrap().1;
+        let bin_op = parse_binary("a+b*c");
         assert_eq!(bin_op.operator, "+");
-        let bin_op = parse("b*c+d").unwrap().1;
+        let bin_op = parse_binary("b*c+d");
         assert_eq!(bin_op.operator, "+");
-        let bin_op = parse("!b+d").unwrap().1;
+        let bin_op = parse_binary("!b+d");
         assert_eq!(bin_op.operator, "+");
+        let bin_op = parse_binary("(a+b)*c");
+        assert_eq!(bin_op.operator, "*");
+        let bin_op = parse_binary("a % b * c");
+        assert_eq!(bin_op.operator, "*");
+        let bin_op = parse_binary("a - b - c");
+        assert_eq!(bin_op.operator, "-");
+        assert!(matches!(
+            bin_op.lhs.as_ref(),
+            RValue::BinaryOperatorResult(BinaryOperatorResult { operator, .. }) if operator == "-"
+        ));
+    }
+
+    #[test]
+    pub fn can_parse_logical() {
+        let bin_op = parse_binary("a || b && c");
+        assert_eq!(bin_op.operator, "||");
+        let bin_op = parse_binary("a && b || c");
+        assert_eq!(bin_op.operator, "||");
+        let bin_op = parse_binary("a & b && c | d");
+        assert_eq!(bin_op.operator, "&&");
+        assert!(matches!(
+            bin_op.lhs.as_ref(),
+            RValue::BinaryOperatorResult(BinaryOperatorResult { operator, .. }) if operator == "&"
+        ));
+        assert!(matches!(
+            bin_op.rhs.as_ref(),
+            RValue::BinaryOperatorResult(BinaryOperatorResult { operator, .. }) if operator == "|"
+        ));
+        let bin_op = parse_binary("a != b");
+        assert_eq!(bin_op.operator, "!=");
+        let bin_op = parse_binary("a << 1 < b >> 2");
+        assert_eq!(bin_op.operator, "<");
+        let bin_op = parse_binary("a ^ b & c");
+        assert_eq!(bin_op.operator, "^");
     }
 }
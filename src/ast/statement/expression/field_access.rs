@@ -1,6 +1,5 @@
-use super::{variable_ref, LValue};
-use crate::utility::parsing;
-use nom::{bytes::complete::tag, combinator::map, multi::fold_many0, sequence::preceded, IResult};
+use super::{lvalue, LValue};
+use nom::{combinator::map_opt, IResult};
 
 /// [`FieldAccess`] represents result of accessing field in a struct.
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
@@ -11,30 +10,18 @@ pub struct FieldAccess {
     pub name: String,
 }
 
-pub fn higher_than_field_access(code: &str) -> IResult<&str, LValue> {
-    map(variable_ref::parse, LValue::VariableRef)(code)
-}
-
 /// Parse source code to get a [`FieldAccess`].
 pub fn parse(code: &str) -> IResult<&str, FieldAccess> {
-    let (rest, first) = higher_than_field_access(code)?;
-    let (rest, second) = preceded(tag("."), parsing::ident)(rest)?;
-    fold_many0(
-        preceded(parsing::in_multispace(tag(".")), parsing::ident),
-        move || FieldAccess {
-            from: Box::new(first.clone()),
-            name: second.clone(),
-        },
-        |acc, next| FieldAccess {
-            from: Box::new(LValue::FieldAccess(acc)),
-            name: next,
-        },
-    )(rest)
+    map_opt(lvalue::parse, |lvalue| match lvalue {
+        LValue::FieldAccess(field_access) => Some(field_access),
+        _ => None,
+    })(code)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ast::expression::variable_ref;
 
     #[test]
     pub fn can_parse() {
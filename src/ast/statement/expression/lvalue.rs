@@ -1,10 +1,19 @@
 use super::{
-    field_access::{self, FieldAccess},
-    rvalue::RValue,
+    field_access::FieldAccess,
+    rvalue::{self, RValue},
+    subscript::Subscript,
     variable_ref::{self, VariableRef},
 };
+use crate::utility::parsing;
 use enum_dispatch::enum_dispatch;
-use nom::{branch::alt, combinator::map, IResult};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::map,
+    multi::fold_many0,
+    sequence::{delimited, preceded},
+    IResult,
+};
 
 /// Tag trait for [`LValue`].
 #[enum_dispatch]
@@ -16,14 +25,43 @@ trait IsLValue {}
 pub enum LValue {
     VariableRef,
     FieldAccess,
+    Subscript,
+}
+
+/// Things which can follow an [`LValue`] to get another [`LValue`].
+enum Postfix {
+    /// `.field`
+    Field(String),
+    /// `[index]`
+    Index(RValue),
 }
 
 /// Parse source code to get a [`LValue`].
 pub fn parse(code: &str) -> IResult<&str, LValue> {
-    alt((
-        map(field_access::parse, LValue::FieldAccess),
-        map(variable_ref::parse, LValue::VariableRef),
-    ))(code)
+    let (rest, root) = variable_ref::parse(code)?;
+    fold_many0(
+        alt((
+            map(
+                preceded(parsing::in_multispace(tag(".")), parsing::ident),
+                Postfix::Field,
+            ),
+            map(
+                delimited(tag("["), parsing::in_multispace(rvalue::parse), tag("]")),
+                Postfix::Index,
+            ),
+        )),
+        move || LValue::VariableRef(root.clone()),
+        |from, postfix| match postfix {
+            Postfix::Field(name) => LValue::FieldAccess(FieldAccess {
+                from: Box::new(from),
+                name,
+            }),
+            Postfix::Index(index) => LValue::Subscript(Subscript {
+                from: Box::new(from),
+                index: Box::new(index),
+            }),
+        },
+    )(rest)
 }
 
 impl TryFrom<RValue> for LValue {
@@ -33,6 +71,7 @@ impl TryFrom<RValue> for LValue {
         match rvalue {
             RValue::VariableRef(variable_ref) => Ok(LValue::VariableRef(variable_ref)),
             RValue::FieldAccess(field_access) => Ok(LValue::FieldAccess(field_access)),
+            RValue::Subscript(subscript) => Ok(LValue::Subscript(subscript)),
             _ => Err(()),
         }
     }
@@ -61,5 +100,10 @@ mod tests {
                 name: "b".to_string(),
             })
         );
+        let lvalue = parse("a[0].b").unwrap().1;
+        assert!(matches!(
+            lvalue,
+            LValue::FieldAccess(FieldAccess { ref from, .. }) if matches!(from.as_ref(), LValue::Subscript(_))
+        ));
     }
 }
@@ -8,11 +8,12 @@ pub mod function_call;
 pub mod in_brackets;
 /// An integer literal.
 pub mod integer_literal;
+/// Result of accessing an element in an array.
+pub mod subscript;
 /// Result of a unary operator.
 pub mod unary_operator;
 /// Refer to a variable.
 pub mod variable_ref;
-// todo: mod subscripting;
 
 /// Enumeration of all expressions which can be assigned to.
 pub mod lvalue;
@@ -26,5 +27,6 @@ pub use in_brackets::InBrackets;
 pub use integer_literal::IntegerLiteral;
 pub use lvalue::LValue;
 pub use rvalue::RValue;
+pub use subscript::Subscript;
 pub use unary_operator::UnaryOperatorResult;
 pub use variable_ref::VariableRef;
@@ -2,14 +2,15 @@ use super::{
     binary_operator::{self, BinaryOperatorResult},
     field_access::FieldAccess,
     function_call::FunctionCall,
-    in_brackets::{self, InBrackets},
+    in_brackets::InBrackets,
     integer_literal::IntegerLiteral,
     lvalue::LValue,
-    unary_operator::{self, UnaryOperatorResult},
+    subscript::Subscript,
+    unary_operator::UnaryOperatorResult,
     variable_ref::VariableRef,
 };
 use enum_dispatch::enum_dispatch;
-use nom::{branch::alt, combinator::map, IResult};
+use nom::IResult;
 
 /// Tag trait for [`RValue`].
 #[enum_dispatch]
@@ -24,18 +25,14 @@ pub enum RValue {
     InBrackets,
     FieldAccess,
     FunctionCall,
+    Subscript,
     UnaryOperatorResult,
     BinaryOperatorResult,
 }
 
 /// Parse source code to get a [`RValue`].
 pub fn parse(code: &str) -> IResult<&str, RValue> {
-    alt((
-        map(in_brackets::parse, RValue::InBrackets),
-        map(binary_operator::parse, RValue::BinaryOperatorResult),
-        map(unary_operator::parse, RValue::UnaryOperatorResult),
-        unary_operator::higher_than_unary_operator_result,
-    ))(code)
+    binary_operator::parse(code)
 }
 
 impl From<LValue> for RValue {
@@ -43,6 +40,7 @@ impl From<LValue> for RValue {
         match lvalue {
             LValue::VariableRef(variable_ref) => RValue::VariableRef(variable_ref),
             LValue::FieldAccess(field_access) => RValue::FieldAccess(field_access),
+            LValue::Subscript(subscript) => RValue::Subscript(subscript),
         }
     }
 }
@@ -65,6 +63,9 @@ mod tests {
         let rvalue = super::parse("a.b").unwrap().1;
         assert!(matches!(rvalue, RValue::FieldAccess(_)));
 
+        let rvalue = super::parse("a[i]").unwrap().1;
+        assert!(matches!(rvalue, RValue::Subscript(_)));
+
         let rvalue = super::parse("f(a, b, c)").unwrap().1;
         assert!(matches!(rvalue, RValue::FunctionCall(_)));
 
@@ -73,5 +74,26 @@ mod tests {
 
         let rvalue = super::parse("a + b").unwrap().1;
         assert!(matches!(rvalue, RValue::BinaryOperatorResult(_)));
+
+        let rvalue = super::parse("(a + b) * c").unwrap().1;
+        assert!(matches!(rvalue, RValue::BinaryOperatorResult(_)));
+
+        let rvalue = super::parse("~(a | b)").unwrap().1;
+        assert!(matches!(rvalue, RValue::UnaryOperatorResult(_)));
+    }
+
+    #[test]
+    fn parse_deeply_nested_brackets() {
+        let depth = 30;
+        let code = format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        let (rest, mut rvalue) = super::parse(&code).unwrap();
+        assert!(rest.is_empty());
+        for _ in 0..depth {
+            let RValue::InBrackets(InBrackets(inner)) = rvalue else {
+                panic!("expected brackets");
+            };
+            rvalue = *inner;
+        }
+        assert!(matches!(rvalue, RValue::IntegerLiteral(_)));
     }
 }
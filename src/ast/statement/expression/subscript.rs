@@ -0,0 +1,46 @@
+use super::{lvalue, rvalue::RValue, LValue};
+use nom::{combinator::map_opt, IResult};
+
+/// [`Subscript`] represents accessing an element of an array.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct Subscript {
+    /// Which array to access from.
+    pub from: Box<LValue>,
+    /// Index of the element.
+    pub index: Box<RValue>,
+}
+
+/// Parse source code to get a [`Subscript`].
+pub fn parse(code: &str) -> IResult<&str, Subscript> {
+    map_opt(lvalue::parse, |lvalue| match lvalue {
+        LValue::Subscript(subscript) => Some(subscript),
+        _ => None,
+    })(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::expression::{FieldAccess, IntegerLiteral, VariableRef};
+
+    #[test]
+    pub fn can_parse() {
+        let result = parse("a[1]").unwrap().1;
+        assert_eq!(
+            result,
+            Subscript {
+                from: Box::new(LValue::VariableRef(VariableRef("a".to_string()))),
+                index: Box::new(IntegerLiteral(1).into()),
+            }
+        );
+        let result = parse("a[i + 1][j]").unwrap().1;
+        assert!(matches!(result.from.as_ref(), LValue::Subscript(_)));
+        assert_eq!(result.index.as_ref(), &VariableRef("j".to_string()).into());
+        let result = parse("s.buf[ i ]").unwrap().1;
+        assert!(matches!(
+            result.from.as_ref(),
+            LValue::FieldAccess(FieldAccess { name, .. }) if name == "buf"
+        ));
+        assert!(parse("a.b").is_err());
+    }
+}
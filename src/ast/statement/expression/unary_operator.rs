@@ -1,4 +1,4 @@
-use super::{field_access, function_call, integer_literal, rvalue::RValue, variable_ref};
+use super::{function_call, in_brackets, integer_literal, lvalue, rvalue::RValue};
 use nom::{branch::alt, bytes::complete::tag, combinator::map, sequence::tuple, IResult};
 
 /// [`UnaryOperatorResult`] represents result of a unary operator.
@@ -12,9 +12,9 @@ pub struct UnaryOperatorResult {
 
 pub fn higher_than_unary_operator_result(code: &str) -> IResult<&str, RValue> {
     alt((
+        map(in_brackets::parse, RValue::InBrackets),
         map(function_call::parse, RValue::FunctionCall),
-        map(field_access::parse, RValue::FieldAccess),
-        map(variable_ref::parse, RValue::VariableRef),
+        map(lvalue::parse, RValue::from),
         map(integer_literal::parse, RValue::IntegerLiteral),
     ))(code)
 }
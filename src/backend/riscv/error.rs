@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// Kinds of errors which can be found while assembling.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssembleErrorKind {
+    /// There is no instruction with this name.
+    UnknownInstruction(String),
+    /// The directive is not supported, or its arguments cannot be parsed.
+    InvalidDirective(String),
+    /// An instruction is given a wrong number of operands.
+    OperandCountMismatch {
+        instruction: String,
+        expected: usize,
+        found: usize,
+    },
+    /// The operand cannot be used at its place in the instruction.
+    InvalidOperand {
+        instruction: String,
+        operand: String,
+    },
+    /// The immediate cannot be held by its field in the instruction.
+    ImmediateOutOfRange { instruction: String, value: i64 },
+    /// The value of an expression cannot be decided while assembling.
+    NotConstant(String),
+    /// The offset given to a jump or branch instruction is not a multiple of 2.
+    MisalignedOffset { instruction: String, value: i64 },
+    /// A jump or branch target which is not aligned to 2 bytes.
+    MisalignedTarget(String),
+    /// A jump or branch target which is too far away from the instruction.
+    TargetOutOfRange(String),
+    /// A label is defined more than once in a section.
+    Redefinition(String),
+    /// A symbol is exported but not defined in the section.
+    UndefinedSymbol(String),
+    /// `%pcrel_lo(label)` is used, but there is no `%pcrel_hi` at `label`.
+    UnpairedPcrelLo(String),
+    /// Code or data is placed before any section directive.
+    OutsideSection,
+}
+
+impl fmt::Display for AssembleErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssembleErrorKind::UnknownInstruction(name) => {
+                write!(f, "unknown instruction `{name}`")
+            }
+            AssembleErrorKind::InvalidDirective(directive) => {
+                write!(f, "invalid directive `{directive}`")
+            }
+            AssembleErrorKind::OperandCountMismatch {
+                instruction,
+                expected,
+                found,
+            } => write!(
+                f,
+                "`{instruction}` takes {expected} operand(s) but {found} were given"
+            ),
+            AssembleErrorKind::InvalidOperand {
+                instruction,
+                operand,
+            } => write!(
+                f,
+                "`{operand}` cannot be used as an operand of `{instruction}`"
+            ),
+            AssembleErrorKind::ImmediateOutOfRange { instruction, value } => {
+                write!(f, "immediate `{value}` is out of range for `{instruction}`")
+            }
+            AssembleErrorKind::NotConstant(expression) => {
+                write!(f, "`{expression}` is not a constant")
+            }
+            AssembleErrorKind::MisalignedOffset { instruction, value } => write!(
+                f,
+                "branch offset `{value}` of `{instruction}` must be a multiple of 2"
+            ),
+            AssembleErrorKind::MisalignedTarget(symbol) => {
+                write!(f, "jump target `{symbol}` is not aligned to 2 bytes")
+            }
+            AssembleErrorKind::TargetOutOfRange(symbol) => {
+                write!(f, "jump target `{symbol}` is out of range")
+            }
+            AssembleErrorKind::Redefinition(name) => {
+                write!(f, "label `{name}` is defined more than once")
+            }
+            AssembleErrorKind::UndefinedSymbol(name) => {
+                write!(f, "exported symbol `{name}` is not defined")
+            }
+            AssembleErrorKind::UnpairedPcrelLo(label) => write!(
+                f,
+                "`%pcrel_lo({label})` is not paired with an `auipc` using `%pcrel_hi`"
+            ),
+            AssembleErrorKind::OutsideSection => write!(f, "expected a section directive first"),
+        }
+    }
+}
+
+/// [`AssembleError`] represents an error found in an asm file, with the line it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    /// Path of the asm file, if known.
+    pub file: Option<String>,
+    /// Line where the error happened, starts from 1.
+    pub line: usize,
+    /// What is wrong.
+    pub kind: AssembleErrorKind,
+}
+
+impl AssembleError {
+    pub fn new(line: usize, kind: AssembleErrorKind) -> Self {
+        Self {
+            file: None,
+            line,
+            kind,
+        }
+    }
+
+    /// Attach the asm file path to this error.
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: error: {}",
+            self.file.as_deref().unwrap_or("<source>"),
+            self.line,
+            self.kind
+        )
+    }
+}
+
+impl std::error::Error for AssembleError {}
@@ -0,0 +1,357 @@
+use std::{collections::HashMap, fmt};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{anychar, none_of},
+    combinator::map,
+    multi::many0,
+    sequence::{delimited, pair, preceded},
+    IResult,
+};
+
+use super::simple_instruction::param::parse_symbol;
+use crate::utility::parsing::{self, in_multispace};
+
+/// Binary operators, from the highest precedence to the lowest.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BinaryOperator {
+    Multiply,
+    Add,
+    Subtract,
+    ShiftLeft,
+    ShiftRight,
+    And,
+    Or,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum UnaryOperator {
+    Negative,
+    Not,
+}
+
+/// A constant expression in asm code.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expression {
+    Integer(i64),
+    Symbol(String),
+    /// `.`, the current location.
+    Location,
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryOperator::Multiply => write!(f, "*"),
+            BinaryOperator::Add => write!(f, "+"),
+            BinaryOperator::Subtract => write!(f, "-"),
+            BinaryOperator::ShiftLeft => write!(f, "<<"),
+            BinaryOperator::ShiftRight => write!(f, ">>"),
+            BinaryOperator::And => write!(f, "&"),
+            BinaryOperator::Or => write!(f, "|"),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Integer(value) => write!(f, "{value}"),
+            Expression::Symbol(name) => write!(f, "{name}"),
+            Expression::Location => write!(f, "."),
+            Expression::Unary(UnaryOperator::Negative, operand) => write!(f, "-{operand}"),
+            Expression::Unary(UnaryOperator::Not, operand) => write!(f, "~{operand}"),
+            Expression::Binary(operator, lhs, rhs) => write!(f, "({lhs} {operator} {rhs})"),
+        }
+    }
+}
+
+/// The value of an [`Expression`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Value {
+    Constant(i64),
+    /// Address of a symbol plus an addend, which is decided while linking.
+    Symbol {
+        name: String,
+        addend: i64,
+    },
+}
+
+/// The section being assembled.
+#[derive(Debug, Clone, Copy)]
+pub struct SectionContext<'a> {
+    pub name: &'a str,
+    /// Offsets of the labels defined so far in this section.
+    pub labels: &'a HashMap<String, u32>,
+    /// Offset of the current location in this section.
+    pub location: u32,
+}
+
+/// What the names in an [`Expression`] refer to.
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    /// Constants defined by `.equ` or `.set`.
+    pub constants: &'a HashMap<String, i64>,
+    /// `None` if the expression is not in a section, and thus `.` cannot be used.
+    pub section: Option<SectionContext<'a>>,
+}
+
+/// Unescape the character after a `\` in a character or string literal.
+pub fn unescape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        c => c,
+    }
+}
+
+fn character(code: &str) -> IResult<&str, i64> {
+    map(
+        delimited(
+            tag("'"),
+            alt((map(preceded(tag("\\"), anychar), unescape), none_of("\\'"))),
+            tag("'"),
+        ),
+        |c| c as i64,
+    )(code)
+}
+
+fn primary(code: &str) -> IResult<&str, Expression> {
+    in_multispace(alt((
+        map(parsing::integer, Expression::Integer),
+        map(character, Expression::Integer),
+        map(parse_symbol, Expression::Symbol),
+        map(tag("."), |_| Expression::Location),
+        delimited(tag("("), parse, tag(")")),
+    )))(code)
+}
+
+fn unary(code: &str) -> IResult<&str, Expression> {
+    alt((
+        map(
+            pair(
+                in_multispace(alt((
+                    map(tag("-"), |_| UnaryOperator::Negative),
+                    map(tag("~"), |_| UnaryOperator::Not),
+                ))),
+                unary,
+            ),
+            |(operator, operand)| Expression::Unary(operator, Box::new(operand)),
+        ),
+        primary,
+    ))(code)
+}
+
+/// Parse a level of left associative binary operators.
+fn binary<'a>(
+    operators: &'static [(&'static str, BinaryOperator)],
+    operand: fn(&'a str) -> IResult<&'a str, Expression>,
+    code: &'a str,
+) -> IResult<&'a str, Expression> {
+    let operator = |code: &'a str| {
+        for (token, operator) in operators {
+            if let Some(rest) = code.strip_prefix(token) {
+                return Ok((rest, *operator));
+            }
+        }
+        Err(nom::Err::Error(nom::error::Error::new(
+            code,
+            nom::error::ErrorKind::Tag,
+        )))
+    };
+    let (code, first) = operand(code)?;
+    let (code, rest) = many0(pair(in_multispace(operator), operand))(code)?;
+    let result = rest.into_iter().fold(first, |lhs, (operator, rhs)| {
+        Expression::Binary(operator, Box::new(lhs), Box::new(rhs))
+    });
+    Ok((code, result))
+}
+
+fn multiplicative(code: &str) -> IResult<&str, Expression> {
+    binary(&[("*", BinaryOperator::Multiply)], unary, code)
+}
+
+fn additive(code: &str) -> IResult<&str, Expression> {
+    binary(
+        &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+        multiplicative,
+        code,
+    )
+}
+
+fn shift(code: &str) -> IResult<&str, Expression> {
+    binary(
+        &[
+            ("<<", BinaryOperator::ShiftLeft),
+            (">>", BinaryOperator::ShiftRight),
+        ],
+        additive,
+        code,
+    )
+}
+
+fn and(code: &str) -> IResult<&str, Expression> {
+    binary(&[("&", BinaryOperator::And)], shift, code)
+}
+
+/// Parse asm code to get an [`Expression`].
+pub fn parse(code: &str) -> IResult<&str, Expression> {
+    binary(&[("|", BinaryOperator::Or)], and, code)
+}
+
+impl Expression {
+    /// Evaluate the expression, returns `None` if the value cannot be decided while assembling.
+    ///
+    /// Labels defined before in the current section and `.` are treated as symbols relative to
+    /// the beginning of the section, so their differences are constants.
+    pub fn evaluate(&self, context: &Context) -> Option<Value> {
+        let section_relative = |offset: u32| {
+            context.section.map(|section| Value::Symbol {
+                name: section.name.to_string(),
+                addend: offset as i64,
+            })
+        };
+        match self {
+            Expression::Integer(value) => Some(Value::Constant(*value)),
+            Expression::Symbol(name) => {
+                if let Some(value) = context.constants.get(name) {
+                    Some(Value::Constant(*value))
+                } else if let Some(&offset) =
+                    context.section.and_then(|section| section.labels.get(name))
+                {
+                    section_relative(offset)
+                } else {
+                    Some(Value::Symbol {
+                        name: name.clone(),
+                        addend: 0,
+                    })
+                }
+            }
+            Expression::Location => section_relative(context.section?.location),
+            Expression::Unary(operator, operand) => {
+                let Value::Constant(operand) = operand.evaluate(context)? else {
+                    return None;
+                };
+                Some(Value::Constant(match operator {
+                    UnaryOperator::Negative => operand.wrapping_neg(),
+                    UnaryOperator::Not => !operand,
+                }))
+            }
+            Expression::Binary(operator, lhs, rhs) => {
+                let lhs = lhs.evaluate(context)?;
+                let rhs = rhs.evaluate(context)?;
+                match (operator, lhs, rhs) {
+                    (operator, Value::Constant(lhs), Value::Constant(rhs)) => {
+                        Some(Value::Constant(match operator {
+                            BinaryOperator::Multiply => lhs.wrapping_mul(rhs),
+                            BinaryOperator::Add => lhs.wrapping_add(rhs),
+                            BinaryOperator::Subtract => lhs.wrapping_sub(rhs),
+                            BinaryOperator::ShiftLeft => lhs.wrapping_shl(rhs as u32),
+                            BinaryOperator::ShiftRight => lhs.wrapping_shr(rhs as u32),
+                            BinaryOperator::And => lhs & rhs,
+                            BinaryOperator::Or => lhs | rhs,
+                        }))
+                    }
+                    (BinaryOperator::Add, Value::Symbol { name, addend }, Value::Constant(rhs))
+                    | (BinaryOperator::Add, Value::Constant(rhs), Value::Symbol { name, addend }) => {
+                        Some(Value::Symbol {
+                            name,
+                            addend: addend + rhs,
+                        })
+                    }
+                    (
+                        BinaryOperator::Subtract,
+                        Value::Symbol { name, addend },
+                        Value::Constant(rhs),
+                    ) => Some(Value::Symbol {
+                        name,
+                        addend: addend - rhs,
+                    }),
+                    (
+                        BinaryOperator::Subtract,
+                        Value::Symbol {
+                            name: lhs_name,
+                            addend: lhs,
+                        },
+                        Value::Symbol {
+                            name: rhs_name,
+                            addend: rhs,
+                        },
+                    ) if lhs_name == rhs_name => Some(Value::Constant(lhs - rhs)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// Evaluate the expression, which should be a constant.
+    pub fn evaluate_constant(&self, context: &Context) -> Option<i64> {
+        match self.evaluate(context)? {
+            Value::Constant(value) => Some(value),
+            Value::Symbol { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(code: &str, context: &Context) -> Option<Value> {
+        let (rest, expression) = parse(code).unwrap();
+        assert_eq!(rest, "");
+        expression.evaluate(context)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let constants = HashMap::from([("GPIO_BASE".to_string(), 0x1000_0000)]);
+        let labels = HashMap::from([("message".to_string(), 4)]);
+        let context = Context {
+            constants: &constants,
+            section: Some(SectionContext {
+                name: ".rodata",
+                labels: &labels,
+                location: 12,
+            }),
+        };
+        let constant = |value| Some(Value::Constant(value));
+        assert_eq!(
+            evaluate("GPIO_BASE + 0x10", &context),
+            constant(0x1000_0010)
+        );
+        assert_eq!(evaluate("1 + 2 * 3", &context), constant(7));
+        assert_eq!(evaluate("(1 + 2) * 3", &context), constant(9));
+        assert_eq!(evaluate("1 << 4 + 1", &context), constant(32));
+        assert_eq!(evaluate("0xff & ~0xf | 1", &context), constant(0xf1));
+        assert_eq!(evaluate("10 - 2 - 3", &context), constant(5));
+        assert_eq!(evaluate("-(8 >> 2)", &context), constant(-2));
+        assert_eq!(evaluate("'a' + '\\n'", &context), constant(107));
+        assert_eq!(evaluate(". - message", &context), constant(8));
+        assert_eq!(
+            evaluate("message + 2", &context),
+            Some(Value::Symbol {
+                name: ".rodata".to_string(),
+                addend: 6
+            })
+        );
+        assert_eq!(
+            evaluate("f - 4", &context),
+            Some(Value::Symbol {
+                name: "f".to_string(),
+                addend: -4
+            })
+        );
+        assert_eq!(evaluate("f * 2", &context), None);
+        let context = Context {
+            constants: &constants,
+            section: None,
+        };
+        assert_eq!(evaluate(".", &context), None);
+    }
+}
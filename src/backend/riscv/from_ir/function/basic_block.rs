@@ -1,6 +1,6 @@
 use crate::{backend::riscv::from_ir::register_assign::RegisterAssign, ir};
 
-use super::{statement, FunctionCompileContext};
+use super::{statement, FunctionCompileContext, WordPlace};
 
 /// Emit assembly code for a [`ir::function::basic_block::BasicBlock`].
 pub fn emit_code(
@@ -31,6 +31,14 @@ fn append_phi_insert(
     basic_block: &ir::function::basic_block::BasicBlock,
 ) -> String {
     let mut result = String::new();
+    // phi targets with constant sources may be the sources of the copies,
+    // so do the copies first
+    if let Some(copies) = ctx
+        .phi_register_copies
+        .get(basic_block.name.as_ref().unwrap())
+    {
+        result.push_str(&emit_parallel_copies(copies));
+    }
     if let Some(phi_insert) = ctx
         .phi_constant_assign
         .get(basic_block.name.as_ref().unwrap())
@@ -57,6 +65,43 @@ fn append_phi_insert(
     result
 }
 
+/// Emit code for copying each word from `from` to `to` as if all of them are done at once.
+///
+/// A copy is done once no other copy reads its destination, and a cycle of copies is broken by
+/// saving one of the destinations to `t1`.
+fn emit_parallel_copies(copies: &[(WordPlace, WordPlace)]) -> String {
+    let mut result = String::new();
+    let mut pending = copies.to_vec();
+    while !pending.is_empty() {
+        if let Some(index) = pending
+            .iter()
+            .position(|(to, _)| !pending.iter().any(|(_, from)| from == to))
+        {
+            let (to, from) = pending.remove(index);
+            result.push_str(&emit_move(&to, &from));
+        } else {
+            let saved = pending[0].0.clone();
+            let temporary = WordPlace::Register("t1".to_string());
+            result.push_str(&emit_move(&temporary, &saved));
+            for (_, from) in pending.iter_mut().filter(|(_, from)| *from == saved) {
+                *from = temporary.clone();
+            }
+        }
+    }
+    result
+}
+
+fn emit_move(to: &WordPlace, from: &WordPlace) -> String {
+    match (to, from) {
+        (WordPlace::Register(to), WordPlace::Register(from)) => format!("    mv {to}, {from}\n"),
+        (WordPlace::Register(to), WordPlace::Stack(from)) => format!("    lw {to}, {from}(sp)\n"),
+        (WordPlace::Stack(to), WordPlace::Register(from)) => format!("    sw {from}, {to}(sp)\n"),
+        (WordPlace::Stack(to), WordPlace::Stack(from)) => {
+            format!("    lw t0, {from}(sp)\n    sw t0, {to}(sp)\n")
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::borrow_interior_mutable_const)]
@@ -133,14 +178,14 @@ mod tests {
             RegisterName("reg3".to_string()),
             RegisterAssign::Register("t5".to_string()),
         );
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         let mut context = FunctionCompileContext {
             parent_context: &mut ctx,
             local_assign: register_assign,
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         context.phi_constant_assign.insert(
             "bb1".to_string(),
@@ -173,4 +218,25 @@ mod tests {
 "#
         )
     }
+
+    #[test]
+    fn parallel_copies() {
+        let register = |name: &str| WordPlace::Register(name.to_string());
+        // t2 and t3 are swapped, and the old t3 is also copied to the stack
+        let copies = vec![
+            (register("t2"), register("t3")),
+            (register("t3"), register("t2")),
+            (WordPlace::Stack(4), register("t3")),
+            (register("t4"), WordPlace::Stack(8)),
+        ];
+        assert_eq!(
+            emit_parallel_copies(&copies),
+            r#"    sw t3, 4(sp)
+    lw t4, 8(sp)
+    mv t1, t2
+    mv t2, t3
+    mv t3, t1
+"#
+        );
+    }
 }
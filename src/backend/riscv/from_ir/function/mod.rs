@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use super::register_assign::{self, RegisterAssign};
+use super::{
+    register_assign::{self, ArgumentPosition, RegisterAssign},
+    HasSize,
+};
 use crate::ir::{
     self,
     analyzer::{self, IsAnalyzer},
@@ -21,6 +24,12 @@ pub struct FunctionCompileContext<'a> {
     /// So we can jump to this label instead of return directly.
     pub cleanup_label: Option<String>,
     pub phi_constant_assign: HashMap<String, Vec<(RegisterAssign, i64)>>,
+    /// Copies to do at the end of each basic block for phis in its successors, as `(to, from)`.
+    /// They should be done in parallel, since a phi target can be the source of another phi.
+    pub phi_register_copies: HashMap<String, Vec<(WordPlace, WordPlace)>>,
+    /// Caller-saved registers used in this function and the stack offsets to save them to
+    /// around calls.
+    pub caller_saved_registers: Vec<(String, usize)>,
 }
 
 fn collect_phi_constant_assign(
@@ -43,12 +52,150 @@ fn collect_phi_constant_assign(
     result
 }
 
+/// Where a word of a value is kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WordPlace {
+    Register(String),
+    /// At this offset to `sp`.
+    Stack(usize),
+}
+
+/// Where each word of a value taking `words` words and assigned to `assign` is kept.
+fn word_places(assign: &RegisterAssign, words: usize) -> Vec<WordPlace> {
+    match assign {
+        RegisterAssign::Register(register) => vec![WordPlace::Register(register.clone())],
+        RegisterAssign::MultipleRegisters(registers) => registers
+            .iter()
+            .map(|it| WordPlace::Register(it.clone()))
+            .collect(),
+        RegisterAssign::StackValue(offset) => (0..words)
+            .map(|i| WordPlace::Stack(offset + i * 4))
+            .collect(),
+        RegisterAssign::StackRef(_) => unreachable!("results of alloca are never phied"),
+    }
+}
+
+/// Phis with register sources which don't share the hardware register with the phi target need
+/// copies at the end of the predecessor blocks.
+fn collect_phi_register_copies(
+    function: &ir::FunctionDefinition,
+    register_assign: &HashMap<RegisterName, RegisterAssign>,
+    ctx: &super::Context,
+) -> HashMap<String, Vec<(WordPlace, WordPlace)>> {
+    let mut result: HashMap<String, Vec<(WordPlace, WordPlace)>> = HashMap::new();
+    for statement in function.iter() {
+        if let IRStatement::Phi(Phi {
+            to,
+            data_type,
+            from,
+        }) = statement
+        {
+            let words = ((data_type.size(ctx) + 7) / 8).div_ceil(4);
+            let to_places = word_places(&register_assign[to], words);
+            for from in from {
+                if let Quantity::RegisterName(register) = &from.value {
+                    let from_places = word_places(&register_assign[register], words);
+                    result.entry(from.block.clone()).or_default().extend(
+                        to_places
+                            .iter()
+                            .cloned()
+                            .zip(from_places)
+                            .filter(|(to, from)| to != from),
+                    );
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Whether `register` should be saved by the caller if it is still needed after a call.
+fn is_caller_saved(register: &str) -> bool {
+    register.starts_with('t') || register.starts_with('a')
+}
+
+/// Whether `register` should be restored by the callee before it returns.
+fn is_callee_saved(register: &str) -> bool {
+    register.starts_with('s') && register != "sp"
+}
+
+/// Registers saved in the stack frame, and the offsets of their slots.
+type SaveSlots = Vec<(String, usize)>;
+
+/// Decide where registers are saved in the stack frame, see [`emit_code`] for the layout.
+/// Return the slots of caller-saved and callee-saved registers, and the size of the frame.
+fn frame_layout(
+    stack_space: usize,
+    used_registers: &BTreeSet<String>,
+    has_call: bool,
+) -> (SaveSlots, SaveSlots, usize) {
+    let mut frame_size = stack_space;
+    let mut caller_saved_registers = Vec::new();
+    if has_call {
+        for register in used_registers.iter().filter(|it| is_caller_saved(it)) {
+            caller_saved_registers.push((register.clone(), frame_size));
+            frame_size += 4;
+        }
+    }
+    // `ra` will be overwritten by calls, so it is saved like a callee-saved register
+    let mut callee_saved_registers = Vec::new();
+    let used_callee_saved = used_registers.iter().filter(|it| is_callee_saved(it));
+    for register in has_call
+        .then(|| "ra".to_string())
+        .into_iter()
+        .chain(used_callee_saved.cloned())
+    {
+        callee_saved_registers.push((register, frame_size));
+        frame_size += 4;
+    }
+    // the stack pointer should always be 16-byte aligned
+    (
+        caller_saved_registers,
+        callee_saved_registers,
+        frame_size.next_multiple_of(16),
+    )
+}
+
 /// Emit assembly code for a [`ir::FunctionDefinition`].
+///
+/// The stack frame looks like this, from `sp` upwards:
+/// - stack space for allocas and spilled registers
+/// - slots for saving caller-saved registers around calls
+/// - slots for saving `ra` and the callee-saved registers used
+///
+/// And parameters passed on the stack are right above the frame.
 pub fn emit_code(function: &ir::FunctionDefinition, ctx: &mut super::Context) -> String {
     let binding = analyzer::Analyzer::new();
     let analyzer = binding.bind(function);
-    let (register_assign, stack_space) = register_assign::assign_register(ctx, function, &analyzer);
+    let (mut register_assign, stack_space) =
+        register_assign::assign_register(ctx, function, &analyzer);
+    let has_call = function.iter().any(|statement| {
+        matches!(statement, IRStatement::Call(call) if !statement::is_builtin(&call.name))
+    });
+    let used_registers: BTreeSet<_> = register_assign
+        .values()
+        .flat_map(|assign| match assign {
+            RegisterAssign::Register(register) => vec![register.clone()],
+            RegisterAssign::MultipleRegisters(registers) => registers.clone(),
+            RegisterAssign::StackRef(_) | RegisterAssign::StackValue(_) => vec![],
+        })
+        .collect();
+    let (caller_saved_registers, callee_saved_registers, frame_size) =
+        frame_layout(stack_space, &used_registers, has_call);
+    let (parameter_positions, _) = register_assign::argument_positions(
+        function.header.parameters.iter().map(|it| &it.data_type),
+        ctx,
+    );
+    for (parameter, position) in function.header.parameters.iter().zip(parameter_positions) {
+        if let ArgumentPosition::Stack(offset) = position {
+            register_assign.insert(
+                parameter.name.clone(),
+                RegisterAssign::StackValue(frame_size + offset),
+            );
+        }
+    }
     let phi_constant_assign = collect_phi_constant_assign(function, &register_assign);
+    let phi_register_copies = collect_phi_register_copies(function, &register_assign, ctx);
     let mut result = format!(
         ".global {}\n{}:\n",
         function.header.name, function.header.name
@@ -56,23 +203,31 @@ pub fn emit_code(function: &ir::FunctionDefinition, ctx: &mut super::Context) ->
     let mut context = FunctionCompileContext {
         parent_context: ctx,
         local_assign: register_assign,
-        cleanup_label: if stack_space != 0 {
+        cleanup_label: if frame_size != 0 {
             Some(format!("{}_end", function.header.name))
         } else {
             None
         },
         phi_constant_assign,
+        phi_register_copies,
+        caller_saved_registers,
     };
-    if stack_space != 0 {
-        result.push_str(format!("    addi sp, sp, -{stack_space}\n").as_str());
+    if frame_size != 0 {
+        result.push_str(format!("    addi sp, sp, -{frame_size}\n").as_str());
+    }
+    for (register, offset) in &callee_saved_registers {
+        result.push_str(format!("    sw {register}, {offset}(sp)\n").as_str());
     }
     for basic_block in function.content.iter() {
         result.push_str(basic_block::emit_code(basic_block, &mut context).as_str());
     }
     if let Some(cleanup_label) = context.cleanup_label {
         result.push_str(format!("{cleanup_label}:\n").as_str());
-        if stack_space != 0 {
-            result.push_str(format!("    addi sp, sp, {stack_space}\n").as_str());
+        for (register, offset) in &callee_saved_registers {
+            result.push_str(format!("    lw {register}, {offset}(sp)\n").as_str());
+        }
+        if frame_size != 0 {
+            result.push_str(format!("    addi sp, sp, {frame_size}\n").as_str());
         }
         result.push_str("    ret\n");
     }
@@ -149,4 +304,30 @@ mod tests {
         assert_eq!(bb2_result[0].0, RegisterAssign::Register("t0".to_string()));
         assert_eq!(bb2_result[0].1, 2);
     }
+
+    #[test]
+    fn test_frame_layout() {
+        let registers = |names: &[&str]| -> BTreeSet<String> {
+            names.iter().map(|it| it.to_string()).collect()
+        };
+        let slots = |slots: &[(&str, usize)]| -> SaveSlots {
+            slots
+                .iter()
+                .map(|(register, offset)| (register.to_string(), *offset))
+                .collect()
+        };
+        let (caller_saved, callee_saved, frame_size) =
+            frame_layout(8, &registers(&["a0", "s1", "t2"]), true);
+        assert_eq!(caller_saved, slots(&[("a0", 8), ("t2", 12)]));
+        assert_eq!(callee_saved, slots(&[("ra", 16), ("s1", 20)]));
+        assert_eq!(frame_size, 32);
+        // without calls, neither caller-saved registers nor `ra` need saving
+        let (caller_saved, callee_saved, frame_size) =
+            frame_layout(4, &registers(&["a0", "s1", "t2"]), false);
+        assert!(caller_saved.is_empty());
+        assert_eq!(callee_saved, slots(&[("s1", 4)]));
+        assert_eq!(frame_size, 16);
+        let (_, _, frame_size) = frame_layout(0, &registers(&["t2"]), false);
+        assert_eq!(frame_size, 0);
+    }
 }
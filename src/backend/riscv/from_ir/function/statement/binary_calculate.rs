@@ -1,6 +1,10 @@
 use crate::{
-    backend::riscv::from_ir::{function::FunctionCompileContext, register_assign::RegisterAssign},
-    ir,
+    backend::riscv::from_ir::{
+        function::FunctionCompileContext,
+        register_assign::RegisterAssign,
+        software_routine::{self, SoftwareRoutine},
+    },
+    ir::{self, statement::calculate::binary::BinaryOperation},
 };
 
 /// Emit assembly code for a [`ir::statement::BinaryCalculate`].
@@ -29,7 +33,10 @@ pub fn emit_code(
                 RegisterAssign::StackRef(_) => unreachable!(),
             }
         }
-        ir::quantity::Quantity::GlobalVariableName(_global) => todo!(),
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t0, {}\n", global.0));
+            "t0".to_string()
+        }
         ir::quantity::Quantity::NumberLiteral(literal) => {
             result.push_str(&format!("    li t0, {literal}\n"));
             "t0".to_string()
@@ -47,7 +54,10 @@ pub fn emit_code(
                 unreachable!()
             }
         }
-        ir::quantity::Quantity::GlobalVariableName(_global) => todo!(),
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t1, {}\n", global.0));
+            "t1".to_string()
+        }
         ir::quantity::Quantity::NumberLiteral(literal) => {
             result.push_str(&format!("    li t1, {literal}\n"));
             "t1".to_string()
@@ -60,34 +70,121 @@ pub fn emit_code(
         RegisterAssign::StackValue(_stack_offset) => "t0",
         RegisterAssign::MultipleRegisters(_registers) => todo!(),
     };
+    let slt = if matches!(
+        operation,
+        BinaryOperation::UnsignedLessThan
+            | BinaryOperation::UnsignedLessOrEqualThan
+            | BinaryOperation::UnsignedGreaterThan
+            | BinaryOperation::UnsignedGreaterOrEqualThan
+    ) {
+        "sltu"
+    } else {
+        "slt"
+    };
     match operation {
-        ir::statement::calculate::binary::BinaryOperation::Add => {
+        BinaryOperation::Add
+        | BinaryOperation::Sub
+        | BinaryOperation::Or
+        | BinaryOperation::Xor
+        | BinaryOperation::And
+        | BinaryOperation::LessThan
+        | BinaryOperation::UnsignedLessThan
+        | BinaryOperation::LogicalShiftLeft
+        | BinaryOperation::LogicalShiftRight
+        | BinaryOperation::AthematicShiftRight => {
+            let instruction = match operation {
+                BinaryOperation::Add => "add",
+                BinaryOperation::Sub => "sub",
+                BinaryOperation::Or => "or",
+                BinaryOperation::Xor => "xor",
+                BinaryOperation::And => "and",
+                BinaryOperation::LessThan | BinaryOperation::UnsignedLessThan => slt,
+                BinaryOperation::LogicalShiftLeft => "sll",
+                BinaryOperation::LogicalShiftRight => "srl",
+                BinaryOperation::AthematicShiftRight => "sra",
+                _ => unreachable!(),
+            };
+            result.push_str(&format!(
+                "    {instruction} {to_register}, {operand1_register}, {operand2_register}\n"
+            ));
+        }
+        // a > b <=> b < a
+        BinaryOperation::GreaterThan | BinaryOperation::UnsignedGreaterThan => {
+            result.push_str(&format!(
+                "    {slt} {to_register}, {operand2_register}, {operand1_register}\n"
+            ));
+        }
+        // a <= b <=> !(b < a)
+        BinaryOperation::LessOrEqualThan | BinaryOperation::UnsignedLessOrEqualThan => {
             result.push_str(&format!(
-                "    add {to_register}, {operand1_register}, {operand2_register}\n"
+                "    {slt} {to_register}, {operand2_register}, {operand1_register}\n"
             ));
+            result.push_str(&format!("    xori {to_register}, {to_register}, 1\n"));
         }
-        ir::statement::calculate::binary::BinaryOperation::LessThan => {
+        // a >= b <=> !(a < b)
+        BinaryOperation::GreaterOrEqualThan | BinaryOperation::UnsignedGreaterOrEqualThan => {
             result.push_str(&format!(
-                "    slt {to_register}, {operand1_register}, {operand2_register}\n"
+                "    {slt} {to_register}, {operand1_register}, {operand2_register}\n"
             ));
+            result.push_str(&format!("    xori {to_register}, {to_register}, 1\n"));
         }
-        ir::statement::calculate::binary::BinaryOperation::LessOrEqualThan => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::GreaterThan => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::GreaterOrEqualThan => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::Equal => {
+        BinaryOperation::Equal => {
             result.push_str(&format!(
                 "    sub {to_register}, {operand1_register}, {operand2_register}\n"
             ));
             result.push_str(&format!("    seqz {to_register}, {to_register}\n"));
         }
-        ir::statement::calculate::binary::BinaryOperation::NotEqual => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::Sub => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::Or => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::Xor => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::And => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::LogicalShiftLeft => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::LogicalShiftRight => todo!(),
-        ir::statement::calculate::binary::BinaryOperation::AthematicShiftRight => todo!(),
+        BinaryOperation::NotEqual => {
+            result.push_str(&format!(
+                "    sub {to_register}, {operand1_register}, {operand2_register}\n"
+            ));
+            result.push_str(&format!("    snez {to_register}, {to_register}\n"));
+        }
+        BinaryOperation::Mul
+        | BinaryOperation::SignedDiv
+        | BinaryOperation::UnsignedDiv
+        | BinaryOperation::SignedRem
+        | BinaryOperation::UnsignedRem
+            if ctx.parent_context.options.arch.has_m_extension() =>
+        {
+            let instruction = match operation {
+                BinaryOperation::Mul => "mul",
+                BinaryOperation::SignedDiv => "div",
+                BinaryOperation::UnsignedDiv => "divu",
+                BinaryOperation::SignedRem => "rem",
+                BinaryOperation::UnsignedRem => "remu",
+                _ => unreachable!(),
+            };
+            result.push_str(&format!(
+                "    {instruction} {to_register}, {operand1_register}, {operand2_register}\n"
+            ));
+        }
+        BinaryOperation::Mul
+        | BinaryOperation::SignedDiv
+        | BinaryOperation::UnsignedDiv
+        | BinaryOperation::SignedRem
+        | BinaryOperation::UnsignedRem => {
+            let (routine, result_register) = match operation {
+                BinaryOperation::Mul => (SoftwareRoutine::Mul, "t0"),
+                BinaryOperation::SignedDiv => (SoftwareRoutine::SignedDivRem, "t0"),
+                BinaryOperation::UnsignedDiv => (SoftwareRoutine::UnsignedDivRem, "t0"),
+                BinaryOperation::SignedRem => (SoftwareRoutine::SignedDivRem, "t1"),
+                BinaryOperation::UnsignedRem => (SoftwareRoutine::UnsignedDivRem, "t1"),
+                _ => unreachable!(),
+            };
+            // operand2 is never in t0, so it is safe to move operand1 first
+            if operand1_register != "t0" {
+                result.push_str(&format!("    mv t0, {operand1_register}\n"));
+            }
+            if operand2_register != "t1" {
+                result.push_str(&format!("    mv t1, {operand2_register}\n"));
+            }
+            result.push_str(&software_routine::emit_call(routine));
+            ctx.parent_context.software_routines.insert(routine);
+            if to_register != result_register {
+                result.push_str(&format!("    mv {to_register}, {result_register}\n"));
+            }
+        }
     }
     if let RegisterAssign::StackValue(stack_offset) = to_register_assign {
         result.push_str(&format!("    sw {to_register}, {stack_offset}(sp)\n"));
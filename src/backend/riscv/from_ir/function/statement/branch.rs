@@ -21,6 +21,8 @@ pub fn emit_code(branch: &Branch, ctx: &mut FunctionCompileContext) -> String {
         BranchType::GE => "bge",
         BranchType::LT => "blt",
         BranchType::NE => "bne",
+        BranchType::LTU => "bltu",
+        BranchType::GEU => "bgeu",
     };
     let operand1_register = match operand1 {
         Quantity::RegisterName(local) => {
@@ -35,7 +37,10 @@ pub fn emit_code(branch: &Branch, ctx: &mut FunctionCompileContext) -> String {
                 RegisterAssign::MultipleRegisters(_) => unreachable!(),
             }
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t0, {}\n", global.0));
+            "t0".to_string()
+        }
         Quantity::NumberLiteral(n) => {
             result.push_str(&format!("    li t0, {n}\n"));
             "t0".to_string()
@@ -54,7 +59,10 @@ pub fn emit_code(branch: &Branch, ctx: &mut FunctionCompileContext) -> String {
                 RegisterAssign::MultipleRegisters(_) => unreachable!(),
             }
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t1, {}\n", global.0));
+            "t1".to_string()
+        }
         Quantity::NumberLiteral(n) => {
             result.push_str(&format!("    li t1, {n}\n"));
             "t1".to_string()
@@ -1,8 +1,17 @@
 use crate::{
-    backend::riscv::from_ir::{function::FunctionCompileContext, register_assign::RegisterAssign},
+    backend::riscv::from_ir::{
+        function::FunctionCompileContext,
+        register_assign::{self, ArgumentPosition, RegisterAssign},
+        HasSize,
+    },
     ir::{quantity::Quantity, statement::Call},
 };
 
+/// Whether `name` is a builtin function, which is compiled inline instead of being called.
+pub fn is_builtin(name: &str) -> bool {
+    name == "load_u32" || name == "store_u32"
+}
+
 pub fn emit_code(call: &Call, ctx: &mut FunctionCompileContext) -> String {
     let Call {
         to,
@@ -16,10 +25,146 @@ pub fn emit_code(call: &Call, ctx: &mut FunctionCompileContext) -> String {
     } else if name == "store_u32" {
         store_u32(&params[0], &params[1], ctx)
     } else {
-        todo!()
+        call_function(call, ctx)
     }
 }
 
+/// Emit code for putting the `word`th word of `argument` into `target_register`.
+/// `sp_offset` is how far the `sp` has been moved since entering the function body.
+fn load_argument_word(
+    argument: &Quantity,
+    word: usize,
+    words: usize,
+    target_register: &str,
+    sp_offset: usize,
+    ctx: &FunctionCompileContext,
+) -> String {
+    // caller-saved registers are already saved, and argument registers may be overwritten
+    // by the former arguments, so we read them from where they are saved
+    let read_register = |register: &str| {
+        if let Some((_, offset)) = ctx
+            .caller_saved_registers
+            .iter()
+            .find(|(saved, _)| saved == register)
+        {
+            format!("    lw {target_register}, {}(sp)\n", offset + sp_offset)
+        } else {
+            format!("    mv {target_register}, {register}\n")
+        }
+    };
+    match argument {
+        Quantity::RegisterName(local) => match ctx.local_assign.get(local).unwrap() {
+            RegisterAssign::Register(register) => read_register(register),
+            RegisterAssign::MultipleRegisters(registers) => read_register(&registers[word]),
+            RegisterAssign::StackValue(offset) => format!(
+                "    lw {target_register}, {}(sp)\n",
+                offset + word * 4 + sp_offset
+            ),
+            RegisterAssign::StackRef(_) => unreachable!(),
+        },
+        Quantity::NumberLiteral(n) if words == 1 => format!("    li {target_register}, {n}\n"),
+        Quantity::NumberLiteral(n) => {
+            format!("    li {target_register}, {}\n", (n >> (word * 32)) as i32)
+        }
+        Quantity::GlobalVariableName(global) => {
+            format!("    la {target_register}, {}\n", global.0)
+        }
+    }
+}
+
+/// Emit code for calling a user defined function, following the ILP32 calling convention.
+fn call_function(call: &Call, ctx: &mut FunctionCompileContext) -> String {
+    let Call {
+        to,
+        name,
+        data_type,
+        params,
+    } = call;
+    let header = ctx.parent_context.function_headers.get(name).unwrap();
+    let parameter_types: Vec<_> = header
+        .parameters
+        .iter()
+        .map(|it| it.data_type.clone())
+        .collect();
+    let parameter_words: Vec<_> = parameter_types
+        .iter()
+        .map(|it| ((it.size(ctx.parent_context) + 7) / 8).div_ceil(4))
+        .collect();
+    let (positions, stack_bytes) =
+        register_assign::argument_positions(&parameter_types, ctx.parent_context);
+    let mut result = String::new();
+    for (register, offset) in &ctx.caller_saved_registers {
+        result.push_str(&format!("    sw {register}, {offset}(sp)\n"));
+    }
+    // the stack pointer should always be 16-byte aligned
+    let outgoing_bytes = stack_bytes.next_multiple_of(16);
+    if outgoing_bytes != 0 {
+        result.push_str(&format!("    addi sp, sp, -{outgoing_bytes}\n"));
+    }
+    for ((param, position), words) in params.iter().zip(positions).zip(parameter_words) {
+        match position {
+            ArgumentPosition::Registers(registers) => {
+                for (word, register) in registers.iter().enumerate() {
+                    result.push_str(&load_argument_word(
+                        param,
+                        word,
+                        words,
+                        register,
+                        outgoing_bytes,
+                        ctx,
+                    ));
+                }
+            }
+            ArgumentPosition::Stack(offset) => {
+                for word in 0..words {
+                    result.push_str(&load_argument_word(
+                        param,
+                        word,
+                        words,
+                        "t0",
+                        outgoing_bytes,
+                        ctx,
+                    ));
+                    result.push_str(&format!("    sw t0, {}(sp)\n", offset + word * 4));
+                }
+            }
+        }
+    }
+    result.push_str(&format!("    jal ra, {name}\n"));
+    if outgoing_bytes != 0 {
+        result.push_str(&format!("    addi sp, sp, {outgoing_bytes}\n"));
+    }
+    // move the return value, which is in `a0` and `a1`, to where it should be
+    let mut result_registers = Vec::new();
+    if let Some(to) = to {
+        match ctx.local_assign.get(to).unwrap() {
+            RegisterAssign::Register(register) => {
+                result.push_str(&format!("    mv {register}, a0\n"));
+                result_registers.push(register.clone());
+            }
+            RegisterAssign::MultipleRegisters(registers) => {
+                for (i, register) in registers.iter().enumerate() {
+                    result.push_str(&format!("    mv {register}, a{i}\n"));
+                }
+                result_registers.extend(registers.iter().cloned());
+            }
+            RegisterAssign::StackValue(offset) => {
+                let words = ((data_type.size(ctx.parent_context) + 7) / 8).div_ceil(4);
+                for i in 0..words {
+                    result.push_str(&format!("    sw a{i}, {}(sp)\n", offset + i * 4));
+                }
+            }
+            RegisterAssign::StackRef(_) => unreachable!(),
+        }
+    }
+    for (register, offset) in &ctx.caller_saved_registers {
+        if !result_registers.contains(register) {
+            result.push_str(&format!("    lw {register}, {offset}(sp)\n"));
+        }
+    }
+    result
+}
+
 fn store_u32(to_address: &Quantity, value: &Quantity, ctx: &mut FunctionCompileContext) -> String {
     let mut result = String::new();
     match value {
@@ -38,7 +183,9 @@ fn store_u32(to_address: &Quantity, value: &Quantity, ctx: &mut FunctionCompileC
                 RegisterAssign::MultipleRegisters(_) => todo!(),
             }
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la a1, {}\n", global.0))
+        }
         Quantity::NumberLiteral(constant) => result.push_str(&format!("    li a1, {constant}\n")),
     }
     match to_address {
@@ -57,7 +204,9 @@ fn store_u32(to_address: &Quantity, value: &Quantity, ctx: &mut FunctionCompileC
                 RegisterAssign::MultipleRegisters(_) => todo!(),
             }
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la a0, {}\n", global.0))
+        }
         Quantity::NumberLiteral(constant) => result.push_str(&format!("    li a0, {constant}\n")),
     }
     result.push_str("    sw a1, 0(a0)\n");
@@ -84,7 +233,9 @@ fn load_u32(
         Quantity::NumberLiteral(constant) => {
             result.push_str(&format!("    li a0, {constant}\n"));
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la a0, {}\n", global.0));
+        }
     }
     result.push_str("    lw a0, 0(a0)\n");
     if let Some(to_register) = to {
@@ -104,3 +255,80 @@ fn load_u32(
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        backend::riscv::from_ir::Context,
+        ir::{self, function::parameter::Parameter, RegisterName},
+        utility::data_type,
+    };
+
+    #[test]
+    fn test_call_function() {
+        let mut ctx = Context::default();
+        ctx.function_headers.insert(
+            "g".to_string(),
+            ir::FunctionHeader {
+                name: "g".to_string(),
+                parameters: vec![
+                    Parameter {
+                        name: RegisterName("a".to_string()),
+                        data_type: data_type::I32.clone(),
+                    },
+                    Parameter {
+                        name: RegisterName("b".to_string()),
+                        data_type: data_type::I32.clone(),
+                    },
+                ],
+                return_type: data_type::I32.clone(),
+            },
+        );
+        let mut ctx = FunctionCompileContext {
+            parent_context: &mut ctx,
+            local_assign: HashMap::new(),
+            cleanup_label: None,
+            phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: vec![("a0".to_string(), 0), ("t2".to_string(), 4)],
+        };
+        ctx.local_assign.insert(
+            RegisterName("x".to_string()),
+            RegisterAssign::Register("a0".to_string()),
+        );
+        ctx.local_assign.insert(
+            RegisterName("y".to_string()),
+            RegisterAssign::Register("t2".to_string()),
+        );
+        ctx.local_assign.insert(
+            RegisterName("z".to_string()),
+            RegisterAssign::Register("t3".to_string()),
+        );
+        let call = Call {
+            to: Some(RegisterName("z".to_string())),
+            name: "g".to_string(),
+            data_type: data_type::I32.clone(),
+            params: vec![
+                RegisterName("y".to_string()).into(),
+                RegisterName("x".to_string()).into(),
+            ],
+        };
+        assert_eq!(
+            emit_code(&call, &mut ctx),
+            "    sw a0, 0(sp)
+    sw t2, 4(sp)
+    lw a0, 4(sp)
+    lw a1, 0(sp)
+    jal ra, g
+    mv t3, a0
+    lw a0, 0(sp)
+    lw t2, 4(sp)
+"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    backend::riscv::from_ir::{
+        function::FunctionCompileContext, register_assign::RegisterAssign, HasSize,
+    },
+    ir,
+};
+
+/// Emit code for `t0 = index * element_bytes`, where `index` is in the register `index_register`.
+fn emit_scale_index(index_register: &str, element_bytes: usize) -> String {
+    if element_bytes == 1 {
+        return format!("    mv t0, {index_register}\n");
+    } else if element_bytes.is_power_of_two() {
+        return format!(
+            "    slli t0, {index_register}, {}\n",
+            element_bytes.trailing_zeros()
+        );
+    }
+    // shift-and-add, from the highest bit to the lowest
+    let mut result = format!("    mv t0, {index_register}\n");
+    let highest_bit = usize::BITS - 1 - element_bytes.leading_zeros();
+    for bit in (0..highest_bit).rev() {
+        result.push_str("    slli t0, t0, 1\n");
+        if element_bytes & (1 << bit) != 0 {
+            result.push_str(&format!("    add t0, t0, {index_register}\n"));
+        }
+    }
+    result
+}
+
+/// Emit code for `to = base + offset`, where `offset` is a constant.
+pub fn emit_offset_address(
+    to: &ir::RegisterName,
+    base: &ir::quantity::Quantity,
+    offset: i64,
+    ctx: &mut FunctionCompileContext,
+) -> String {
+    let mut result = String::new();
+    let to_register_assign = ctx.local_assign.get(to).unwrap();
+    let to_register = match to_register_assign {
+        RegisterAssign::Register(register) => register.as_str(),
+        RegisterAssign::StackValue(_) => "t0",
+        RegisterAssign::StackRef(_) | RegisterAssign::MultipleRegisters(_) => unreachable!(),
+    };
+    let (base_register, offset) = match base {
+        ir::quantity::Quantity::RegisterName(local) => match ctx.local_assign.get(local).unwrap() {
+            RegisterAssign::StackRef(stack_offset) => {
+                ("sp".to_string(), *stack_offset as i64 + offset)
+            }
+            RegisterAssign::Register(register) => (register.clone(), offset),
+            RegisterAssign::StackValue(stack_offset) => {
+                result.push_str(&format!("    lw t0, {stack_offset}(sp)\n"));
+                ("t0".to_string(), offset)
+            }
+            RegisterAssign::MultipleRegisters(_) => unreachable!(),
+        },
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t0, {}\n", global.0));
+            ("t0".to_string(), offset)
+        }
+        ir::quantity::Quantity::NumberLiteral(_) => unreachable!(),
+    };
+    if (-2048..2048).contains(&offset) {
+        result.push_str(&format!(
+            "    addi {to_register}, {base_register}, {offset}\n"
+        ));
+    } else {
+        result.push_str(&format!("    li t1, {offset}\n"));
+        result.push_str(&format!("    add {to_register}, {base_register}, t1\n"));
+    }
+    if let RegisterAssign::StackValue(stack_offset) = to_register_assign {
+        result.push_str(&format!("    sw t0, {stack_offset}(sp)\n"));
+    }
+    result
+}
+
+/// Emit assembly code for a [`ir::statement::ElementAddress`].
+pub fn emit_code(
+    statement: &ir::statement::ElementAddress,
+    ctx: &mut FunctionCompileContext,
+) -> String {
+    let ir::statement::ElementAddress {
+        to,
+        element_type,
+        base,
+        index,
+    } = statement;
+    let element_bytes = element_type.stride(ctx.parent_context);
+    if let ir::quantity::Quantity::NumberLiteral(literal) = index {
+        return emit_offset_address(to, base, *literal * element_bytes as i64, ctx);
+    }
+    let mut result = String::new();
+    let to_register_assign = ctx.local_assign.get(to).unwrap();
+    let to_register = match to_register_assign {
+        RegisterAssign::Register(register) => register.as_str(),
+        RegisterAssign::StackValue(_) => "t0",
+        RegisterAssign::StackRef(_) | RegisterAssign::MultipleRegisters(_) => unreachable!(),
+    };
+    // `None` means the base is the address of a global variable
+    let base_assign = match base {
+        ir::quantity::Quantity::RegisterName(local) => Some(ctx.local_assign.get(local).unwrap()),
+        ir::quantity::Quantity::GlobalVariableName(_) => None,
+        ir::quantity::Quantity::NumberLiteral(_) => unreachable!(),
+    };
+    let global_base = match base {
+        ir::quantity::Quantity::GlobalVariableName(global) => global.0.as_str(),
+        _ => "",
+    };
+    let index_register = match index {
+        ir::quantity::Quantity::RegisterName(local) => match ctx.local_assign.get(local).unwrap() {
+            RegisterAssign::Register(register) => register.clone(),
+            RegisterAssign::StackValue(stack_offset) => {
+                result.push_str(&format!("    lw t1, {stack_offset}(sp)\n"));
+                "t1".to_string()
+            }
+            RegisterAssign::StackRef(_) | RegisterAssign::MultipleRegisters(_) => {
+                unreachable!()
+            }
+        },
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t1, {}\n", global.0));
+            "t1".to_string()
+        }
+        ir::quantity::Quantity::NumberLiteral(_) => unreachable!(),
+    };
+    result.push_str(&emit_scale_index(&index_register, element_bytes));
+    match base_assign {
+        Some(RegisterAssign::StackRef(0)) => {
+            result.push_str(&format!("    add {to_register}, sp, t0\n"));
+        }
+        Some(RegisterAssign::StackRef(stack_offset)) => {
+            result.push_str("    add t0, sp, t0\n");
+            result.push_str(&format!("    addi {to_register}, t0, {stack_offset}\n"));
+        }
+        Some(RegisterAssign::Register(register)) => {
+            result.push_str(&format!("    add {to_register}, {register}, t0\n"));
+        }
+        Some(RegisterAssign::StackValue(stack_offset)) => {
+            result.push_str(&format!("    lw t1, {stack_offset}(sp)\n"));
+            result.push_str(&format!("    add {to_register}, t1, t0\n"));
+        }
+        Some(RegisterAssign::MultipleRegisters(_)) => unreachable!(),
+        None => {
+            result.push_str(&format!("    la t1, {global_base}\n"));
+            result.push_str(&format!("    add {to_register}, t1, t0\n"));
+        }
+    }
+    if let RegisterAssign::StackValue(stack_offset) = to_register_assign {
+        result.push_str(&format!("    sw t0, {stack_offset}(sp)\n"));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+    use super::*;
+    use crate::{
+        backend::riscv::from_ir::Context,
+        utility::data_type::{self, Type},
+    };
+
+    #[test]
+    fn test_struct_stride() {
+        let u8_type = Type::Integer(data_type::Integer {
+            signed: false,
+            width: 8,
+        });
+        let mut ctx = Context::default();
+        ctx.struct_definitions.insert(
+            "S".to_string(),
+            ir::TypeDefinition {
+                name: "S".to_string(),
+                fields: vec![data_type::I32.clone(), u8_type.clone()],
+            },
+        );
+        let struct_type = Type::StructRef("S".to_string());
+        assert_eq!(struct_type.size(&ctx), 64);
+        assert_eq!(struct_type.stride(&ctx), 8);
+        assert_eq!(Type::Array(Box::new(struct_type), 3).size(&ctx), 3 * 64);
+        assert_eq!(u8_type.stride(&ctx), 1);
+    }
+
+    #[test]
+    fn test_emit_scale_index() {
+        assert_eq!(emit_scale_index("t2", 4), "    slli t0, t2, 2\n");
+        assert_eq!(emit_scale_index("t2", 1), "    mv t0, t2\n");
+        assert_eq!(
+            emit_scale_index("t2", 12),
+            "    mv t0, t2\n    slli t0, t0, 1\n    add t0, t0, t2\n    slli t0, t0, 1\n    slli t0, t0, 1\n"
+        );
+    }
+}
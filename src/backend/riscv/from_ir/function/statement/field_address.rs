@@ -0,0 +1,94 @@
+use crate::{
+    backend::riscv::from_ir::{function::FunctionCompileContext, HasSize},
+    ir,
+    utility::data_type::Type,
+};
+
+use super::element_address::emit_offset_address;
+
+/// Emit assembly code for a [`ir::statement::FieldAddress`].
+pub fn emit_code(
+    statement: &ir::statement::FieldAddress,
+    ctx: &mut FunctionCompileContext,
+) -> String {
+    let ir::statement::FieldAddress {
+        to,
+        base,
+        field_chain,
+    } = statement;
+    // fields are laid out the same as `load_field` expects
+    let mut offset = 0;
+    for (field_parent_type, field_index) in field_chain {
+        if let Type::StructRef(struct_name) = field_parent_type {
+            let parent_type = ctx
+                .parent_context
+                .struct_definitions
+                .get(struct_name)
+                .unwrap();
+            for field_type in &parent_type.fields[..*field_index] {
+                offset += field_type.size(ctx.parent_context);
+            }
+        }
+    }
+    emit_offset_address(to, base, ((offset + 7) / 8) as i64, ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+
+    use std::collections::HashMap;
+
+    use crate::{
+        backend::riscv::from_ir::{register_assign::RegisterAssign, Context},
+        ir::RegisterName,
+        utility::data_type,
+    };
+
+    use super::*;
+
+    #[test]
+    fn emit_code_nested() {
+        let mut ctx = Context::default();
+        ctx.struct_definitions.insert(
+            "S1".to_string(),
+            ir::TypeDefinition {
+                name: "S1".to_string(),
+                fields: vec![
+                    data_type::I32.clone(),
+                    Type::Array(Box::new(data_type::I32.clone()), 4),
+                ],
+            },
+        );
+        ctx.struct_definitions.insert(
+            "S2".to_string(),
+            ir::TypeDefinition {
+                name: "S2".to_string(),
+                fields: vec![data_type::I64.clone(), Type::StructRef("S1".to_string())],
+            },
+        );
+        let mut ctx = FunctionCompileContext {
+            parent_context: &mut ctx,
+            local_assign: HashMap::new(),
+            cleanup_label: None,
+            phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
+        };
+        ctx.local_assign
+            .insert(RegisterName("s".to_string()), RegisterAssign::StackRef(16));
+        ctx.local_assign.insert(
+            RegisterName("p".to_string()),
+            RegisterAssign::Register("t2".to_string()),
+        );
+        let ir_code = ir::statement::FieldAddress {
+            to: RegisterName("p".to_string()),
+            base: RegisterName("s".to_string()).into(),
+            field_chain: vec![
+                (Type::StructRef("S2".to_string()), 1),
+                (Type::StructRef("S1".to_string()), 1),
+            ],
+        };
+        assert_eq!(emit_code(&ir_code, &mut ctx), "    addi t2, sp, 28\n");
+    }
+}
@@ -1,8 +1,53 @@
 use crate::{
-    backend::riscv::from_ir::{function::FunctionCompileContext, register_assign::RegisterAssign},
+    backend::riscv::from_ir::{
+        function::FunctionCompileContext, register_assign::RegisterAssign, HasSize,
+    },
     ir,
+    utility::data_type::{Integer, Type},
 };
 
+/// The instruction for loading a value of `data_type` from memory.
+pub fn load_instruction(data_type: &Type) -> &'static str {
+    match data_type {
+        Type::Integer(Integer {
+            signed: true,
+            width: 8,
+        }) => "lb",
+        Type::Integer(Integer {
+            signed: false,
+            width: 8,
+        }) => "lbu",
+        Type::Integer(Integer {
+            signed: true,
+            width: 16,
+        }) => "lh",
+        Type::Integer(Integer {
+            signed: false,
+            width: 16,
+        }) => "lhu",
+        _ => "lw",
+    }
+}
+
+/// Decide the base register and offset of the memory `address` refers to.
+/// If the address is spilled to the stack, it will be loaded into `temp_register` first.
+pub fn memory_operand(
+    address: &ir::RegisterName,
+    temp_register: &str,
+    ctx: &FunctionCompileContext,
+    result: &mut String,
+) -> (String, usize) {
+    match ctx.local_assign.get(address).unwrap() {
+        RegisterAssign::StackRef(stack_offset) => ("sp".to_string(), *stack_offset),
+        RegisterAssign::Register(register) => (register.clone(), 0),
+        RegisterAssign::StackValue(stack_offset) => {
+            result.push_str(&format!("    lw {temp_register}, {stack_offset}(sp)\n"));
+            (temp_register.to_string(), 0)
+        }
+        RegisterAssign::MultipleRegisters(_) => unreachable!(),
+    }
+}
+
 /// Emit assembly code for a [`ir::statement::Load`].
 pub fn emit_code(
     statement: &ir::function::statement::Load,
@@ -10,30 +55,31 @@ pub fn emit_code(
 ) -> String {
     let ir::function::statement::Load {
         to,
-        data_type: _,
+        data_type,
         from,
     } = statement;
     let mut result = String::new();
-    let from_stack_offset = match from {
+    let (base, offset) = match from {
         ir::quantity::Quantity::RegisterName(local) => {
-            let physical_register = ctx.local_assign.get(local).unwrap();
-            match physical_register {
-                RegisterAssign::Register(_register) => {
-                    // unreachable!() ?
-                    todo!()
-                }
-                RegisterAssign::StackRef(stack_offset) => stack_offset,
-                RegisterAssign::StackValue(_) => {
-                    // unreachable!() ?
-                    todo!()
-                }
-                RegisterAssign::MultipleRegisters(_) => unreachable!(),
-            }
+            memory_operand(local, "t1", ctx, &mut result)
+        }
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t1, {}\n", global.0));
+            ("t1".to_string(), 0)
         }
-        ir::quantity::Quantity::GlobalVariableName(_global) => todo!(),
         ir::quantity::Quantity::NumberLiteral(_literal) => unreachable!(),
     };
     let to_physical = ctx.local_assign.get(to).unwrap();
+    let words = ((data_type.size(ctx.parent_context) + 7) / 8).div_ceil(4);
+    if let RegisterAssign::StackValue(stack_offset) = to_physical
+        && words > 1
+    {
+        for i in 0..words {
+            result.push_str(&format!("    lw t0, {}({base})\n", offset + i * 4));
+            result.push_str(&format!("    sw t0, {}(sp)\n", stack_offset + i * 4));
+        }
+        return result;
+    }
     let to_register = match to_physical {
         RegisterAssign::Register(register) => register.to_string(),
         RegisterAssign::StackValue(_) => "t0".to_string(),
@@ -41,15 +87,18 @@ pub fn emit_code(
         RegisterAssign::MultipleRegisters(registers) => {
             for (i, register) in registers.iter().enumerate() {
                 result.push_str(&format!(
-                    "    lw {}, {}(sp)\n",
+                    "    lw {}, {}({base})\n",
                     register,
-                    from_stack_offset + i * 4
+                    offset + i * 4
                 ));
             }
             return result;
         }
     };
-    result.push_str(&format!("    lw {to_register}, {from_stack_offset}(sp)\n"));
+    result.push_str(&format!(
+        "    {} {to_register}, {offset}({base})\n",
+        load_instruction(data_type)
+    ));
     if let RegisterAssign::StackValue(stack_offset) = to_physical {
         result.push_str(&format!("    sw {to_register}, {stack_offset}(sp)\n"));
     }
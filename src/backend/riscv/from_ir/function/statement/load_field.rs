@@ -77,8 +77,8 @@ pub fn emit_code(statement: &ir::statement::LoadField, ctx: &mut FunctionCompile
             (RegisterAssign::StackValue(to), RegisterAssign::StackValue(from)) => {
                 let mut result = String::new();
                 let mut current_from = from + current_offset_bytes;
-                let mut current_to = to + current_offset_bytes;
-                for _ in 0..final_result_bytes / 4 {
+                let mut current_to = *to;
+                for _ in 0..final_result_bytes.div_ceil(4) {
                     result.push_str(&format!("    lw t0, {current_from}(sp)\n"));
                     result.push_str(&format!("    sw t0, {current_to}(sp)\n"));
                     current_from += 4;
@@ -88,9 +88,9 @@ pub fn emit_code(statement: &ir::statement::LoadField, ctx: &mut FunctionCompile
             }
             (RegisterAssign::StackValue(to), RegisterAssign::MultipleRegisters(from)) => {
                 let mut result = String::new();
-                let mut current_offset = to + current_offset_bytes;
+                let mut current_offset = *to;
                 let start_at_register = current_offset_bytes / 4;
-                let final_result_words = final_result_bytes / 4;
+                let final_result_words = final_result_bytes.div_ceil(4);
                 for from_item in from.iter().skip(start_at_register).take(final_result_words) {
                     result.push_str(&format!("    sw {from_item}, {current_offset}(sp)\n"));
                     current_offset += 4;
@@ -128,9 +128,7 @@ mod tests {
 
     #[test]
     fn emit_code_multiple_to_single() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -154,6 +152,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -220,9 +220,7 @@ mod tests {
 
     #[test]
     fn emit_code_multiple_to_multiple() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -246,6 +244,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -275,9 +275,7 @@ mod tests {
 
     #[test]
     fn emit_code_single_to_memory() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S0".to_string(),
             ir::TypeDefinition {
@@ -290,6 +288,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -311,9 +311,7 @@ mod tests {
 
     #[test]
     fn emit_code_memory_to_single() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -337,6 +335,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -387,9 +387,7 @@ mod tests {
 
     #[test]
     fn emit_code_multiple_to_memory() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -413,6 +411,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -434,14 +434,12 @@ mod tests {
             leaf_type: Type::StructRef("S1".to_string()),
         };
         let result = emit_code(&ir_code, &mut ctx);
-        assert_eq!(result, "    sw a1, 20(sp)\n    sw a2, 24(sp)\n");
+        assert_eq!(result, "    sw a1, 16(sp)\n    sw a2, 20(sp)\n");
     }
 
     #[test]
     fn emit_code_memory_to_multiple() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -465,6 +463,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -486,9 +486,7 @@ mod tests {
 
     #[test]
     fn emit_code_memory_to_memory() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -512,6 +510,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -531,9 +531,9 @@ mod tests {
         assert_eq!(
             result,
             r#"    lw t0, 20(sp)
-    sw t0, 36(sp)
+    sw t0, 32(sp)
     lw t0, 24(sp)
-    sw t0, 40(sp)
+    sw t0, 36(sp)
 "#
         );
     }
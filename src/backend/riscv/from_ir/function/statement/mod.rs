@@ -6,6 +6,10 @@ use super::FunctionCompileContext;
 mod binary_calculate;
 mod branch;
 mod call;
+/// Compile an element address calculation.
+mod element_address;
+/// Compile a field address calculation.
+mod field_address;
 /// Compile a load command.
 mod load;
 mod load_field;
@@ -16,6 +20,8 @@ mod store;
 /// Compile a unary operator.
 mod unary_calculate;
 
+pub use call::is_builtin;
+
 /// Emit assembly code for a [`ir::function::statement::IRStatement`].
 pub fn emit_code(
     statement: &ir::function::statement::IRStatement,
@@ -34,6 +40,12 @@ pub fn emit_code(
         ir::statement::IRStatement::Store(store) => store::emit_code(store, ctx),
         ir::statement::IRStatement::LoadField(load_field) => load_field::emit_code(load_field, ctx),
         ir::statement::IRStatement::SetField(set_field) => set_field::emit_code(set_field, ctx),
+        ir::statement::IRStatement::ElementAddress(element_address) => {
+            element_address::emit_code(element_address, ctx)
+        }
+        ir::statement::IRStatement::FieldAddress(field_address) => {
+            field_address::emit_code(field_address, ctx)
+        }
         ir::statement::IRStatement::Branch(branch) => branch::emit_code(branch, ctx),
         ir::statement::IRStatement::Jump(jump) => format!("    j {}\n", jump.label),
         ir::statement::IRStatement::Ret(ret) => ret::emit_code(ret, ctx),
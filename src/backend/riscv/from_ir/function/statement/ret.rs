@@ -15,11 +15,22 @@ pub fn emit_code(ret: &Ret, ctx: &mut FunctionCompileContext) -> String {
                         format!("    lw a0, {stack_offset}(sp)\n")
                     }
                     RegisterAssign::StackRef(_) => unreachable!(),
-                    RegisterAssign::MultipleRegisters(_) => todo!(),
+                    RegisterAssign::MultipleRegisters(registers) => {
+                        // values no larger than two words are returned in `a0` and `a1`
+                        assert!(
+                            registers.len() <= 2,
+                            "returning a value larger than two words is not supported"
+                        );
+                        let mut result = String::new();
+                        for (i, register) in registers.iter().enumerate() {
+                            result.push_str(&format!("    mv a{i}, {register}\n"));
+                        }
+                        result
+                    }
                 }
             }
             Quantity::NumberLiteral(n) => format!("    li a0, {n}\n"),
-            Quantity::GlobalVariableName(_) => todo!(),
+            Quantity::GlobalVariableName(global) => format!("    la a0, {}\n", global.0),
         }
     } else {
         String::new()
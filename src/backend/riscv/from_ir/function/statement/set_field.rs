@@ -22,7 +22,10 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
             code.push_str(&format!("    li t1, {n}\n"));
             RegisterAssign::Register("t1".to_string())
         }
-        Quantity::GlobalVariableName(_) => todo!(),
+        Quantity::GlobalVariableName(global) => {
+            code.push_str(&format!("    la t1, {}\n", global.0));
+            RegisterAssign::Register("t1".to_string())
+        }
     };
     let value_to_be_setted = ctx.local_assign.get(origin_root).unwrap();
     let result_register = ctx.local_assign.get(target).unwrap();
@@ -78,7 +81,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     result_code.push_str(&format!("    mv {}, {}\n", result[i], to_be_setted[i]));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     result_code.push_str(&format!(
                         "    mv {}, {}\n",
                         result[i],
@@ -103,7 +106,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     result_code.push_str(&format!("    mv {}, {}\n", result[i], to_be_setted[i]));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = value_to_set + (i - current_offset_bytes / 4) * 4;
                     result_code.push_str(&format!("    lw {}, {}(sp)\n", result[i], offset));
                     i += 1;
@@ -143,7 +146,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     result_code.push_str(&format!("    lw {}, {}(sp)\n", result[i], offset));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let index = i - current_offset_bytes / 4;
                     result_code
                         .push_str(&format!("    mv {}, {}\n", result[i], value_to_set[index]));
@@ -168,7 +171,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     result_code.push_str(&format!("    lw {}, {}(sp)\n", result[i], offset));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = value_to_set + (i - current_offset_bytes / 4) * 4;
                     result_code.push_str(&format!("    lw {}, {}(sp)\n", result[i], offset));
                     i += 1;
@@ -219,7 +222,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     result_code.push_str(&format!("    sw {}, {}(sp)\n", to_be_setted[i], offset));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = result + i * 4;
                     let index = i - current_offset_bytes / 4;
                     result_code
@@ -249,7 +252,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     ));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = i * 4;
                     let value_to_set_offset = (i - current_offset_bytes / 4) * 4;
                     result_code.push_str(&format!(
@@ -276,7 +279,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                 RegisterAssign::Register(value_to_set),
             ) => {
                 let mut result_code = String::new();
-                for i in 0..current_offset_bytes / 4 {
+                for i in 0..root_type_bytes / 4 {
                     let offset = i * 4;
                     if i == current_offset_bytes / 4 {
                         result_code.push_str(&format!(
@@ -310,7 +313,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     ));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = i * 4;
                     let index = i - current_offset_bytes / 4;
                     result_code.push_str(&format!(
@@ -347,7 +350,7 @@ pub fn emit_code(set_field: &ir::statement::SetField, ctx: &mut FunctionCompileC
                     ));
                     i += 1;
                 }
-                while i < current_offset_bytes / 4 + final_type_bytes / 4 {
+                while i < current_offset_bytes / 4 + final_type_bytes.div_ceil(4) {
                     let offset = i * 4;
                     let value_to_set_offset = (i - current_offset_bytes / 4) * 4;
                     result_code.push_str(&format!(
@@ -400,9 +403,7 @@ mod tests {
 
     #[test]
     fn emit_code_reg_whatever_reg() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S0".to_string(),
             ir::TypeDefinition {
@@ -415,6 +416,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -438,9 +441,7 @@ mod tests {
 
     #[test]
     fn emit_code_reg_whatever_mem() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S0".to_string(),
             ir::TypeDefinition {
@@ -453,6 +454,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -480,9 +483,7 @@ mod tests {
 
     #[test]
     fn emit_code_multi_multi_reg() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -495,6 +496,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
@@ -526,9 +529,7 @@ mod tests {
 
     #[test]
     fn emit_code_multi_multi_multi() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -552,6 +553,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -600,9 +603,7 @@ mod tests {
 
     #[test]
     fn emit_code_multi_multi_mem() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -626,6 +627,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -674,9 +677,7 @@ mod tests {
 
     #[test]
     fn emit_code_multi_mem_reg() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -689,6 +690,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         // Simple struct
         ctx.local_assign.insert(
@@ -716,9 +719,7 @@ mod tests {
 
     #[test]
     fn emit_code_multi_mem_multi() {
-        let mut ctx = Context {
-            struct_definitions: HashMap::new(),
-        };
+        let mut ctx = Context::default();
         ctx.struct_definitions.insert(
             "S1".to_string(),
             ir::TypeDefinition {
@@ -742,6 +743,8 @@ mod tests {
             local_assign: HashMap::new(),
             cleanup_label: None,
             phi_constant_assign: HashMap::new(),
+            phi_register_copies: HashMap::new(),
+            caller_saved_registers: Vec::new(),
         };
         ctx.local_assign.insert(
             RegisterName("a".to_string()),
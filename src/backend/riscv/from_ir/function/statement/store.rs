@@ -1,70 +1,84 @@
+use super::load::memory_operand;
 use crate::{
-    backend::riscv::from_ir::{function::FunctionCompileContext, register_assign::RegisterAssign},
+    backend::riscv::from_ir::{
+        function::FunctionCompileContext, register_assign::RegisterAssign, HasSize,
+    },
     ir,
+    utility::data_type::{Integer, Type},
 };
 
+/// The instruction for storing a value of `data_type` to memory.
+pub fn store_instruction(data_type: &Type) -> &'static str {
+    match data_type {
+        Type::Integer(Integer { width: 8, .. }) => "sb",
+        Type::Integer(Integer { width: 16, .. }) => "sh",
+        _ => "sw",
+    }
+}
+
 /// Emit assembly code for a [`ir::function::statement::Store`].
 pub fn emit_code(
     statement: &ir::function::statement::Store,
     ctx: &mut FunctionCompileContext,
 ) -> String {
     let ir::function::statement::Store {
-        data_type: _,
+        data_type,
         source,
         target,
     } = statement;
     let mut result = String::new();
+    let (base, offset) = match target {
+        ir::quantity::Quantity::RegisterName(local) => {
+            memory_operand(local, "t1", ctx, &mut result)
+        }
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t1, {}\n", global.0));
+            ("t1".to_string(), 0)
+        }
+        ir::quantity::Quantity::NumberLiteral(_) => unreachable!(),
+    };
     let source_register = match source {
         ir::quantity::Quantity::RegisterName(local) => {
             let local = ctx.local_assign.get(local).unwrap();
             match local {
                 RegisterAssign::Register(register) => register.clone(),
                 RegisterAssign::StackValue(stack_offset) => {
+                    let words = ((data_type.size(ctx.parent_context) + 7) / 8).div_ceil(4);
+                    if words > 1 {
+                        for i in 0..words {
+                            result.push_str(&format!("    lw t0, {}(sp)\n", stack_offset + i * 4));
+                            result.push_str(&format!("    sw t0, {}({base})\n", offset + i * 4));
+                        }
+                        return result;
+                    }
                     result.push_str(&format!("    lw t0, {stack_offset}(sp)\n"));
                     "t0".to_string()
                 }
                 RegisterAssign::StackRef(_) => unreachable!(),
                 RegisterAssign::MultipleRegisters(registers) => {
-                    if let ir::quantity::Quantity::RegisterName(local) = target {
-                        let local = ctx.local_assign.get(local).unwrap();
-                        if let RegisterAssign::StackRef(stack_offset) = local {
-                            for (i, register) in registers.iter().enumerate() {
-                                result.push_str(&format!(
-                                    "    sw {}, {}(sp)\n",
-                                    register,
-                                    stack_offset + i * 4
-                                ));
-                            }
-                        } else {
-                            unimplemented!()
-                        }
-                    } else {
-                        unimplemented!()
+                    for (i, register) in registers.iter().enumerate() {
+                        result.push_str(&format!(
+                            "    sw {}, {}({base})\n",
+                            register,
+                            offset + i * 4
+                        ));
                     }
                     return result;
                 }
             }
         }
-        ir::quantity::Quantity::GlobalVariableName(_) => todo!(),
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t0, {}\n", global.0));
+            "t0".to_string()
+        }
         ir::quantity::Quantity::NumberLiteral(n) => {
             result.push_str(&format!("    li t0, {n}\n"));
             "t0".to_string()
         }
     };
-    let target_stack_offset = if let ir::quantity::Quantity::RegisterName(local) = target {
-        let local = ctx.local_assign.get(local).unwrap();
-        match local {
-            RegisterAssign::StackRef(stack_offset) => stack_offset,
-            RegisterAssign::Register(_) => todo!(),
-            RegisterAssign::StackValue(_stack_offset) => todo!(),
-            RegisterAssign::MultipleRegisters(_) => todo!(),
-        }
-    } else {
-        // unreachable!() ?
-        todo!()
-    };
     result.push_str(&format!(
-        "    sw {source_register}, {target_stack_offset}(sp)\n"
+        "    {} {source_register}, {offset}({base})\n",
+        store_instruction(data_type)
     ));
     result
 }
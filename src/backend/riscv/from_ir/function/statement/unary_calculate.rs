@@ -27,7 +27,10 @@ pub fn emit_code(
                 unreachable!()
             }
         }
-        ir::quantity::Quantity::GlobalVariableName(_global) => todo!(),
+        ir::quantity::Quantity::GlobalVariableName(global) => {
+            result.push_str(&format!("    la t0, {}\n", global.0));
+            "t0".to_string()
+        }
         ir::quantity::Quantity::NumberLiteral(literal) => {
             result.push_str(&format!("    li t0, {literal}\n"));
             "t0".to_string()
@@ -0,0 +1,103 @@
+use super::{Context, HasSize};
+use crate::{ir, utility::data_type::Type};
+
+/// Emit the data directives for a single global variable, padded to a multiple of 4 bytes,
+/// so the next global variable is still word aligned.
+fn emit_data(global: &ir::GlobalDefinition, ctx: &Context) -> String {
+    let bytes = (global.data_type.size(ctx) + 7) / 8;
+    let padded_bytes = bytes.next_multiple_of(4);
+    let value = global.initial_value.0;
+    let mut result = match (&global.data_type, bytes) {
+        (Type::Integer(_), 1) => format!("    .byte {value}\n"),
+        (Type::Integer(_), 2) => format!("    .half {value}\n"),
+        (Type::Integer(_), 4) | (Type::Address, 4) => format!("    .word {value}\n"),
+        (Type::Integer(_), 8) => format!(
+            "    .word {}\n    .word {}\n",
+            value as i32,
+            (value >> 32) as i32
+        ),
+        _ => unimplemented!(
+            "initializing a global variable of type {}",
+            global.data_type
+        ),
+    };
+    if padded_bytes != bytes {
+        result.push_str(&format!("    .zero {}\n", padded_bytes - bytes));
+    }
+    result
+}
+
+/// Emit assembly code for global variables.
+/// Initialized ones go into `.data`, and zero-initialized ones go into `.bss`.
+pub fn emit_code(globals: &[&ir::GlobalDefinition], ctx: &Context) -> String {
+    let (bss, data): (Vec<&ir::GlobalDefinition>, Vec<_>) = globals
+        .iter()
+        .partition(|global| global.initial_value.0 == 0);
+    let mut result = String::new();
+    if !data.is_empty() {
+        result.push_str(".section .data\n");
+        for global in data {
+            result.push_str(&format!(".global {0}\n{0}:\n", global.name.0));
+            result.push_str(&emit_data(global, ctx));
+        }
+    }
+    if !bss.is_empty() {
+        result.push_str(".section .bss\n");
+        for global in bss {
+            let bytes = (global.data_type.size(ctx) + 7) / 8;
+            result.push_str(&format!(".global {0}\n{0}:\n", global.name.0));
+            result.push_str(&format!("    .zero {}\n", bytes.next_multiple_of(4)));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+    use super::*;
+    use crate::{
+        ir::{quantity::GlobalVariableName, IntegerLiteral},
+        utility::data_type::{self, Integer},
+    };
+
+    #[test]
+    fn test_emit_code() {
+        let a = ir::GlobalDefinition {
+            name: GlobalVariableName("a".to_string()),
+            data_type: data_type::I32.clone(),
+            initial_value: IntegerLiteral(-1),
+        };
+        let b = ir::GlobalDefinition {
+            name: GlobalVariableName("b".to_string()),
+            data_type: data_type::I64.clone(),
+            initial_value: IntegerLiteral(0),
+        };
+        let c = ir::GlobalDefinition {
+            name: GlobalVariableName("c".to_string()),
+            data_type: Integer {
+                signed: false,
+                width: 8,
+            }
+            .into(),
+            initial_value: IntegerLiteral(3),
+        };
+        let code = emit_code(&[&a, &b, &c], &Context::default());
+        assert_eq!(
+            code,
+            ".section .data
+.global a
+a:
+    .word -1
+.global c
+c:
+    .byte 3
+    .zero 3
+.section .bss
+.global b
+b:
+    .zero 8
+"
+        );
+    }
+}
@@ -1,19 +1,79 @@
 use crate::{ir, utility::data_type};
-use std::{collections::HashMap, str};
+use software_routine::SoftwareRoutine;
+use std::{
+    collections::{BTreeSet, HashMap},
+    str,
+};
 /// Compiling a function.
 mod function;
+/// Global variables.
+mod global;
 /// Register assign.
 mod register_assign;
+/// Software implementations for operations which the hardware may not support.
+mod software_routine;
+
+/// The RISC-V ISA variant the code is generated for.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Arch {
+    /// Base integer instruction set only, multiplication and division are done by software routines.
+    RV32I,
+    /// Base integer instruction set with the `M` extension.
+    #[default]
+    RV32IM,
+}
+
+impl Arch {
+    /// Whether the hardware can do multiplication and division.
+    pub fn has_m_extension(self) -> bool {
+        matches!(self, Arch::RV32IM)
+    }
+}
+
+/// The algorithm used for assigning hardware registers to IR registers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegisterAllocator {
+    /// Color the interference graph built from the statement level live ranges of registers,
+    /// using all allocatable registers and spilling the cheapest registers when running out
+    /// of them.
+    #[default]
+    GraphColoring,
+    /// Give each group of registers which are never alive at the same time one of `t2`..`t6`
+    /// in order, and spill all the others.
+    Simple,
+}
+
+/// Options for generating asm.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// The ISA variant to generate code for.
+    pub arch: Arch,
+    /// The register allocator to use.
+    pub register_allocator: RegisterAllocator,
+}
 
 /// Context for compiling IR to asm.
 #[derive(Debug, Default)]
 pub struct Context {
     /// Struct type definitions
     pub struct_definitions: HashMap<String, ir::TypeDefinition>,
+    /// Headers of all functions defined, for deciding how to pass arguments when calling them.
+    pub function_headers: HashMap<String, ir::FunctionHeader>,
+    /// Options for generating asm.
+    pub options: Options,
+    /// Software routines used by the generated code.
+    pub software_routines: BTreeSet<SoftwareRoutine>,
 }
 /// Implement by the [`data_type::Type`] struct for calculating the size of a type.
 pub trait HasSize {
+    /// Size of the type in bits.
     fn size(&self, ctx: &Context) -> usize;
+    /// Alignment of the type in bits.
+    fn align(&self) -> usize;
+    /// Distance in bytes between two adjacent elements of this type in an array.
+    fn stride(&self, ctx: &Context) -> usize {
+        self.size(ctx).next_multiple_of(self.align()) / 8
+    }
 }
 
 impl HasSize for data_type::Type {
@@ -26,10 +86,23 @@ impl HasSize for data_type::Type {
                     .fields
                     .iter()
                     .map(|field_type| field_type.size(ctx))
-                    .sum()
+                    .sum::<usize>()
+                    .next_multiple_of(self.align())
             }
             data_type::Type::None => 0,
             data_type::Type::Address => 32,
+            data_type::Type::Array(element_type, length) => element_type.stride(ctx) * 8 * length,
+        }
+    }
+
+    fn align(&self) -> usize {
+        match self {
+            data_type::Type::Integer(integer) => integer.width.next_power_of_two().max(8),
+            // structs are passed in registers, so they are always word aligned
+            data_type::Type::StructRef(_) => 32,
+            data_type::Type::None => 8,
+            data_type::Type::Address => 32,
+            data_type::Type::Array(element_type, _) => element_type.align(),
         }
     }
 }
@@ -46,11 +119,22 @@ impl Context {
 }
 
 /// Emit assembly code for ir.
-pub fn emit_asm(ir: &[ir::IR]) -> String {
+pub fn emit_asm(ir: &[ir::IR], options: Options) -> String {
     let mut code = ".section .text\n".to_string();
     let mut ctx = Context {
-        struct_definitions: HashMap::new(),
+        options,
+        ..Default::default()
     };
+    // functions can be called before they are defined
+    for ir in ir {
+        if let ir::IR::FunctionDefinition(function_definition) = ir {
+            ctx.function_headers.insert(
+                function_definition.header.name.clone(),
+                function_definition.header.clone(),
+            );
+        }
+    }
+    let mut globals = Vec::new();
     for ir in ir {
         match ir {
             ir::IR::FunctionDefinition(function_definition) => {
@@ -60,8 +144,10 @@ pub fn emit_asm(ir: &[ir::IR]) -> String {
                 ctx.struct_definitions
                     .insert(type_definition.name.clone(), type_definition.clone());
             }
-            ir::IR::GlobalDefinition(_) => todo!(),
+            ir::IR::GlobalDefinition(global_definition) => globals.push(global_definition),
         }
     }
+    code.push_str(&software_routine::emit_routines(&ctx.software_routines));
+    code.push_str(&global::emit_code(&globals, &ctx));
     code
 }
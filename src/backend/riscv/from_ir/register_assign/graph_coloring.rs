@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{coalesce_phied_registers, ranges_overlap, register_live_ranges, RegisterAssign};
+use crate::{
+    backend::riscv::from_ir::{function::statement::is_builtin, Context, HasSize},
+    ir::{
+        self,
+        analyzer::{self, LiveRange},
+        function::FunctionDefinitionIndex,
+        statement::IRStatement,
+        RegisterName,
+    },
+};
+
+/// Caller-saved registers which can be allocated.
+///
+/// `t0` and `t1` are used as scratch registers by the statement emitters,
+/// and `a0`, `a1` are used for return values and by the builtin functions, so they are not here.
+const CALLER_SAVED_REGISTERS: [&str; 11] = [
+    "t2", "t3", "t4", "t5", "t6", "a2", "a3", "a4", "a5", "a6", "a7",
+];
+
+/// Callee-saved registers which can be allocated.
+const CALLEE_SAVED_REGISTERS: [&str; 12] = [
+    "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+];
+
+/// A node in the interference graph.
+/// Registers in the same node are coalesced, and share the same [`RegisterAssign`].
+#[derive(Debug)]
+struct Node {
+    registers: Vec<RegisterName>,
+    /// Count of hardware registers needed.
+    words: usize,
+    /// Ranges in which any register of this node is alive.
+    live_ranges: Vec<LiveRange>,
+    /// Estimated cost of spilling this node to the stack.
+    spill_cost: usize,
+    /// Already decided assign, for nodes containing a parameter.
+    precolored: Option<RegisterAssign>,
+}
+
+/// Loop nesting depth of each basic block.
+fn loop_depths(
+    ir_code: &ir::FunctionDefinition,
+    control_flow_graph: &analyzer::BindedControlFlowGraph,
+) -> Vec<u32> {
+    let mut result = vec![0; ir_code.content.len()];
+    for (latch, header) in control_flow_graph.back_edges() {
+        for &block in control_flow_graph.may_pass_blocks(header, latch).iter() {
+            result[block] += 1;
+        }
+    }
+    result
+}
+
+/// Cost of accessing a register at `index`, accesses in loops are more expensive.
+fn access_cost(index: &FunctionDefinitionIndex, loop_depths: &[u32]) -> usize {
+    10usize.pow(loop_depths[index.0].min(6))
+}
+
+fn build_nodes(
+    consider_registers: &[&RegisterName],
+    assigned: &HashMap<RegisterName, RegisterAssign>,
+    ir_code: &ir::FunctionDefinition,
+    ctx: &Context,
+    analyzer: &analyzer::BindedAnalyzer,
+) -> Vec<Node> {
+    let register_usage = analyzer.register_usage();
+    let control_flow_graph = analyzer.control_flow_graph();
+    let loop_depths = loop_depths(ir_code, &control_flow_graph);
+    // registers already assigned, eg. parameters, are grouped too, so the registers phied with
+    // them can be precolored
+    let mut live_ranges = register_live_ranges(
+        consider_registers.iter().cloned().chain(assigned.keys()),
+        ctx,
+        analyzer,
+    );
+    let mut groups: Vec<Vec<RegisterName>> =
+        coalesce_phied_registers(ir_code, analyzer, &mut live_ranges)
+            .into_iter()
+            .map(|group| group.into_iter().collect())
+            .collect();
+    for &register in consider_registers {
+        if !groups.iter().any(|group| group.contains(register)) {
+            groups.push(vec![register.clone()]);
+        }
+    }
+    // keep the result stable between runs
+    for group in &mut groups {
+        group.sort();
+    }
+    groups.sort();
+    groups
+        .into_iter()
+        .filter(|group| !group.is_empty())
+        .map(|group| {
+            let precolored = group.iter().find_map(|it| assigned.get(it)).cloned();
+            let data_type = register_usage.get(&group[0]).data_type();
+            let words = ((data_type.size(ctx) + 7) / 8).div_ceil(4);
+            let mut node_live_ranges = Vec::new();
+            let mut spill_cost = 0;
+            for register in &group {
+                node_live_ranges.extend(live_ranges.remove(register).unwrap_or_default());
+                let usage = register_usage.get(register);
+                if let Some(define_index) = usage.define_position().body() {
+                    spill_cost += access_cost(define_index, &loop_depths);
+                }
+                for use_index in usage.use_indexes() {
+                    spill_cost += access_cost(use_index, &loop_depths);
+                }
+            }
+            Node {
+                registers: group,
+                words,
+                live_ranges: node_live_ranges,
+                spill_cost,
+                precolored,
+            }
+        })
+        .collect()
+}
+
+/// Decide the order for picking nodes to color, by simplifying the interference graph.
+///
+/// Nodes which can surely be colored are removed first. When there is no such node,
+/// the one with the lowest spill cost per interference is removed, and it may still
+/// be colored later if we are lucky.
+fn simplify(nodes: &[Node], interference: &[HashSet<usize>], available: usize) -> Vec<usize> {
+    let mut remaining: HashSet<usize> = (0..nodes.len())
+        .filter(|&it| nodes[it].precolored.is_none())
+        .collect();
+    let mut stack = Vec::new();
+    while !remaining.is_empty() {
+        let pressure = |node: usize| -> usize {
+            interference[node]
+                .iter()
+                .filter(|it| remaining.contains(it) || nodes[**it].precolored.is_some())
+                .map(|&it| nodes[it].words)
+                .sum()
+        };
+        let mut candidates: Vec<_> = remaining.iter().cloned().collect();
+        candidates.sort();
+        let picked = candidates
+            .iter()
+            .find(|&&it| pressure(it) + nodes[it].words <= available)
+            .cloned()
+            .unwrap_or_else(|| {
+                candidates
+                    .iter()
+                    .cloned()
+                    .min_by(|&a, &b| {
+                        // compare spill_cost / (pressure + 1) without floating point
+                        (nodes[a].spill_cost * (pressure(b) + 1))
+                            .cmp(&(nodes[b].spill_cost * (pressure(a) + 1)))
+                    })
+                    .unwrap()
+            });
+        remaining.remove(&picked);
+        stack.push(picked);
+    }
+    stack
+}
+
+fn assign_to_physical(assign: &RegisterAssign) -> Vec<String> {
+    match assign {
+        RegisterAssign::Register(register) => vec![register.clone()],
+        RegisterAssign::MultipleRegisters(registers) => registers.clone(),
+        RegisterAssign::StackRef(_) | RegisterAssign::StackValue(_) => Vec::new(),
+    }
+}
+
+/// Assign registers in `consider_registers` by graph coloring.
+///
+/// `assigned` are registers already decided, eg. parameters; their hardware registers won't
+/// be used by other registers.
+pub fn assign(
+    consider_registers: &[&RegisterName],
+    assigned: &HashMap<RegisterName, RegisterAssign>,
+    ir_code: &ir::FunctionDefinition,
+    ctx: &Context,
+    analyzer: &analyzer::BindedAnalyzer,
+    current_used_stack_space: &mut usize,
+) -> HashMap<RegisterName, RegisterAssign> {
+    let nodes = build_nodes(consider_registers, assigned, ir_code, ctx, analyzer);
+    let reserved: HashSet<_> = assigned.values().flat_map(assign_to_physical).collect();
+    let has_call = ir_code
+        .iter()
+        .any(|statement| matches!(statement, IRStatement::Call(call) if !is_builtin(&call.name)));
+    // callee-saved registers only need to be saved once in the prologue,
+    // while caller-saved registers need to be saved around every call
+    let preference: Vec<&str> = if has_call {
+        CALLEE_SAVED_REGISTERS
+            .iter()
+            .chain(CALLER_SAVED_REGISTERS.iter())
+            .cloned()
+            .collect()
+    } else {
+        CALLER_SAVED_REGISTERS
+            .iter()
+            .chain(CALLEE_SAVED_REGISTERS.iter())
+            .cloned()
+            .collect()
+    };
+    let preference: Vec<&str> = preference
+        .into_iter()
+        .filter(|it| !reserved.contains(*it))
+        .collect();
+    let interference: Vec<HashSet<usize>> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            nodes
+                .iter()
+                .enumerate()
+                .filter(|(j, other)| {
+                    i != *j && ranges_overlap(&node.live_ranges, &other.live_ranges)
+                })
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect();
+    let mut node_assign: Vec<Option<RegisterAssign>> =
+        nodes.iter().map(|it| it.precolored.clone()).collect();
+    let mut stack = simplify(&nodes, &interference, preference.len());
+    while let Some(node_index) = stack.pop() {
+        let node = &nodes[node_index];
+        let occupied: HashSet<String> = interference[node_index]
+            .iter()
+            .filter_map(|&it| node_assign[it].as_ref())
+            .flat_map(assign_to_physical)
+            .collect();
+        let free = preference
+            .iter()
+            .filter(|it| !occupied.contains(**it))
+            .take(node.words)
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        let assign = if free.len() < node.words {
+            let offset = *current_used_stack_space;
+            *current_used_stack_space += node.words * 4;
+            RegisterAssign::StackValue(offset)
+        } else if node.words == 1 {
+            RegisterAssign::Register(free.into_iter().next().unwrap())
+        } else {
+            RegisterAssign::MultipleRegisters(free)
+        };
+        node_assign[node_index] = Some(assign);
+    }
+    let mut result = HashMap::new();
+    for (node, assign) in nodes.iter().zip(node_assign) {
+        let assign = assign.unwrap();
+        for register in &node.registers {
+            if !assigned.contains_key(register) {
+                result.insert(register.clone(), assign.clone());
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::{
+            analyzer::{Analyzer, IsAnalyzer},
+            function::{basic_block::BasicBlock, test_util::*},
+            statement::Ret,
+            FunctionDefinition,
+        },
+        utility::data_type::Type,
+    };
+
+    fn assign_of(
+        function_definition: &FunctionDefinition,
+    ) -> (HashMap<RegisterName, RegisterAssign>, usize) {
+        let analyzer = Analyzer::new();
+        let binded = analyzer.bind(function_definition);
+        let register_usage = binded.register_usage();
+        let registers = register_usage.registers();
+        let mut stack_space = 0;
+        let result = assign(
+            &registers,
+            &HashMap::new(),
+            function_definition,
+            &Context::default(),
+            &binded,
+            &mut stack_space,
+        );
+        (result, stack_space)
+    }
+
+    #[test]
+    fn test_assign() {
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: Type::None,
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("bb0".to_string()),
+                    content: vec![
+                        binop_constant("m"),
+                        binop_constant("n"),
+                        binop("i0", "m", "n"),
+                        binop_constant("r"),
+                        jump("bb1"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![phi("i_bb1", "bb0", "i0", "bb2", "i1"), branch("bb2", "bb3")],
+                },
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![binop("i1", "i_bb1", "i_bb1"), jump("bb1")],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![Ret {
+                        value: Some(RegisterName("r".to_string()).into()),
+                    }
+                    .into()],
+                },
+            ],
+        };
+        let (assign, stack_space) = assign_of(&function_definition);
+        let of = |name: &str| assign[&RegisterName(name.to_string())].clone();
+        assert_eq!(stack_space, 0);
+        assert!(matches!(of("m"), RegisterAssign::Register(_)));
+        assert_ne!(of("m"), of("n"));
+        assert_ne!(of("i0"), of("r"));
+        assert_eq!(of("i0"), of("i_bb1"));
+        assert_eq!(of("i0"), of("i1"));
+        // `r` is alive in the loop
+        assert_ne!(of("i1"), of("r"));
+    }
+
+    #[test]
+    fn test_spill() {
+        let available = CALLER_SAVED_REGISTERS.len() + CALLEE_SAVED_REGISTERS.len();
+        let count = available + 2;
+        let mut content: Vec<_> = (0..count)
+            .map(|i| binop_constant(&format!("v{i}")))
+            .collect();
+        // all values are alive at the same time, and `v0` is used much more than others
+        for i in 1..count {
+            content.push(binop(&format!("s{i}"), "v0", &format!("v{i}")));
+        }
+        content.push(Ret { value: None }.into());
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: Type::None,
+            },
+            content: vec![BasicBlock {
+                name: Some("bb0".to_string()),
+                content,
+            }],
+        };
+        let (assign, stack_space) = assign_of(&function_definition);
+        let spilled = assign
+            .values()
+            .filter(|it| matches!(it, RegisterAssign::StackValue(_)))
+            .count();
+        // the values can't all fit in registers, simplifying may spill a bit more than needed
+        assert!(spilled >= count - available);
+        assert_eq!(stack_space, spilled * 4);
+        assert!(matches!(
+            assign[&RegisterName("v0".to_string())],
+            RegisterAssign::Register(_)
+        ));
+        // only `v0` is still alive when the last sum is calculated
+        assert!(matches!(
+            assign[&RegisterName(format!("s{}", count - 1))],
+            RegisterAssign::Register(_)
+        ));
+    }
+}
@@ -0,0 +1,742 @@
+use core::fmt;
+use std::{
+    collections::{HashMap, HashSet},
+    iter,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    ir::{
+        self,
+        analyzer::{self, LiveRange},
+        function::parameter::Parameter,
+        statement::{IRStatement, IsIRStatement, Phi},
+        RegisterName,
+    },
+    utility::data_type::Type,
+};
+
+use super::{Context, HasSize, RegisterAllocator};
+
+/// Graph coloring register allocator.
+mod graph_coloring;
+
+/// How a logical register is mapped to real hardware register or memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegisterAssign {
+    /// The logical register is mapped to a hardware register.
+    Register(String),
+    /// The logical register is mapped to a set of hardware register.
+    MultipleRegisters(Vec<String>),
+    /// The logical register is actually alias to some stack space created by alloca and should only be used in `load` and `store`.
+    StackRef(usize),
+    /// The logical register is spilled to the stack.
+    StackValue(usize),
+}
+
+impl fmt::Display for RegisterAssign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegisterAssign::Register(register) => write!(f, "{register}"),
+            RegisterAssign::MultipleRegisters(registers) => write!(
+                f,
+                "{}",
+                registers
+                    .iter()
+                    .map(|register| register.to_string())
+                    .collect_vec()
+                    .join(",")
+            ),
+            RegisterAssign::StackRef(offset) => write!(f, "alias to {offset}(sp)"),
+            RegisterAssign::StackValue(offset) => write!(f, "{offset}(sp)"),
+        }
+    }
+}
+
+/// Registers for passing arguments, in order.
+const ARGUMENT_REGISTERS: [&str; 8] = ["a0", "a1", "a2", "a3", "a4", "a5", "a6", "a7"];
+
+/// Where an argument is passed, following the ILP32 calling convention.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgumentPosition {
+    /// The argument is passed in these argument registers, one word in each register.
+    Registers(Vec<String>),
+    /// The argument is passed on the stack, at this offset to the caller's `sp`.
+    Stack(usize),
+}
+
+/// Decide where each argument with `types` is passed.
+/// Return the positions and the size of stack space used for passing arguments.
+///
+/// Arguments take argument registers in order, structs take several consecutive ones.
+/// Once an argument cannot fit in the remaining argument registers, it and all the following
+/// arguments are passed on the stack.
+pub fn argument_positions<'a>(
+    types: impl IntoIterator<Item = &'a Type>,
+    ctx: &Context,
+) -> (Vec<ArgumentPosition>, usize) {
+    let mut positions = Vec::new();
+    let mut next_register = 0;
+    let mut stack_bytes = 0;
+    for data_type in types {
+        let words = ((data_type.size(ctx) + 7) / 8).div_ceil(4);
+        if next_register + words <= ARGUMENT_REGISTERS.len() {
+            positions.push(ArgumentPosition::Registers(
+                ARGUMENT_REGISTERS[next_register..next_register + words]
+                    .iter()
+                    .map(|it| it.to_string())
+                    .collect(),
+            ));
+            next_register += words;
+        } else {
+            next_register = ARGUMENT_REGISTERS.len();
+            positions.push(ArgumentPosition::Stack(stack_bytes));
+            stack_bytes += words * 4;
+        }
+    }
+    (positions, stack_bytes)
+}
+
+/// Assign registers for a [`ir::FunctionDefinition`].
+///
+/// Parameters passed on the stack are not assigned here, since they live in the caller's frame.
+pub fn assign_register(
+    ctx: &Context,
+    ir_code: &ir::FunctionDefinition,
+    analyzer: &analyzer::BindedAnalyzer,
+) -> (HashMap<ir::RegisterName, RegisterAssign>, usize) {
+    let mut register_assign = assign_param(&ir_code.header.parameters, ctx);
+    let mut current_used_stack_space = 0;
+    let alloca_registers: Vec<_> = ir_code
+        .iter()
+        .filter_map(|it| it.try_as_alloca())
+        .map(|it| it.generate_register().unwrap().0)
+        .collect();
+    let alloca_assign = assign_alloca(
+        &alloca_registers,
+        ctx,
+        ir_code,
+        &analyzer.register_usage(),
+        &mut current_used_stack_space,
+    );
+    register_assign.extend(alloca_assign);
+    let binding = analyzer.register_usage();
+    let consider_registers = binding
+        .registers()
+        .iter()
+        .filter(|&&it| !alloca_registers.contains(it))
+        .filter(|&&it| {
+            !ir_code
+                .header
+                .parameters
+                .iter()
+                .any(|param| it == &param.name)
+        })
+        .cloned()
+        .collect_vec();
+    if ctx.options.register_allocator == RegisterAllocator::GraphColoring {
+        let colored = graph_coloring::assign(
+            &consider_registers,
+            &register_assign,
+            ir_code,
+            ctx,
+            analyzer,
+            &mut current_used_stack_space,
+        );
+        register_assign.extend(colored);
+        return (register_assign, current_used_stack_space);
+    }
+    let mut live_ranges = register_live_ranges(consider_registers.iter().cloned(), ctx, analyzer);
+    let phied_registers = coalesce_phied_registers(ir_code, analyzer, &mut live_ranges);
+    let mut register_groups = register_groups(
+        &consider_registers,
+        phied_registers,
+        ctx,
+        &live_ranges,
+        &analyzer.register_usage(),
+    );
+    register_groups.sort_by_cached_key(|group| {
+        // todo: can be register usage count
+        group_live_ranges(group, &live_ranges)
+            .map(|range| range.block)
+            .collect::<HashSet<_>>()
+            .len()
+    });
+    let mut next_temporary_register_id = 2;
+    for group in register_groups {
+        let sample_register = group.iter().next().unwrap();
+
+        let data_type = analyzer.register_usage().get(sample_register).data_type();
+        let type_bytes = (data_type.size(ctx) + 7) / 8;
+        // values narrower than a word still take a whole register
+        let need_registers = type_bytes.div_ceil(4);
+        let assigned_to_register = if next_temporary_register_id + need_registers - 1 <= 6 {
+            let current_temporary_register_id = next_temporary_register_id;
+            next_temporary_register_id += need_registers;
+            if need_registers == 1 {
+                RegisterAssign::Register(format!("t{current_temporary_register_id}"))
+            } else {
+                RegisterAssign::MultipleRegisters(
+                    (current_temporary_register_id..current_temporary_register_id + need_registers)
+                        .map(|it| format!("t{it}"))
+                        .collect(),
+                )
+            }
+        } else {
+            let result = current_used_stack_space;
+            current_used_stack_space += need_registers * 4;
+            RegisterAssign::StackValue(result)
+        };
+
+        for register in group {
+            register_assign.insert(register.clone(), assigned_to_register.clone());
+        }
+    }
+    (register_assign, current_used_stack_space)
+}
+
+fn assign_param(params: &[Parameter], ctx: &Context) -> HashMap<ir::RegisterName, RegisterAssign> {
+    let mut result = HashMap::new();
+    let (positions, _) = argument_positions(params.iter().map(|it| &it.data_type), ctx);
+    for (param, position) in params.iter().zip(positions) {
+        if let ArgumentPosition::Registers(mut registers) = position {
+            let assigned_to_register = if registers.len() == 1 {
+                RegisterAssign::Register(registers.pop().unwrap())
+            } else {
+                RegisterAssign::MultipleRegisters(registers)
+            };
+            result.insert(param.name.clone(), assigned_to_register);
+        }
+    }
+    result
+}
+
+fn assign_alloca(
+    allocaed_registers: &[RegisterName],
+    ctx: &Context,
+    _ir_code: &ir::FunctionDefinition,
+    register_usage: &analyzer::BindedRegisterUsageAnalyzer,
+    current_used_stack_space: &mut usize,
+) -> HashMap<ir::RegisterName, RegisterAssign> {
+    let mut result = HashMap::new();
+    for register in allocaed_registers {
+        let data_type = register_usage
+            .register_usages()
+            .get(register)
+            .unwrap()
+            .alloca_type();
+        // keep every stack slot word aligned
+        let type_bytes = ((data_type.size(ctx) + 7) / 8).next_multiple_of(4);
+        result.insert(
+            register.clone(),
+            RegisterAssign::StackRef(*current_used_stack_space),
+        );
+        *current_used_stack_space += type_bytes;
+    }
+    result
+}
+
+/// Ranges in which each register in `registers` is alive.
+///
+/// Where phi targets are written in the predecessor blocks is not included here, see
+/// [`coalesce_phied_registers`].
+fn register_live_ranges<'a>(
+    registers: impl IntoIterator<Item = &'a RegisterName>,
+    ctx: &Context,
+    analyzer: &analyzer::BindedAnalyzer,
+) -> HashMap<RegisterName, Vec<LiveRange>> {
+    let liveness = analyzer.liveness();
+    let register_usage = analyzer.register_usage();
+    registers
+        .into_iter()
+        .map(|register| {
+            let mut ranges = liveness.live_ranges(register).to_vec();
+            let data_type = register_usage.get(register).data_type();
+            // a value taking several registers is written one word at a time, maybe before
+            // all operands of the statement are read, so it must not share registers with them
+            if ((data_type.size(ctx) + 7) / 8).div_ceil(4) > 1 {
+                for range in &mut ranges {
+                    range.from = range.from.and_then(|from| from.checked_sub(1));
+                }
+            }
+            (register.clone(), ranges)
+        })
+        .collect()
+}
+
+/// A value flowing into a phi target from a predecessor block.
+struct PhiEdge {
+    to: RegisterName,
+    /// The source register, `None` if the source is a constant.
+    from: Option<RegisterName>,
+    /// Where `to` is written if it doesn't share the hardware register with `from`,
+    /// ie. right before the terminator of the predecessor block until the end of it.
+    range: LiveRange,
+}
+
+fn phi_edges(
+    ir_code: &ir::FunctionDefinition,
+    analyzer: &analyzer::BindedAnalyzer,
+) -> Vec<PhiEdge> {
+    let control_flow_graph = analyzer.control_flow_graph();
+    let mut result = Vec::new();
+    for statement in ir_code.iter() {
+        if let IRStatement::Phi(Phi { to, from, .. }) = statement {
+            for from in from {
+                let block = control_flow_graph.basic_block_index_by_name(&from.block);
+                let statement_count = ir_code.content[block].content.len();
+                result.push(PhiEdge {
+                    to: to.clone(),
+                    from: from.value.clone().try_into().ok(),
+                    range: LiveRange {
+                        block,
+                        from: statement_count.checked_sub(2),
+                        to: None,
+                    },
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Whether any two registers in `group` are alive at the same time, if they share one hardware
+/// register.
+fn group_interferes(
+    group: &HashSet<RegisterName>,
+    edges: &[PhiEdge],
+    live_ranges: &HashMap<RegisterName, Vec<LiveRange>>,
+) -> bool {
+    let ranges = group
+        .iter()
+        .map(|register| {
+            let written_by_copies = edges.iter().filter(|edge| {
+                &edge.to == register && !edge.from.as_ref().is_some_and(|it| group.contains(it))
+            });
+            live_ranges[register]
+                .iter()
+                .chain(written_by_copies.map(|edge| &edge.range))
+                .collect_vec()
+        })
+        .collect_vec();
+    ranges
+        .iter()
+        .tuple_combinations()
+        .any(|(ranges1, ranges2)| ranges_overlap(ranges1.iter().cloned(), ranges2.iter().cloned()))
+}
+
+/// Group registers connected by phis, registers in one group share the same hardware register
+/// so the phis need no copy.
+///
+/// Registers are only grouped if they are never alive at the same time. Otherwise the phi target
+/// is copied from the source at the end of the predecessor block, and the ranges where it is
+/// written there are added to `live_ranges`.
+/// Only registers in `live_ranges` are grouped.
+fn coalesce_phied_registers(
+    ir_code: &ir::FunctionDefinition,
+    analyzer: &analyzer::BindedAnalyzer,
+    live_ranges: &mut HashMap<RegisterName, Vec<LiveRange>>,
+) -> Vec<HashSet<RegisterName>> {
+    let edges = phi_edges(ir_code, analyzer);
+    let mut groups: Vec<HashSet<RegisterName>> = Vec::new();
+    for edge in &edges {
+        let Some(from) = &edge.from else {
+            continue;
+        };
+        if !live_ranges.contains_key(&edge.to) || !live_ranges.contains_key(from) {
+            continue;
+        }
+        let to_group = groups.iter().position(|it| it.contains(&edge.to));
+        let from_group = groups.iter().position(|it| it.contains(from));
+        if to_group.is_some() && to_group == from_group {
+            continue;
+        }
+        let mut merged: HashSet<_> = [edge.to.clone(), from.clone()].into_iter().collect();
+        for &index in to_group.iter().chain(from_group.iter()) {
+            merged.extend(groups[index].iter().cloned());
+        }
+        if group_interferes(&merged, &edges, live_ranges) {
+            continue;
+        }
+        groups.retain(|it| it.is_disjoint(&merged));
+        groups.push(merged);
+    }
+    for edge in edges {
+        let same_group = |it: &RegisterName| {
+            groups
+                .iter()
+                .any(|group| group.contains(&edge.to) && group.contains(it))
+        };
+        if !edge.from.as_ref().is_some_and(same_group)
+            && let Some(ranges) = live_ranges.get_mut(&edge.to)
+        {
+            ranges.push(edge.range);
+        }
+    }
+    groups
+}
+
+/// Live ranges of all registers in `group`.
+fn group_live_ranges<'a>(
+    group: &'a HashSet<RegisterName>,
+    live_ranges: &'a HashMap<RegisterName, Vec<LiveRange>>,
+) -> impl Iterator<Item = &'a LiveRange> {
+    group.iter().filter_map(|it| live_ranges.get(it)).flatten()
+}
+
+/// Whether registers with `ranges1` and `ranges2` are alive at the same time somewhere.
+fn ranges_overlap<'a>(
+    ranges1: impl IntoIterator<Item = &'a LiveRange>,
+    ranges2: impl IntoIterator<Item = &'a LiveRange> + Clone,
+) -> bool {
+    ranges1.into_iter().any(|range1| {
+        ranges2
+            .clone()
+            .into_iter()
+            .any(|range2| range1.overlaps(range2))
+    })
+}
+
+fn register_groups(
+    consider_registers: &[&RegisterName],
+    phied_registers: Vec<HashSet<ir::RegisterName>>,
+    ctx: &Context,
+    live_ranges: &HashMap<RegisterName, Vec<LiveRange>>,
+    register_usage: &analyzer::BindedRegisterUsageAnalyzer,
+) -> Vec<HashSet<ir::RegisterName>> {
+    // values narrower than a word still take a whole register
+    let need_registers = |register: &RegisterName| {
+        let data_type = register_usage.get(register).data_type();
+        ((data_type.size(ctx) + 7) / 8).div_ceil(4)
+    };
+    // todo: phied_registers groups can also be mergered
+    let mut register_groups = phied_registers;
+    'a: for &register in consider_registers {
+        for register_group in register_groups.iter() {
+            if register_group.contains(register) {
+                continue 'a;
+            }
+        }
+        if need_registers(register) == 1 {
+            for register_group in register_groups.iter_mut() {
+                if register_group.iter().all(|it| need_registers(it) == 1)
+                    && !ranges_overlap(
+                        group_live_ranges(register_group, live_ranges),
+                        &live_ranges[register],
+                    )
+                {
+                    register_group.insert(register.clone());
+                    continue 'a;
+                }
+            }
+        }
+        register_groups.push(iter::once(register.clone()).collect());
+    }
+    register_groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ir::{
+            analyzer::IsAnalyzer,
+            function::{basic_block::BasicBlock, test_util::*},
+            statement::Ret,
+            FunctionDefinition,
+        },
+        utility::data_type::{self, Type},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_argument_positions() {
+        let mut ctx = Context::default();
+        ctx.struct_definitions.insert(
+            "S".to_string(),
+            ir::TypeDefinition {
+                name: "S".to_string(),
+                fields: vec![data_type::I32.clone(), data_type::I32.clone()],
+            },
+        );
+        let types = [
+            data_type::I32.clone(),
+            Type::StructRef("S".to_string()),
+            data_type::I64.clone(),
+            data_type::I32.clone(),
+            data_type::I32.clone(),
+            Type::StructRef("S".to_string()),
+            data_type::I32.clone(),
+        ];
+        let (positions, stack_bytes) = argument_positions(&types, &ctx);
+        let registers = |names: &[&str]| {
+            ArgumentPosition::Registers(names.iter().map(|it| it.to_string()).collect())
+        };
+        assert_eq!(
+            positions,
+            vec![
+                registers(&["a0"]),
+                registers(&["a1", "a2"]),
+                registers(&["a3", "a4"]),
+                registers(&["a5"]),
+                registers(&["a6"]),
+                ArgumentPosition::Stack(0),
+                ArgumentPosition::Stack(8),
+            ]
+        );
+        assert_eq!(stack_bytes, 12);
+    }
+
+    #[test]
+    fn test_collect_phied_registers() {
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: Type::None,
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![
+                        binop_constant("t0"),
+                        binop_constant("a1"),
+                        binop_constant("b0"),
+                        jump("bb3"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![
+                        binop_constant("t1"),
+                        binop_constant("a0"),
+                        binop_constant("b1"),
+                        jump("bb3"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![
+                        phi("t2", "bb1", "t0", "bb2", "t1"),
+                        phi("a2", "bb1", "a1", "bb2", "a0"),
+                        jump("bb5"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb4".to_string()),
+                    content: vec![binop_constant("t3"), jump("bb5")],
+                },
+                BasicBlock {
+                    name: Some("bb5".to_string()),
+                    content: vec![
+                        phi("t4", "bb3", "t2", "bb4", "t3"),
+                        Ret { value: None }.into(),
+                    ],
+                },
+            ],
+        };
+        let analyzer = analyzer::Analyzer::new();
+        let binded = analyzer.bind(&function_definition);
+        let register_usage = binded.register_usage();
+        let mut live_ranges =
+            register_live_ranges(register_usage.registers(), &Context::default(), &binded);
+        let phied_together_registers =
+            coalesce_phied_registers(&function_definition, &binded, &mut live_ranges);
+        assert_eq!(phied_together_registers.len(), 2);
+        let contains_t0 = phied_together_registers
+            .iter()
+            .find(|it| it.contains(&RegisterName("t0".to_string())))
+            .unwrap();
+        assert_eq!(contains_t0.len(), 5);
+        assert!(contains_t0.contains(&RegisterName("t1".to_string())));
+        assert!(contains_t0.contains(&RegisterName("t2".to_string())));
+        assert!(contains_t0.contains(&RegisterName("t3".to_string())));
+        assert!(contains_t0.contains(&RegisterName("t4".to_string())));
+        let contains_a0 = phied_together_registers
+            .iter()
+            .find(|it| it.contains(&RegisterName("a0".to_string())))
+            .unwrap();
+        assert!(contains_a0.contains(&RegisterName("a1".to_string())));
+        assert!(contains_a0.contains(&RegisterName("a2".to_string())));
+    }
+
+    #[test]
+    fn test_assign_register() {
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: Type::None,
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("bb0".to_string()),
+                    content: vec![
+                        binop_constant("m"),
+                        binop_constant("n"),
+                        binop_constant("u1"),
+                        binop("i0", "m", "m"),
+                        binop("j0", "n", "n"),
+                        binop("a0", "u1", "u1"),
+                        binop_constant("r"),
+                        jump("bb1"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![
+                        phi("i_bb1", "bb0", "i0", "bb3", "i2"),
+                        phi("a_bb1", "bb0", "a0", "bb3", "a1"),
+                        binop("i1", "i_bb1", "i_bb1"),
+                        binop("j1", "j0", "j0"),
+                        jump("bb2"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![
+                        binop("u2", "a_bb1", "a_bb1"),
+                        binop("a1", "u2", "i1"),
+                        jump("bb3"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![
+                        binop_constant("u3"),
+                        binop("i2", "u3", "j1"),
+                        branch("bb1", "bb4"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb4".to_string()),
+                    content: vec![Ret {
+                        value: Some(RegisterName("r".to_string()).into()),
+                    }
+                    .into()],
+                },
+            ],
+        };
+        let mut ctx = Context::default();
+        ctx.options.register_allocator = RegisterAllocator::Simple;
+        let analyzer = analyzer::Analyzer::new();
+        let (assign, stack_usage) = assign_register(
+            &ctx,
+            &function_definition,
+            &analyzer.bind(&function_definition),
+        );
+        assert_eq!(stack_usage, 0);
+        assert_ne!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("n".to_string())]
+        );
+        assert_ne!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("u1".to_string())]
+        );
+        // `m` dies where `i0` is defined
+        assert_eq!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("i0".to_string())]
+        );
+        assert_ne!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("j0".to_string())]
+        );
+        assert_ne!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("a0".to_string())]
+        );
+        assert_ne!(
+            assign[&RegisterName("m".to_string())],
+            assign[&RegisterName("r".to_string())]
+        );
+
+        assert_eq!(
+            assign[&RegisterName("i_bb1".to_string())],
+            assign[&RegisterName("i0".to_string())]
+        );
+        assert_eq!(
+            assign[&RegisterName("i_bb1".to_string())],
+            assign[&RegisterName("i2".to_string())]
+        );
+        assert_eq!(
+            assign[&RegisterName("a_bb1".to_string())],
+            assign[&RegisterName("a1".to_string())]
+        );
+        assert_eq!(
+            assign[&RegisterName("a_bb1".to_string())],
+            assign[&RegisterName("a0".to_string())]
+        );
+        assert_ne!(
+            assign[&RegisterName("i_bb1".to_string())],
+            assign[&RegisterName("a_bb1".to_string())]
+        );
+
+        // `n` dies before `a0` is defined
+        assert_eq!(
+            assign[&RegisterName("n".to_string())],
+            assign[&RegisterName("a0".to_string())]
+        );
+        assert_eq!(
+            assign[&RegisterName("i1".to_string())],
+            assign[&RegisterName("u3".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_phi_swap() {
+        // `a1` and `b1` swap in each iteration, they are both alive at the end of `bb2`
+        // so they must not be coalesced, although they are phied together
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: Type::None,
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("bb0".to_string()),
+                    content: vec![binop_constant("a0"), binop_constant("b0"), jump("bb1")],
+                },
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![
+                        phi("a1", "bb0", "a0", "bb2", "b1"),
+                        phi("b1", "bb0", "b0", "bb2", "a1"),
+                        branch("bb2", "bb3"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![jump("bb1")],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![
+                        binop("r", "a1", "b1"),
+                        Ret {
+                            value: Some(RegisterName("r".to_string()).into()),
+                        }
+                        .into(),
+                    ],
+                },
+            ],
+        };
+        for register_allocator in [RegisterAllocator::Simple, RegisterAllocator::GraphColoring] {
+            let mut ctx = Context::default();
+            ctx.options.register_allocator = register_allocator;
+            let analyzer = analyzer::Analyzer::new();
+            let (assign, _) = assign_register(
+                &ctx,
+                &function_definition,
+                &analyzer.bind(&function_definition),
+            );
+            let of = |name: &str| assign[&RegisterName(name.to_string())].clone();
+            assert_ne!(of("a1"), of("b1"));
+            assert_eq!(of("a0"), of("a1"));
+            assert_eq!(of("b0"), of("b1"));
+        }
+    }
+}
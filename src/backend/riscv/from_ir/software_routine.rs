@@ -0,0 +1,167 @@
+use std::collections::BTreeSet;
+
+/// Routines which implement operations in software, for cores which don't have the hardware for them.
+///
+/// All routines take their operands in `t0` and `t1`, put the results back in `t0` and `t1`,
+/// and keep all other registers (except `ra`) untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SoftwareRoutine {
+    /// `t0 = t0 * t1`
+    Mul,
+    /// `t0 = t0 / t1`, `t1 = t0 % t1`, operands are treated as unsigned.
+    UnsignedDivRem,
+    /// `t0 = t0 / t1`, `t1 = t0 % t1`, operands are treated as signed.
+    SignedDivRem,
+}
+
+impl SoftwareRoutine {
+    /// Label of the routine's entry.
+    pub fn label(self) -> &'static str {
+        match self {
+            SoftwareRoutine::Mul => "__come_mulsi3",
+            SoftwareRoutine::UnsignedDivRem => "__come_udivmodsi3",
+            SoftwareRoutine::SignedDivRem => "__come_divmodsi3",
+        }
+    }
+
+    /// Other routines this routine calls.
+    fn dependencies(self) -> &'static [SoftwareRoutine] {
+        match self {
+            SoftwareRoutine::SignedDivRem => &[SoftwareRoutine::UnsignedDivRem],
+            _ => &[],
+        }
+    }
+
+    /// Assembly code of the routine.
+    fn code(self) -> &'static str {
+        match self {
+            // shift-and-add
+            SoftwareRoutine::Mul => {
+                "__come_mulsi3:
+    addi sp, sp, -8
+    sw t2, 0(sp)
+    sw t3, 4(sp)
+    li t2, 0
+__come_mulsi3_loop:
+    beq t1, x0, __come_mulsi3_end
+    andi t3, t1, 1
+    beq t3, x0, __come_mulsi3_next
+    add t2, t2, t0
+__come_mulsi3_next:
+    slli t0, t0, 1
+    srli t1, t1, 1
+    j __come_mulsi3_loop
+__come_mulsi3_end:
+    mv t0, t2
+    lw t2, 0(sp)
+    lw t3, 4(sp)
+    addi sp, sp, 8
+    ret
+"
+            }
+            // restoring division, quotient bits are shifted into t0 while the dividend is shifted out
+            SoftwareRoutine::UnsignedDivRem => {
+                "__come_udivmodsi3:
+    addi sp, sp, -12
+    sw t2, 0(sp)
+    sw t3, 4(sp)
+    sw t4, 8(sp)
+    li t2, 0
+    li t3, 32
+__come_udivmodsi3_loop:
+    slli t2, t2, 1
+    srli t4, t0, 31
+    or t2, t2, t4
+    slli t0, t0, 1
+    bltu t2, t1, __come_udivmodsi3_next
+    sub t2, t2, t1
+    ori t0, t0, 1
+__come_udivmodsi3_next:
+    addi t3, t3, -1
+    bne t3, x0, __come_udivmodsi3_loop
+    mv t1, t2
+    lw t2, 0(sp)
+    lw t3, 4(sp)
+    lw t4, 8(sp)
+    addi sp, sp, 12
+    ret
+"
+            }
+            // divide the absolute values, then fix the signs
+            // the quotient is negative iff the signs of operands differ,
+            // and the remainder has the same sign as the dividend
+            // dividing by zero gives -1 and the dividend, same as the `div` and `rem` instructions
+            SoftwareRoutine::SignedDivRem => {
+                "__come_divmodsi3:
+    bne t1, x0, __come_divmodsi3_start
+    mv t1, t0
+    li t0, -1
+    ret
+__come_divmodsi3_start:
+    addi sp, sp, -12
+    sw ra, 0(sp)
+    sw t2, 4(sp)
+    sw t3, 8(sp)
+    srai t2, t0, 31
+    srai t3, t1, 31
+    xor t0, t0, t2
+    sub t0, t0, t2
+    xor t1, t1, t3
+    sub t1, t1, t3
+    jal ra, __come_udivmodsi3
+    xor t3, t3, t2
+    xor t0, t0, t3
+    sub t0, t0, t3
+    xor t1, t1, t2
+    sub t1, t1, t2
+    lw ra, 0(sp)
+    lw t2, 4(sp)
+    lw t3, 8(sp)
+    addi sp, sp, 12
+    ret
+"
+            }
+        }
+    }
+}
+
+/// Emit code for calling `routine`, operands should already be in `t0` and `t1`.
+pub fn emit_call(routine: SoftwareRoutine) -> String {
+    format!(
+        "    addi sp, sp, -4
+    sw ra, 0(sp)
+    jal ra, {}
+    lw ra, 0(sp)
+    addi sp, sp, 4
+",
+        routine.label()
+    )
+}
+
+/// Emit code for all `routines` used and the routines they depend on.
+pub fn emit_routines(routines: &BTreeSet<SoftwareRoutine>) -> String {
+    let mut all_routines = routines.clone();
+    for routine in routines {
+        all_routines.extend(routine.dependencies());
+    }
+    all_routines
+        .into_iter()
+        .map(SoftwareRoutine::code)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_routines() {
+        let used = BTreeSet::from([SoftwareRoutine::SignedDivRem]);
+        let code = emit_routines(&used);
+        assert!(code.contains("__come_divmodsi3:"));
+        assert!(code.contains("__come_udivmodsi3:"));
+        assert!(!code.contains("__come_mulsi3:"));
+        // should be accepted by the assembler
+        crate::backend::riscv::emit_clef(&format!(".section .text\n{code}")).unwrap();
+    }
+}
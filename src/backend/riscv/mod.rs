@@ -1,19 +1,66 @@
+/// Errors found while assembling.
+mod error;
+/// Constant expressions used as operands.
+mod expression;
 /// Functions for generating asm from IR
 pub mod from_ir;
+/// Filling addresses of symbols into instructions and data.
+mod relocation;
 /// Section name information and parser
 mod section;
 /// Instruction information parser
 pub mod simple_instruction;
 
-use self::{section::parse_section, simple_instruction::SimpleInstruction};
+pub use self::error::{AssembleError, AssembleErrorKind};
+use self::{
+    expression::{BinaryOperator, Context, Expression, SectionContext, Value},
+    section::parse_section,
+    simple_instruction::{
+        param::{self, Decided, Param},
+        SimpleInstruction,
+    },
+};
 use crate::{
-    binary_format::clef::{Architecture, Clef, Os, PendingSymbol, Section, SectionMeta, Symbol},
+    binary_format::clef::{
+        Architecture, Clef, Os, PendingSymbol, Relocation, RelocationKind, Section, SectionMeta,
+        Symbol,
+    },
     utility::parsing,
 };
 use bitvec::prelude::*;
 use itertools::Itertools;
+use nom::{bytes::complete::tag, multi::separated_list1};
 use section::SectionName;
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::OnceLock,
+};
+
+/// Parse the string literals separated by `,`, and concat their content.
+fn parse_string_literals(code: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut chars = code.trim().chars();
+    loop {
+        if chars.next()? != '"' {
+            return None;
+        }
+        loop {
+            let c = match chars.next()? {
+                '"' => break,
+                '\\' => expression::unescape(chars.next()?),
+                c => c,
+            };
+            let mut buffer = [0; 4];
+            result.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+        }
+        match chars.find(|c| !c.is_whitespace()) {
+            Some(',') => chars = chars.as_str().trim_start().chars(),
+            None => break,
+            Some(_) => return None,
+        }
+    }
+    Some(result)
+}
 
 /// Directive in an asm file.
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -22,21 +69,82 @@ enum Directive {
     Global(String),
     /// Marks the beginning of a section.
     Section(SectionName),
+    /// Places `values` into the section, each takes `unit_bytes` bytes.
+    Data {
+        unit_bytes: usize,
+        values: Vec<Expression>,
+    },
+    /// Places the bytes of strings into the section.
+    Ascii(Vec<u8>),
+    /// Places the given count of zero bytes into the section.
+    Zero(Expression),
+    /// Pads the section until its size is a multiple of the given bytes.
+    Align(Expression),
+    /// Defines a constant.
+    Equ(String, Expression),
 }
 
-fn parse_directive(line: &str) -> Directive {
-    let mut parts = line
-        .split(' ')
-        .map(|it| it.trim())
-        .filter(|it| !it.is_empty());
-    let first_part = parts.next().unwrap();
-    match first_part {
-        ".globl" | ".global" => Directive::Global(parts.next().unwrap().to_string()),
-        ".section" => Directive::Section(parse_section(parts.next().unwrap()).unwrap().1),
-        section_name => Directive::Section(parse_section(section_name).unwrap().1),
+/// Parse the whole `code` as an expression.
+fn parse_expression(code: &str) -> Option<Expression> {
+    match expression::parse(code) {
+        Ok(("", expression)) => Some(expression),
+        _ => None,
     }
 }
 
+fn parse_directive(line: &str) -> Result<Directive, AssembleErrorKind> {
+    let (first_part, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    let values = || match separated_list1(parsing::in_multispace(tag(",")), expression::parse)(rest)
+    {
+        Ok(("", values)) => Some(values),
+        _ => None,
+    };
+    let directive = match first_part {
+        ".globl" | ".global" => Some(Directive::Global(rest.to_string())),
+        ".section" => match parse_section(rest) {
+            Ok(("", section)) => Some(Directive::Section(section)),
+            _ => None,
+        },
+        ".word" => values().map(|values| Directive::Data {
+            unit_bytes: 4,
+            values,
+        }),
+        ".half" => values().map(|values| Directive::Data {
+            unit_bytes: 2,
+            values,
+        }),
+        ".byte" => values().map(|values| Directive::Data {
+            unit_bytes: 1,
+            values,
+        }),
+        ".ascii" => parse_string_literals(rest).map(Directive::Ascii),
+        ".asciz" | ".string" => parse_string_literals(rest).map(|mut bytes| {
+            bytes.push(0);
+            Directive::Ascii(bytes)
+        }),
+        ".zero" => parse_expression(rest).map(Directive::Zero),
+        // like GNU as on RISC-V, `.align` takes the power of 2
+        ".align" | ".p2align" => parse_expression(rest).map(|it| {
+            Directive::Align(Expression::Binary(
+                BinaryOperator::ShiftLeft,
+                Box::new(Expression::Integer(1)),
+                Box::new(it),
+            ))
+        }),
+        ".balign" => parse_expression(rest).map(Directive::Align),
+        ".equ" | ".set" => rest.split_once(',').and_then(|(name, value)| {
+            let value = parse_expression(value.trim())?;
+            Some(Directive::Equ(name.trim().to_string(), value))
+        }),
+        section_name => match parse_section(section_name) {
+            Ok(("", section)) => Some(Directive::Section(section)),
+            _ => None,
+        },
+    };
+    directive.ok_or_else(|| AssembleErrorKind::InvalidDirective(line.to_string()))
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct UnparsedInstruction {
     name: String,
@@ -54,68 +162,144 @@ enum Line {
     Directive(Directive),
 }
 
+/// A [`Line`] with the number of the line in the asm file it comes from, which starts from 1.
+type NumberedLine = (usize, Line);
+
+/// Split a memory operand like `4(sp)` or `%lo(symbol)(a0)` into the offset and the base.
+fn split_memory_operand(param: &str) -> Vec<String> {
+    let param = param.trim();
+    if let Some(inner) = param.strip_suffix(')')
+        && let Some(open) = inner.rfind('(')
+    {
+        let (offset, base) = (inner[..open].trim(), inner[open + 1..].trim());
+        // `%lo(symbol)` or `4 * (1 + 2)` is not a memory operand
+        if let Ok(("", Param::Decided(Decided::Register(_)))) = param::parse(base) {
+            return vec![offset.to_string(), base.to_string()];
+        }
+    }
+    vec![param.to_string()]
+}
+
 fn instruction_line(line: &str) -> Line {
     let (name, params) = line.split_once(' ').unwrap_or((line, ""));
-    let params = params
-        .replace('(', ",")
-        .replace(')', " ")
-        .split(',')
-        .map(|it| it.trim().to_string())
-        .collect();
+    let params = if params.trim().is_empty() {
+        Vec::new()
+    } else {
+        params.split(',').flat_map(split_memory_operand).collect()
+    };
     Line::Instruction(UnparsedInstruction {
         name: name.to_string(),
         params,
     })
 }
 
-fn preprocess(code: &str) -> Vec<Line> {
+fn preprocess(code: &str, errors: &mut Vec<AssembleError>) -> Vec<NumberedLine> {
     let mut result = Vec::new();
-    for line in code.lines().map(|it| it.trim()).filter(|it| !it.is_empty()) {
+    for (number, line) in code
+        .lines()
+        .enumerate()
+        .map(|(index, it)| (index + 1, it.trim()))
+        .filter(|(_, it)| !it.is_empty())
+    {
         if line.ends_with(':') {
-            result.push(Line::Tag(line.trim_end_matches(':').to_string()));
+            result.push((number, Line::Tag(line.trim_end_matches(':').to_string())));
         } else if line.starts_with('.') {
-            result.push(Line::Directive(parse_directive(line)));
+            match parse_directive(line) {
+                Ok(directive) => result.push((number, Line::Directive(directive))),
+                Err(kind) => errors.push(AssembleError::new(number, kind)),
+            }
         } else {
-            result.push(instruction_line(line));
+            result.push((number, instruction_line(line)));
         }
     }
     result
 }
 
-fn replace_complex_pseudo(preprocessed: &[Line]) -> Vec<Line> {
+fn replace_complex_pseudo(
+    preprocessed: &[NumberedLine],
+    errors: &mut Vec<AssembleError>,
+) -> Vec<NumberedLine> {
     let mut result = Vec::new();
-    for line in preprocessed {
+    let mut next_pcrel_label_id = 0;
+    // `li` is expanded by its value, so the constants defined before it are needed
+    let mut constants = HashMap::new();
+    // push a labeled `auipc`, returns the operand which gets the lower part of the distance
+    let mut pcrel_hi =
+        |result: &mut Vec<NumberedLine>, number: usize, register: &str, symbol: &str| {
+            let label = format!(".Lpcrel_hi{next_pcrel_label_id}");
+            next_pcrel_label_id += 1;
+            result.push((number, Line::Tag(label.clone())));
+            result.push((
+                number,
+                Line::Instruction(UnparsedInstruction {
+                    name: "auipc".to_string(),
+                    params: vec![register.to_string(), format!("%pcrel_hi({symbol})")],
+                }),
+            ));
+            format!("%pcrel_lo({label})")
+        };
+    for (number, line) in preprocessed {
+        let number = *number;
+        let mut push = |line| result.push((number, line));
         match line {
-            t @ Line::Tag(_tag) => result.push(t.clone()),
+            t @ Line::Tag(_tag) => push(t.clone()),
             Line::Instruction(UnparsedInstruction { name, params }) => match name.as_str() {
+                "li" | "la" if params.len() != 2 => errors.push(AssembleError::new(
+                    number,
+                    AssembleErrorKind::OperandCountMismatch {
+                        instruction: name.clone(),
+                        expected: 2,
+                        found: params.len(),
+                    },
+                )),
                 "li" => {
-                    let param: i64 = parsing::integer(&params[1]).unwrap().1;
+                    let context = Context {
+                        constants: &constants,
+                        section: None,
+                    };
+                    let param = match parse_expression(&params[1]) {
+                        Some(expression) => expression
+                            .evaluate_constant(&context)
+                            .ok_or_else(|| AssembleErrorKind::NotConstant(params[1].clone())),
+                        None => Err(AssembleErrorKind::InvalidOperand {
+                            instruction: name.clone(),
+                            operand: params[1].clone(),
+                        }),
+                    };
+                    let param = match param {
+                        Ok(param) => param,
+                        Err(kind) => {
+                            errors.push(AssembleError::new(number, kind));
+                            continue;
+                        }
+                    };
                     let lower = param & 0xfff;
                     let lower_is_negative = lower > 0x7ff;
-                    let higher = (if lower_is_negative {
+                    let higher = if lower_is_negative {
                         // lower is, in fact, a negative number when used in addi
                         (param >> 12) + 1
                     } else {
                         param >> 12
-                    }) & 0xffffffff;
+                    };
                     let lower = param - (higher << 12);
+                    let higher = higher & 0xfffff;
                     if higher == 0 && lower == 0 {
-                        result.push(Line::Instruction(UnparsedInstruction {
+                        push(Line::Instruction(UnparsedInstruction {
                             name: "mv".to_string(),
                             params: vec![params[0].clone(), "zero".to_string()],
                         }))
                     } else if higher == 0 {
-                        result.push(Line::Instruction(UnparsedInstruction {
+                        push(Line::Instruction(UnparsedInstruction {
                             name: "addi".to_string(),
                             params: vec![params[0].clone(), "x0".to_string(), format!("{lower}")],
                         }));
                     } else {
-                        result.push(Line::Instruction(UnparsedInstruction {
+                        push(Line::Instruction(UnparsedInstruction {
                             name: "lui".to_string(),
                             params: vec![params[0].clone(), format!("0x{higher:x}")],
                         }));
                         if lower != 0 {
-                            result.push(Line::Instruction(UnparsedInstruction {
+                            push(Line::Instruction(UnparsedInstruction {
                                 name: "addi".to_string(),
                                 params: vec![
                                     params[0].clone(),
@@ -126,12 +310,63 @@ fn replace_complex_pseudo(preprocessed: &[Line]) -> Vec<Line> {
                         }
                     }
                 }
-                _ => result.push(Line::Instruction(UnparsedInstruction {
+                "la" => {
+                    let lo = pcrel_hi(&mut result, number, &params[0], &params[1]);
+                    result.push((
+                        number,
+                        Line::Instruction(UnparsedInstruction {
+                            name: "addi".to_string(),
+                            params: vec![params[0].clone(), params[0].clone(), lo],
+                        }),
+                    ));
+                }
+                "call" | "tail" => {
+                    // `call symbol`, `call rd, symbol` or `tail symbol`
+                    let (link, base, symbol) = match (name.as_str(), params.as_slice()) {
+                        ("call", [symbol]) => ("ra", "ra", symbol),
+                        ("call", [register, symbol]) => {
+                            (register.as_str(), register.as_str(), symbol)
+                        }
+                        ("tail", [symbol]) => ("zero", "t1", symbol),
+                        _ => {
+                            errors.push(AssembleError::new(
+                                number,
+                                AssembleErrorKind::OperandCountMismatch {
+                                    instruction: name.clone(),
+                                    expected: 1,
+                                    found: params.len(),
+                                },
+                            ));
+                            continue;
+                        }
+                    };
+                    let lo = pcrel_hi(&mut result, number, base, symbol);
+                    result.push((
+                        number,
+                        Line::Instruction(UnparsedInstruction {
+                            name: "jalr".to_string(),
+                            params: vec![link.to_string(), lo, base.to_string()],
+                        }),
+                    ));
+                }
+                _ => push(Line::Instruction(UnparsedInstruction {
                     name: name.to_string(),
                     params: params.clone(),
                 })),
             },
-            d @ Line::Directive(_directive) => result.push(d.clone()),
+            d @ Line::Directive(directive) => {
+                if let Directive::Equ(name, value) = directive {
+                    let context = Context {
+                        constants: &constants,
+                        section: None,
+                    };
+                    // values depend on `.` are only known while assembling the section
+                    if let Some(value) = value.evaluate_constant(&context) {
+                        constants.insert(name.clone(), value);
+                    }
+                }
+                push(d.clone())
+            }
         }
     }
     result
@@ -139,9 +374,13 @@ fn replace_complex_pseudo(preprocessed: &[Line]) -> Vec<Line> {
 
 struct SimplePseudoTemplate {
     template: &'static str,
+    param_count: usize,
 }
 
-fn replace_simple_pseudo(complex_replaced: &[Line]) -> Vec<Line> {
+fn replace_simple_pseudo(
+    complex_replaced: &[NumberedLine],
+    errors: &mut Vec<AssembleError>,
+) -> Vec<NumberedLine> {
     static PSEUDO_SIMPLE_INSTRUCTIONS: OnceLock<HashMap<&'static str, SimplePseudoTemplate>> =
         OnceLock::new();
     let pseudo_simple_instructions = PSEUDO_SIMPLE_INSTRUCTIONS.get_or_init(|| {
@@ -153,176 +392,496 @@ fn replace_simple_pseudo(complex_replaced: &[Line]) -> Vec<Line> {
             .filter(|it| !it.is_empty());
         for template in templates {
             let (name, template) = template.split_once(' ').unwrap();
+            let param_count = (0..)
+                .take_while(|i| template.contains(&format!("{{{{params[{i}]}}}}")))
+                .count();
             pseudo_simple_instructions.insert(
                 name,
                 SimplePseudoTemplate {
                     template: template.trim(),
+                    param_count,
                 },
             );
         }
         pseudo_simple_instructions
     });
     let mut result = Vec::new();
-    for line in complex_replaced {
+    for (number, line) in complex_replaced {
         if let Line::Instruction(UnparsedInstruction { name, params }) = line {
-            if let Some(SimplePseudoTemplate { template }) =
-                pseudo_simple_instructions.get(name.as_str())
+            if let Some(SimplePseudoTemplate {
+                template,
+                param_count,
+            }) = pseudo_simple_instructions.get(name.as_str())
             {
+                if params.len() != *param_count {
+                    errors.push(AssembleError::new(
+                        *number,
+                        AssembleErrorKind::OperandCountMismatch {
+                            instruction: name.clone(),
+                            expected: *param_count,
+                            found: params.len(),
+                        },
+                    ));
+                    continue;
+                }
                 let mut replaced = template.to_string();
                 for (i, param) in params.iter().enumerate() {
                     let param_pattern = format!("{{{{params[{i}]}}}}");
                     replaced = replaced.replace(&param_pattern, param);
                 }
-                result.push(instruction_line(&replaced));
+                result.push((*number, instruction_line(&replaced)));
             } else {
-                result.push(line.clone());
+                result.push((*number, line.clone()));
             }
         } else {
-            result.push(line.clone());
+            result.push((*number, line.clone()));
         }
     }
     result
 }
 
-// todo: test
+/// `addi x0, x0, 0`
+const NOP: u32 = 0x00000013;
+
+/// A piece of content in a section.
+enum Content {
+    /// The instruction at this index of the instructions parsed.
+    Instruction(usize),
+    /// Raw data.
+    Data(BitVec<u32>),
+}
+
+/// Check whether a jump or branch from `place` to `target`, which are both offsets in bytes,
+/// can be encoded in the instruction.
+fn check_jump_distance(
+    kind: RelocationKind,
+    symbol: &str,
+    place: u32,
+    target: i64,
+) -> Result<(), AssembleErrorKind> {
+    let distance = target - place as i64;
+    let range = match kind {
+        RelocationKind::Branch => -(1 << 12)..1 << 12,
+        RelocationKind::Jal => -(1 << 20)..1 << 20,
+        _ => return Ok(()),
+    };
+    if distance % 2 != 0 {
+        Err(AssembleErrorKind::MisalignedTarget(symbol.to_string()))
+    } else if !range.contains(&distance) {
+        Err(AssembleErrorKind::TargetOutOfRange(symbol.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Assemble a section, returns its content, offsets of the labels defined in it and the symbols
+/// it uses but doesn't define.
+///
+/// Symbols marked by `.globl` are collected into `exported_symbols` with the line numbers, since
+/// they may be defined in other sections.
 fn parse_single_section(
-    simple_replaced: impl IntoIterator<Item = Line>,
-) -> (Vec<SimpleInstruction>, Vec<Symbol>, Vec<PendingSymbol>) {
+    section_name: SectionName,
+    simple_replaced: impl IntoIterator<Item = NumberedLine>,
+    constants: &mut HashMap<String, i64>,
+    exported_symbols: &mut Vec<(usize, String)>,
+    errors: &mut Vec<AssembleError>,
+) -> (BitVec<u32>, HashMap<String, u32>, Vec<PendingSymbol>) {
+    let section_name_str = format!("{section_name}");
     let mut current_offset_bytes = 0u32;
     let mut simple_instructions = Vec::new();
+    // the line number of each instruction in `simple_instructions`
+    let mut instruction_line_numbers = Vec::new();
+    let mut contents = Vec::new();
     let mut all_symbols = HashMap::new();
-    let mut exported_symbols = Vec::new();
-    let mut pending_symbols = HashMap::new();
-    for line in simple_replaced.into_iter() {
+    let mut data_relocations = Vec::new();
+    for (number, line) in simple_replaced.into_iter() {
+        let context = Context {
+            constants,
+            section: Some(SectionContext {
+                name: &section_name_str,
+                labels: &all_symbols,
+                location: current_offset_bytes,
+            }),
+        };
+        let mut error = |kind| errors.push(AssembleError::new(number, kind));
+        let evaluate_constant = |expression: &Expression| {
+            expression
+                .evaluate_constant(&context)
+                .ok_or_else(|| AssembleErrorKind::NotConstant(expression.to_string()))
+        };
         match line {
-            Line::Tag(tag) => {
-                all_symbols.insert(tag, current_offset_bytes);
+            Line::Tag(tag) => match all_symbols.entry(tag) {
+                Entry::Occupied(entry) => {
+                    error(AssembleErrorKind::Redefinition(entry.key().clone()))
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(current_offset_bytes);
+                }
+            },
+            Line::Instruction(mut unparsed) => {
+                // replace constant expressions by their values, and leave the symbols to
+                // relocations
+                for param in &mut unparsed.params {
+                    if let Ok(("", expression)) = expression::parse(param)
+                        && let Some(value) = expression.evaluate_constant(&context)
+                    {
+                        *param = value.to_string();
+                    }
+                }
+                match SimpleInstruction::try_from(unparsed) {
+                    Ok(mut instruction) => {
+                        instruction.set_offset_bytes(current_offset_bytes);
+                        current_offset_bytes += (instruction.bit_count() / 8) as u32;
+                        contents.push(Content::Instruction(simple_instructions.len()));
+                        simple_instructions.push(instruction);
+                        instruction_line_numbers.push(number);
+                    }
+                    Err(kind) => {
+                        error(kind);
+                        // keep the place of the instruction, so the offsets after it are right
+                        current_offset_bytes += 4;
+                        contents.push(Content::Data(NOP.view_bits::<Lsb0>().to_bitvec()));
+                    }
+                }
+            }
+            Line::Directive(Directive::Data { unit_bytes, values }) => {
+                let mut data = BitVec::new();
+                for value in values {
+                    let value = match value.evaluate(&context) {
+                        Some(Value::Constant(value)) => value,
+                        Some(Value::Symbol { name, addend }) if unit_bytes == 4 => {
+                            let offset_bytes = current_offset_bytes + (data.len() / 8) as u32;
+                            data_relocations.push((
+                                number,
+                                name,
+                                Relocation {
+                                    kind: RelocationKind::Abs32,
+                                    offset_bytes,
+                                    addend: addend as i32,
+                                },
+                            ));
+                            0
+                        }
+                        // addresses can only be placed in `.word`
+                        _ => {
+                            error(AssembleErrorKind::NotConstant(value.to_string()));
+                            0
+                        }
+                    };
+                    data.extend_from_bitslice(
+                        &(value as u64).view_bits::<Lsb0>()[..unit_bytes * 8],
+                    );
+                }
+                current_offset_bytes += (data.len() / 8) as u32;
+                contents.push(Content::Data(data));
             }
-            Line::Instruction(unparsed) => {
-                let mut instruction: SimpleInstruction = unparsed.clone().try_into().unwrap();
-                instruction.set_offset_bytes(current_offset_bytes);
-                current_offset_bytes += (instruction.bit_count() / 8) as u32;
-                simple_instructions.push(instruction);
+            Line::Directive(Directive::Ascii(bytes)) => {
+                current_offset_bytes += bytes.len() as u32;
+                contents.push(Content::Data(
+                    bytes.iter().flat_map(|it| it.view_bits::<Lsb0>()).collect(),
+                ));
             }
+            Line::Directive(Directive::Align(align)) => {
+                let align = match evaluate_constant(&align) {
+                    Ok(align) if align > 0 => align as u32,
+                    Ok(align) => {
+                        error(AssembleErrorKind::InvalidDirective(format!(
+                            ".balign {align}"
+                        )));
+                        continue;
+                    }
+                    Err(kind) => {
+                        error(kind);
+                        continue;
+                    }
+                };
+                let padding = current_offset_bytes.next_multiple_of(align) - current_offset_bytes;
+                current_offset_bytes += padding;
+                // code may run through the padding, so fill it with `nop`s if possible
+                let data = if section_name == SectionName::Text && padding % 4 == 0 {
+                    std::iter::repeat(NOP)
+                        .take(padding as usize / 4)
+                        .flat_map(|it| it.view_bits::<Lsb0>().to_bitvec())
+                        .collect()
+                } else {
+                    bitvec![u32, Lsb0; 0; padding as usize * 8]
+                };
+                contents.push(Content::Data(data));
+            }
+            Line::Directive(Directive::Zero(bytes)) => {
+                let bytes = match evaluate_constant(&bytes) {
+                    Ok(bytes) if bytes >= 0 => bytes as usize,
+                    Ok(bytes) => {
+                        error(AssembleErrorKind::InvalidDirective(format!(
+                            ".zero {bytes}"
+                        )));
+                        continue;
+                    }
+                    Err(kind) => {
+                        error(kind);
+                        continue;
+                    }
+                };
+                current_offset_bytes += bytes as u32;
+                contents.push(Content::Data(bitvec![u32, Lsb0; 0; bytes * 8]));
+            }
+            Line::Directive(Directive::Equ(name, value)) => match evaluate_constant(&value) {
+                Ok(value) => {
+                    constants.insert(name, value);
+                }
+                Err(kind) => error(kind),
+            },
             Line::Directive(Directive::Global(symbol_name)) => {
-                exported_symbols.push(symbol_name.clone());
+                exported_symbols.push((number, symbol_name));
             }
             Line::Directive(Directive::Section(_)) => {
                 unreachable!("Please separate sections before calling to_instructions");
             }
         }
     }
-    for (index, instruction) in simple_instructions.iter().enumerate() {
-        if let Some(pending_symbol) = instruction.pending_symbol() {
-            pending_symbols
-                .entry(pending_symbol.name.clone())
-                .or_insert(Vec::new())
-                .push(index);
+    let mut content = contents
+        .into_iter()
+        .fold(BitVec::new(), |mut acc, content| {
+            match content {
+                Content::Instruction(index) => {
+                    acc.extend_from_bitslice(&simple_instructions[index].render())
+                }
+                Content::Data(data) => acc.extend_from_bitslice(&data),
+            }
+            acc
+        });
+    let mut pending_symbols: HashMap<String, Vec<Relocation>> = HashMap::new();
+    let relocations = simple_instructions
+        .iter()
+        .zip(instruction_line_numbers)
+        .filter_map(|(it, number)| {
+            it.relocation()
+                .map(|(name, relocation)| (number, name.to_string(), relocation))
+        })
+        .collect_vec();
+    // `%pcrel_lo(label)` uses the symbol of the `%pcrel_hi` at `label`, and its address relative
+    // to the `auipc` there
+    let mut paired_relocations = Vec::new();
+    for (number, label, relocation) in &relocations {
+        if !matches!(
+            relocation.kind,
+            RelocationKind::PcrelLo12I | RelocationKind::PcrelLo12S
+        ) {
+            paired_relocations.push((*number, label.clone(), *relocation));
+            continue;
         }
+        let hi_offset_bytes = all_symbols.get(label).copied();
+        let hi = relocations.iter().find(|(_, _, it)| {
+            it.kind == RelocationKind::PcrelHi20 && Some(it.offset_bytes) == hi_offset_bytes
+        });
+        let Some((_, symbol, hi)) = hi else {
+            errors.push(AssembleError::new(
+                *number,
+                AssembleErrorKind::UnpairedPcrelLo(label.clone()),
+            ));
+            continue;
+        };
+        let addend = hi.addend + relocation.offset_bytes as i32 - hi.offset_bytes as i32;
+        paired_relocations.push((
+            *number,
+            symbol.clone(),
+            Relocation {
+                addend,
+                ..*relocation
+            },
+        ));
     }
-    pending_symbols.extract_if(|name, indexes| {
-        if let Some(symbol_offset_bytes) = all_symbols.get(name) {
-            for index in indexes {
-                simple_instructions[*index].decide_symbol(&Symbol {
-                    name: name.clone(),
-                    offset_bytes: *symbol_offset_bytes,
-                });
+    for (number, name, mut relocation) in paired_relocations.into_iter().chain(data_relocations) {
+        match all_symbols.get(&name) {
+            // relative addresses of symbols in this section can be decided now
+            Some(&symbol_offset_bytes) if !relocation.kind.is_absolute() => {
+                if let Err(kind) = check_jump_distance(
+                    relocation.kind,
+                    &name,
+                    relocation.offset_bytes,
+                    symbol_offset_bytes as i64 + relocation.addend as i64,
+                ) {
+                    errors.push(AssembleError::new(number, kind));
+                    continue;
+                }
+                relocation::apply(&relocation, symbol_offset_bytes, 0, &mut content);
             }
-            true
-        } else {
-            false
+            // absolute ones need the load address, so refer to them by the beginning of the
+            // section, which is valid even if the symbol is not exported
+            Some(&symbol_offset_bytes) => {
+                relocation.addend += symbol_offset_bytes as i32;
+                pending_symbols
+                    .entry(format!("{section_name}"))
+                    .or_default()
+                    .push(relocation);
+            }
+            None => pending_symbols.entry(name).or_default().push(relocation),
         }
-    });
-    let exported_symbols = exported_symbols
-        .into_iter()
-        .map(|name| Symbol {
-            offset_bytes: all_symbols[&name],
-            name,
-        })
-        .collect();
+    }
     let pending_symbols = pending_symbols
         .into_iter()
-        .map(|(name, offset_bytes)| PendingSymbol {
-            name,
-            pending_instructions_offset_bytes: offset_bytes
-                .into_iter()
-                .map(|index| simple_instructions[index].offset_bytes())
-                .collect(),
-        })
+        .map(|(name, relocations)| PendingSymbol { name, relocations })
         .collect();
-    (simple_instructions, exported_symbols, pending_symbols)
+    (content, all_symbols, pending_symbols)
 }
 
+/// Fill the addresses of symbols defined in the section described by `meta` into `content`.
+///
+/// If the section is loaded, symbols in other loaded sections can also be filled in by their
+/// absolute addresses in `external_addresses`.
+/// Returns the pending symbols which are still waiting for their addresses.
 pub fn resolve_pending_symbol(
-    symbols: &[Symbol],
-    pending_symbols: &[PendingSymbol],
+    meta: &SectionMeta,
     content: &mut BitVec<u32>,
+    external_addresses: &HashMap<String, u32>,
 ) -> Vec<PendingSymbol> {
+    let symbol_offsets = meta.symbol_offsets();
+    let section_address = meta.loadable.unwrap_or(0);
     let mut remaining_pending_symbols = Vec::new();
-    for pending_symbol in pending_symbols {
-        if let Some(corresponding_symbol) = symbols.iter().find(|it| it.name == pending_symbol.name)
-        {
-            for pending_instruction_offset_bytes in
-                &pending_symbol.pending_instructions_offset_bytes
-            {
-                let pending_instruction_offset_bits = pending_instruction_offset_bytes * 8;
-                let (_rest, mut instruction) = simple_instruction::parse_binary(
-                    (
-                        &content[pending_instruction_offset_bits as usize..],
-                        pending_instruction_offset_bits as usize,
-                    ),
-                    pending_symbols,
-                )
-                .unwrap();
-                instruction.decide_symbol(corresponding_symbol);
-                let binary_form = instruction.render();
-                content[pending_instruction_offset_bits as usize
-                    ..pending_instruction_offset_bits as usize + instruction.bit_count()]
-                    .copy_from_bitslice(&binary_form);
-            }
+    for pending_symbol in &meta.pending_symbols {
+        let symbol_address = if pending_symbol.name == meta.name {
+            Some(section_address)
+        } else if let Some(offset_bytes) = symbol_offsets.get(&pending_symbol.name) {
+            Some(section_address + offset_bytes)
+        } else if meta.loadable.is_some() {
+            external_addresses.get(&pending_symbol.name).copied()
         } else {
+            None
+        };
+        let Some(symbol_address) = symbol_address else {
             remaining_pending_symbols.push(pending_symbol.clone());
+            continue;
+        };
+        let (resolvable, waiting): (Vec<_>, Vec<_>) = pending_symbol
+            .relocations
+            .iter()
+            .copied()
+            .partition(|it| !it.kind.is_absolute() || meta.loadable.is_some());
+        for relocation in resolvable {
+            relocation::apply(&relocation, symbol_address, section_address, content);
+        }
+        if !waiting.is_empty() {
+            remaining_pending_symbols.push(PendingSymbol {
+                name: pending_symbol.name.clone(),
+                relocations: waiting,
+            });
         }
     }
     remaining_pending_symbols
 }
 
-// Emit clef file from an asm file.
-pub fn emit_clef(asm_code: &str) -> Clef {
+/// Emit clef file from an asm file, or all the errors found in it.
+pub fn emit_clef(asm_code: &str) -> Result<Clef, Vec<AssembleError>> {
     let mut result = Clef::new(Architecture::RiscV, Os::BareMetal);
-    let preprocessed = preprocess(asm_code);
-    let replace_complex_pseudo_done = replace_complex_pseudo(&preprocessed);
-    let replace_simple_pseudo_done = replace_simple_pseudo(&replace_complex_pseudo_done);
-    let mut line_iter = replace_simple_pseudo_done.into_iter();
-    while !line_iter.is_empty() {
-        let first_line = line_iter.next().unwrap();
-        let current_section = if let Line::Directive(Directive::Section(section)) = first_line {
-            section
-        } else {
-            panic!("First line must be a section directive");
+    let mut errors = Vec::new();
+    let preprocessed = preprocess(asm_code, &mut errors);
+    let replace_complex_pseudo_done = replace_complex_pseudo(&preprocessed, &mut errors);
+    let replace_simple_pseudo_done =
+        replace_simple_pseudo(&replace_complex_pseudo_done, &mut errors);
+    let is_section =
+        |(_, line): &NumberedLine| matches!(line, Line::Directive(Directive::Section(_)));
+    let mut line_iter = replace_simple_pseudo_done.into_iter().peekable();
+    let mut constants = HashMap::new();
+    // only constants can be defined before any section
+    while let Some((number, line)) = line_iter.next_if(|it| !is_section(it)) {
+        let Line::Directive(Directive::Equ(name, value)) = line else {
+            errors.push(AssembleError::new(
+                number,
+                AssembleErrorKind::OutsideSection,
+            ));
+            continue;
+        };
+        let context = Context {
+            constants: &constants,
+            section: None,
         };
-        let this_section_lines =
-            line_iter.take_while_ref(|it| !matches!(it, Line::Directive(Directive::Section(_))));
-        let (instructions, symbols, pending_symbols) =
-            parse_single_section(this_section_lines.into_iter());
+        match value.evaluate_constant(&context) {
+            Some(value) => {
+                constants.insert(name, value);
+            }
+            None => errors.push(AssembleError::new(
+                number,
+                AssembleErrorKind::NotConstant(value.to_string()),
+            )),
+        }
+    }
+    let mut exported_symbols = Vec::new();
+    // labels defined in each section
+    let mut section_labels = Vec::new();
+    while let Some((_, Line::Directive(Directive::Section(current_section)))) = line_iter.next() {
+        let this_section_lines = line_iter.peeking_take_while(|it| !is_section(it));
+        let (content, labels, pending_symbols) = parse_single_section(
+            current_section,
+            this_section_lines,
+            &mut constants,
+            &mut exported_symbols,
+            &mut errors,
+        );
+        section_labels.push(labels);
         result.sections.push(Section {
             meta: SectionMeta {
                 name: format!("{current_section}"),
                 linkable: true,
                 loadable: None,
-                symbols,
+                symbols: Vec::new(),
                 pending_symbols,
             },
-            content: instructions.into_iter().map(|it| it.render()).fold(
-                BitVec::new(),
-                |mut acc, instruction| {
-                    acc.extend_from_bitslice(&instruction);
-                    acc
-                },
-            ),
+            content,
         })
     }
-    result
+    // a symbol can be exported in any section, and is defined in the section of its label
+    for (number, name) in exported_symbols {
+        let defined_in = section_labels
+            .iter()
+            .zip(&mut result.sections)
+            .find_map(|(labels, section)| Some((*labels.get(&name)?, section)));
+        match defined_in {
+            Some((offset_bytes, section)) => {
+                section.meta.symbols.push(Symbol { name, offset_bytes })
+            }
+            None => errors.push(AssembleError::new(
+                number,
+                AssembleErrorKind::UndefinedSymbol(name),
+            )),
+        }
+    }
+    // labels in other sections are referred to by the beginning of their sections, which is
+    // valid even if the label is not exported
+    let label_positions: HashMap<&str, (String, u32)> = section_labels
+        .iter()
+        .zip(&result.sections)
+        .rev()
+        .flat_map(|(labels, section)| {
+            labels.iter().map(|(label, &offset_bytes)| {
+                (label.as_str(), (section.meta.name.clone(), offset_bytes))
+            })
+        })
+        .collect();
+    for section in &mut result.sections {
+        let mut pending_symbols: HashMap<String, Vec<Relocation>> = HashMap::new();
+        for PendingSymbol { name, relocations } in section.meta.pending_symbols.drain(..) {
+            match label_positions.get(name.as_str()) {
+                Some((section_name, offset_bytes)) => pending_symbols
+                    .entry(section_name.clone())
+                    .or_default()
+                    .extend(relocations.into_iter().map(|it| Relocation {
+                        addend: it.addend + *offset_bytes as i32,
+                        ..it
+                    })),
+                None => pending_symbols.entry(name).or_default().extend(relocations),
+            }
+        }
+        section.meta.pending_symbols = pending_symbols
+            .into_iter()
+            .map(|(name, relocations)| PendingSymbol { name, relocations })
+            .collect();
+    }
+    if errors.is_empty() {
+        Ok(result)
+    } else {
+        errors.sort_by_key(|it| it.line);
+        Err(errors)
+    }
 }
 
 #[cfg(test)]
@@ -339,9 +898,13 @@ mod tests {
                 not t3, t4
                 lb t5, 4(t6)
         "#;
-        let preprocessed = preprocess(code);
+        let preprocessed = preprocess(code, &mut Vec::new());
         assert_eq!(
-            preprocessed,
+            preprocessed.iter().map(|(number, _)| *number).collect_vec(),
+            vec![2, 3, 4, 5, 6]
+        );
+        assert_eq!(
+            preprocessed.into_iter().map(|(_, it)| it).collect_vec(),
             vec![
                 Line::Tag("label".to_string()),
                 Line::Instruction(UnparsedInstruction {
@@ -385,9 +948,10 @@ mod tests {
                 params: vec!["t5".to_string(), "4".to_string(), "t6".to_string()],
             }),
         ];
-        let result = replace_complex_pseudo(&lines);
+        let lines = lines.into_iter().map(|it| (1, it)).collect_vec();
+        let result = replace_complex_pseudo(&lines, &mut Vec::new());
         assert_eq!(
-            result,
+            result.into_iter().map(|(_, it)| it).collect_vec(),
             vec![
                 Line::Tag("label".to_string()),
                 Line::Instruction(UnparsedInstruction {
@@ -423,9 +987,10 @@ mod tests {
                 params: vec!["t0".to_string(), "t1".to_string()],
             }),
         ];
-        let result = replace_simple_pseudo(&lines);
+        let lines = lines.into_iter().map(|it| (1, it)).collect_vec();
+        let result = replace_simple_pseudo(&lines, &mut Vec::new());
         assert_eq!(
-            result,
+            result.into_iter().map(|(_, it)| it).collect_vec(),
             vec![
                 Line::Tag("label".to_string()),
                 Line::Instruction(UnparsedInstruction {
@@ -448,7 +1013,7 @@ main_entry:
     li t2, 0x998
     not t3, t4
     sw t5, 4(t6)"#;
-        let result = emit_clef(code);
+        let result = emit_clef(code).unwrap();
         assert_eq!(result.sections[0].meta.name, ".text");
         assert_eq!(result.sections[0].meta.symbols[0].name, "main");
         assert_eq!(result.sections[0].meta.symbols[0].offset_bytes, 0);
@@ -474,4 +1039,377 @@ main_entry:
             0x1efa223
         );
     }
+
+    #[test]
+    fn test_emit_clef_data() {
+        let code = r#"
+.section .data
+.global a
+a:
+    .word 0x12345678, -1
+.global b
+b:
+    .half 0x9abc
+    .byte 1, 2
+.section .bss
+.global c
+c:
+    .zero 8"#;
+        let result = emit_clef(code).unwrap();
+        assert_eq!(result.sections[0].meta.name, ".data");
+        assert_eq!(result.sections[0].meta.symbols[0].name, "a");
+        assert_eq!(result.sections[0].meta.symbols[0].offset_bytes, 0);
+        assert_eq!(result.sections[0].meta.symbols[1].name, "b");
+        assert_eq!(result.sections[0].meta.symbols[1].offset_bytes, 8);
+        assert_eq!(result.sections[0].content.len(), 32 * 3);
+        assert_eq!(
+            result.sections[0].content[0..32].load_le::<u32>(),
+            0x12345678
+        );
+        assert_eq!(
+            result.sections[0].content[32..32 * 2].load_le::<u32>(),
+            0xffffffff
+        );
+        assert_eq!(
+            result.sections[0].content[32 * 2..32 * 3].load_le::<u32>(),
+            0x02019abc
+        );
+        assert_eq!(result.sections[1].meta.name, ".bss");
+        assert_eq!(result.sections[1].meta.symbols[0].name, "c");
+        assert_eq!(result.sections[1].content.len(), 64);
+        assert!(result.sections[1].content.not_any());
+    }
+
+    #[test]
+    fn test_emit_clef_relocations() {
+        let code = r#"
+.section .text
+.global main
+main:
+    lui a0, value
+    addi a0, a0, value
+    jal ra, f
+loop:
+    beq a0, zero, loop
+value:
+    addi a0, a0, 1"#;
+        let mut result = emit_clef(code).unwrap();
+        let section = &mut result.sections[0];
+        let mut pending_symbols = section.meta.pending_symbols.clone();
+        pending_symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(pending_symbols.len(), 2);
+        assert_eq!(pending_symbols[0].name, ".text");
+        assert_eq!(
+            pending_symbols[0].relocations,
+            vec![
+                Relocation {
+                    kind: RelocationKind::Hi20,
+                    offset_bytes: 0,
+                    addend: 16,
+                },
+                Relocation {
+                    kind: RelocationKind::Lo12I,
+                    offset_bytes: 4,
+                    addend: 16,
+                },
+            ]
+        );
+        assert_eq!(pending_symbols[1].name, "f");
+        assert_eq!(
+            pending_symbols[1].relocations,
+            vec![Relocation {
+                kind: RelocationKind::Jal,
+                offset_bytes: 8,
+                addend: 0,
+            }]
+        );
+        // branch to a symbol in the same section is decided while assembling
+        assert_eq!(section.content[32 * 3..32 * 4].load_le::<u32>(), 0x00050063);
+        section.meta.loadable = Some(0x8000_0000);
+        section.resolve_pending_symbols(Architecture::RiscV);
+        assert_eq!(section.content[0..32].load_le::<u32>(), 0x80000537);
+        assert_eq!(section.content[32..32 * 2].load_le::<u32>(), 0x01050513);
+        assert_eq!(section.meta.pending_symbols.len(), 1);
+        assert_eq!(section.meta.pending_symbols[0].name, "f");
+    }
+
+    #[test]
+    fn test_emit_clef_directives() {
+        let code = r#"
+.section .rodata
+.global message
+message:
+    .ascii "hi, \"a\"", "\n"
+    .asciz "b"
+    .align 2
+table:
+    .word message, table + 4, f
+    .half 1
+    .balign 8
+.section .text
+    addi a0, a0, 1
+    .align 3
+    ret"#;
+        let result = emit_clef(code).unwrap();
+        let rodata = &result.sections[0];
+        assert_eq!(rodata.meta.name, ".rodata");
+        assert_eq!(rodata.content.len(), 32 * 8);
+        let bytes: Vec<u8> = rodata.content.chunks(8).map(|it| it.load_le()).collect();
+        assert_eq!(&bytes[..11], b"hi, \"a\"\nb\0\0");
+        assert_eq!(bytes[12..24], [0; 12]);
+        assert_eq!(&bytes[24..28], &[1, 0, 0, 0]);
+        let mut pending_symbols = rodata.meta.pending_symbols.clone();
+        pending_symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(pending_symbols[0].name, ".rodata");
+        assert_eq!(
+            pending_symbols[0].relocations,
+            vec![
+                Relocation {
+                    kind: RelocationKind::Abs32,
+                    offset_bytes: 12,
+                    addend: 0,
+                },
+                Relocation {
+                    kind: RelocationKind::Abs32,
+                    offset_bytes: 16,
+                    addend: 16,
+                },
+            ]
+        );
+        assert_eq!(pending_symbols[1].name, "f");
+        assert_eq!(
+            pending_symbols[1].relocations,
+            vec![Relocation {
+                kind: RelocationKind::Abs32,
+                offset_bytes: 20,
+                addend: 0,
+            }]
+        );
+        let text = &result.sections[1];
+        assert_eq!(text.content.len(), 32 * 3);
+        assert_eq!(text.content[32..32 * 2].load_le::<u32>(), NOP);
+    }
+
+    #[test]
+    fn test_split_memory_operand() {
+        assert_eq!(split_memory_operand(" 4(sp)"), vec!["4", "sp"]);
+        assert_eq!(
+            split_memory_operand("%lo(symbol)(a0)"),
+            vec!["%lo(symbol)", "a0"]
+        );
+        assert_eq!(split_memory_operand("%lo(symbol)"), vec!["%lo(symbol)"]);
+        assert_eq!(split_memory_operand("a0"), vec!["a0"]);
+        assert_eq!(split_memory_operand("4 * (1 + 3)"), vec!["4 * (1 + 3)"]);
+        assert_eq!(
+            split_memory_operand("(OFFSET + 4)(a0)"),
+            vec!["(OFFSET + 4)", "a0"]
+        );
+    }
+
+    #[test]
+    fn test_emit_clef_expressions() {
+        let code = r#"
+.equ GPIO_BASE, 0x10000000
+.set OFFSET, 4 * (1 + 3)
+.section .text
+    li a0, GPIO_BASE + 0x10
+    addi a1, zero, 'a'
+    sw a1, OFFSET(a0)
+.section .rodata
+message:
+    .ascii "hello"
+.equ length, . - message
+    .byte length, ~length & 0xff"#;
+        let result = emit_clef(code).unwrap();
+        let word = |index: usize| {
+            result.sections[0].content[32 * index..32 * (index + 1)].load_le::<u32>()
+        };
+        // lui a0, 0x10000; addi a0, a0, 16
+        assert_eq!(word(0), 0x10000537);
+        assert_eq!(word(1), 0x01050513);
+        // addi a1, zero, 97
+        assert_eq!(word(2), 0x06100593);
+        // sw a1, 16(a0)
+        assert_eq!(word(3), 0x00b52823);
+        let bytes: Vec<u8> = result.sections[1]
+            .content
+            .chunks(8)
+            .map(|it| it.load_le())
+            .collect();
+        assert_eq!(bytes, b"hello\x05\xfa");
+    }
+
+    #[test]
+    fn test_emit_clef_pseudo_relocations() {
+        let code = r#"
+.section .text
+.global main
+main:
+    la a0, value
+    call f
+    tail main
+    lui a1, %hi(value)
+    sw a0, %lo(value)(a1)
+value:
+    addi a0, a0, 1"#;
+        let mut result = emit_clef(code).unwrap();
+        let section = &mut result.sections[0];
+        let word = |content: &BitVec<u32>, index: usize| {
+            content[32 * index..32 * (index + 1)].load_le::<u32>()
+        };
+        // auipc a0, 0; addi a0, a0, 32
+        assert_eq!(word(&section.content, 0), 0x00000517);
+        assert_eq!(word(&section.content, 1), 0x02050513);
+        // auipc t1, 0; jalr zero, -16(t1)
+        assert_eq!(word(&section.content, 4), 0x00000317);
+        assert_eq!(word(&section.content, 5), 0xff030067);
+        let mut pending_symbols = section.meta.pending_symbols.clone();
+        pending_symbols.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(pending_symbols.len(), 2);
+        assert_eq!(
+            pending_symbols[0].relocations,
+            vec![
+                Relocation {
+                    kind: RelocationKind::Hi20,
+                    offset_bytes: 24,
+                    addend: 32,
+                },
+                Relocation {
+                    kind: RelocationKind::Lo12S,
+                    offset_bytes: 28,
+                    addend: 32,
+                },
+            ]
+        );
+        assert_eq!(pending_symbols[1].name, "f");
+        assert_eq!(
+            pending_symbols[1].relocations,
+            vec![
+                Relocation {
+                    kind: RelocationKind::PcrelHi20,
+                    offset_bytes: 8,
+                    addend: 0,
+                },
+                Relocation {
+                    kind: RelocationKind::PcrelLo12I,
+                    offset_bytes: 12,
+                    addend: 4,
+                },
+            ]
+        );
+        section.meta.loadable = Some(0x8000_0000);
+        section.resolve_pending_symbols(Architecture::RiscV);
+        // lui a1, 0x80000; sw a0, 32(a1)
+        assert_eq!(word(&section.content, 6), 0x800005b7);
+        assert_eq!(word(&section.content, 7), 0x02a5a023);
+    }
+
+    #[test]
+    fn test_emit_clef_cross_section() {
+        let code = r#"
+.section .text
+.globl msg
+    la a0, msg
+.section .data
+    .word 0
+msg:
+    .word 1"#;
+        let result = emit_clef(code).unwrap();
+        let text = &result.sections[0];
+        assert!(text.meta.symbols.is_empty());
+        assert_eq!(text.meta.pending_symbols.len(), 1);
+        assert_eq!(text.meta.pending_symbols[0].name, ".data");
+        assert_eq!(
+            text.meta.pending_symbols[0].relocations,
+            vec![
+                Relocation {
+                    kind: RelocationKind::PcrelHi20,
+                    offset_bytes: 0,
+                    addend: 4,
+                },
+                Relocation {
+                    kind: RelocationKind::PcrelLo12I,
+                    offset_bytes: 4,
+                    addend: 8,
+                },
+            ]
+        );
+        let data = &result.sections[1];
+        assert_eq!(data.meta.symbols[0].name, "msg");
+        assert_eq!(data.meta.symbols[0].offset_bytes, 4);
+    }
+
+    #[test]
+    fn test_emit_clef_errors() {
+        let code = r#"
+.section .text
+main:
+    addi a0, a0, 5000
+    frob a0, a1
+    add a0, a1
+    beq a0, a1, odd
+    .byte 1
+odd:
+    .global missing
+    .wibble 3
+    li a0, undefined
+main:
+    jalr ra, %pcrel_lo(nowhere)(ra)
+    beq a0, a1, 3"#;
+        let errors = emit_clef(code).unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|it| (it.line, it.kind.clone()))
+                .collect_vec(),
+            vec![
+                (
+                    4,
+                    AssembleErrorKind::ImmediateOutOfRange {
+                        instruction: "addi".to_string(),
+                        value: 5000
+                    }
+                ),
+                (5, AssembleErrorKind::UnknownInstruction("frob".to_string())),
+                (
+                    6,
+                    AssembleErrorKind::OperandCountMismatch {
+                        instruction: "add".to_string(),
+                        expected: 3,
+                        found: 2
+                    }
+                ),
+                (7, AssembleErrorKind::MisalignedTarget("odd".to_string())),
+                (
+                    10,
+                    AssembleErrorKind::UndefinedSymbol("missing".to_string())
+                ),
+                (
+                    11,
+                    AssembleErrorKind::InvalidDirective(".wibble 3".to_string())
+                ),
+                (12, AssembleErrorKind::NotConstant("undefined".to_string())),
+                (13, AssembleErrorKind::Redefinition("main".to_string())),
+                (
+                    14,
+                    AssembleErrorKind::UnpairedPcrelLo("nowhere".to_string())
+                ),
+                (
+                    15,
+                    AssembleErrorKind::MisalignedOffset {
+                        instruction: "beq".to_string(),
+                        value: 3
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            format!("{}", errors[0].clone().with_file("main.s")),
+            "main.s:4: error: immediate `5000` is out of range for `addi`"
+        );
+        assert_eq!(
+            format!("{}", errors[9]),
+            "<source>:15: error: branch offset `3` of `beq` must be a multiple of 2"
+        );
+    }
 }
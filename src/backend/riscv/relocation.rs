@@ -0,0 +1,131 @@
+use bitvec::prelude::*;
+
+use crate::binary_format::clef::{Relocation, RelocationKind};
+
+/// Put `value` into the immediate field of `instruction` as `kind` requires.
+fn patch(instruction: u32, kind: RelocationKind, value: u32) -> u32 {
+    let bits = |high: u32, low: u32| (value >> low) & ((1 << (high - low + 1)) - 1);
+    // add 0x800 so the sign extended lower 12 bits can be added to the higher part
+    let hi20 = value.wrapping_add(0x800) & 0xfffff000;
+    match kind {
+        RelocationKind::Branch => {
+            (instruction & !0xfe000f80)
+                | (bits(12, 12) << 31)
+                | (bits(10, 5) << 25)
+                | (bits(4, 1) << 8)
+                | (bits(11, 11) << 7)
+        }
+        RelocationKind::Jal => {
+            (instruction & !0xfffff000)
+                | (bits(20, 20) << 31)
+                | (bits(10, 1) << 21)
+                | (bits(11, 11) << 20)
+                | (bits(19, 12) << 12)
+        }
+        RelocationKind::Hi20 | RelocationKind::PcrelHi20 => (instruction & !0xfffff000) | hi20,
+        RelocationKind::Lo12I | RelocationKind::PcrelLo12I => {
+            (instruction & !0xfff00000) | (bits(11, 0) << 20)
+        }
+        RelocationKind::Lo12S | RelocationKind::PcrelLo12S => {
+            (instruction & !0xfe000f80) | (bits(11, 5) << 25) | (bits(4, 0) << 7)
+        }
+        RelocationKind::Abs32 => value,
+    }
+}
+
+/// Fill the address of a symbol into `content` at `relocation`.
+///
+/// `symbol_address` and `section_address` are both absolute addresses, for relocations which are
+/// not [absolute](RelocationKind::is_absolute), they can also be both relative to the beginning
+/// of the section.
+pub fn apply(
+    relocation: &Relocation,
+    symbol_address: u32,
+    section_address: u32,
+    content: &mut BitSlice<u32>,
+) {
+    let place = section_address.wrapping_add(relocation.offset_bytes);
+    let target = symbol_address.wrapping_add_signed(relocation.addend);
+    let value = match relocation.kind {
+        RelocationKind::Hi20
+        | RelocationKind::Lo12I
+        | RelocationKind::Lo12S
+        | RelocationKind::Abs32 => target,
+        RelocationKind::Branch
+        | RelocationKind::Jal
+        | RelocationKind::PcrelHi20
+        | RelocationKind::PcrelLo12I
+        | RelocationKind::PcrelLo12S => target.wrapping_sub(place),
+    };
+    let start = relocation.offset_bytes as usize * 8;
+    let word = &mut content[start..start + 32];
+    word.store_le(patch(word.load_le(), relocation.kind, value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply a relocation on `instruction` which is at 0x8000_0004, returns the patched one.
+    fn applied(instruction: u32, kind: RelocationKind, symbol_address: u32, addend: i32) -> u32 {
+        let mut content = bitvec![u32, Lsb0; 0; 64];
+        content[32..64].store_le(instruction);
+        apply(
+            &Relocation {
+                kind,
+                offset_bytes: 4,
+                addend,
+            },
+            symbol_address,
+            0x8000_0000,
+            &mut content,
+        );
+        content[32..64].load_le()
+    }
+
+    #[test]
+    fn test_apply() {
+        // beq t0, t1, -8
+        assert_eq!(
+            applied(0x00628063, RelocationKind::Branch, 0x7fff_fffc, 0),
+            0xfe628ce3
+        );
+        // jal ra, 12
+        assert_eq!(
+            applied(0x000000ef, RelocationKind::Jal, 0x8000_0010, 0),
+            0x00c000ef
+        );
+        // lui a0, 0x12345; addi a0, a0, 0x678
+        assert_eq!(
+            applied(0x00000537, RelocationKind::Hi20, 0x1234_5678, 0),
+            0x12345537
+        );
+        assert_eq!(
+            applied(0x00050513, RelocationKind::Lo12I, 0x1234_5678, 0),
+            0x67850513
+        );
+        // lui a0, 0x12346; sw a1, -0x800(a0)
+        assert_eq!(
+            applied(0x00000537, RelocationKind::Hi20, 0x1234_5000, 0x800),
+            0x12346537
+        );
+        assert_eq!(
+            applied(0x00b52023, RelocationKind::Lo12S, 0x1234_5000, 0x800),
+            0x80b52023
+        );
+        assert_eq!(
+            applied(0xdeadbeef, RelocationKind::Abs32, 0x8000_0100, 4),
+            0x8000_0104
+        );
+        // auipc a0, 0x1 at 0x8000_0004, which points to 0x8000_0808
+        assert_eq!(
+            applied(0x00000517, RelocationKind::PcrelHi20, 0x8000_0808, 0),
+            0x00001517
+        );
+        // `addi a0, a0, -0x7f8` paired with `auipc a0, 0x1` at 0x8000_0000
+        assert_eq!(
+            applied(0x00050513, RelocationKind::PcrelLo12I, 0x8000_0808, 4),
+            0x80850513
+        );
+    }
+}
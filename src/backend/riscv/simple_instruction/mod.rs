@@ -20,15 +20,15 @@ use nom::{
 };
 
 use crate::{
-    binary_format::clef::{PendingSymbol, Symbol},
+    binary_format::clef::{PendingSymbol, Relocation, RelocationKind},
     utility::parsing::{ident, in_multispace},
 };
 
-use param::Param;
+use param::{Decided, Param, RelocationOperator};
 
-use self::template::Template;
+use self::{param_transformer::ParamTransformer, template::Template};
 
-use super::UnparsedInstruction;
+use super::{AssembleErrorKind, UnparsedInstruction};
 
 #[derive(Debug, Eq, Clone)]
 pub struct SimpleInstruction {
@@ -65,22 +65,30 @@ impl SimpleInstruction {
     pub fn offset_bytes(&self) -> u32 {
         self.offset_bytes.unwrap()
     }
-    pub fn pending_symbol(&self) -> Option<PendingSymbol> {
-        self.params
+    /// The symbol used by this instruction, and where its address should be filled in.
+    ///
+    /// For `%pcrel_lo(label)`, the symbol is the label of the paired `%pcrel_hi` instruction.
+    pub fn relocation(&self) -> Option<(&str, Relocation)> {
+        let (param_id, param) = self
+            .params
             .iter()
-            .find(|param| matches!(param, Param::Unresolved(_)))
-            .map(|param| PendingSymbol {
-                name: param.unwrap_symbol().to_string(),
-                pending_instructions_offset_bytes: self.offset_bytes.iter().cloned().collect(),
-            })
-    }
-    pub fn decide_symbol(&mut self, symbol: &Symbol) {
-        assert!(self.offset_bytes.is_some());
-        for param in self.params.iter_mut() {
-            if matches!(param, Param::Unresolved(_)) && param.unwrap_symbol() == symbol.name {
-                param.fill_symbol(symbol, self.offset_bytes.unwrap());
-            }
-        }
+            .enumerate()
+            .find(|(_, param)| matches!(param, Param::Unresolved(_) | Param::Relocated(..)))?;
+        let kind = symbol_relocation_kind(param, self.template.relocation_kind(param_id))
+            .unwrap_or_else(|| {
+                panic!(
+                    "`{param}` cannot be used as an operand of `{}`",
+                    self.template.name
+                )
+            });
+        Some((
+            param.unwrap_symbol(),
+            Relocation {
+                kind,
+                offset_bytes: self.offset_bytes(),
+                addend: 0,
+            },
+        ))
     }
     pub fn bit_count(&self) -> usize {
         self.template.bit_count()
@@ -91,18 +99,127 @@ impl SimpleInstruction {
     }
 }
 
+/// How the address of the symbol in `param` should be filled into a param, which is filled as
+/// `kind` if it is a symbol without relocation operators.
+fn symbol_relocation_kind(param: &Param, kind: Option<RelocationKind>) -> Option<RelocationKind> {
+    match (param, kind?) {
+        (Param::Unresolved(_), kind)
+        | (Param::Relocated(RelocationOperator::Hi, _), kind @ RelocationKind::Hi20)
+        | (
+            Param::Relocated(RelocationOperator::Lo, _),
+            kind @ (RelocationKind::Lo12I | RelocationKind::Lo12S),
+        )
+        | (Param::Relocated(RelocationOperator::PcrelHi, _), kind @ RelocationKind::PcrelHi20) => {
+            Some(kind)
+        }
+        (Param::Relocated(RelocationOperator::PcrelLo, _), RelocationKind::Lo12I) => {
+            Some(RelocationKind::PcrelLo12I)
+        }
+        (Param::Relocated(RelocationOperator::PcrelLo, _), RelocationKind::Lo12S) => {
+            Some(RelocationKind::PcrelLo12S)
+        }
+        _ => None,
+    }
+}
+
+/// Check whether `param` can be used as the `param_id`th param of `template`.
+fn check_param(
+    template: &Template,
+    param_id: usize,
+    param: &Param,
+) -> Result<(), AssembleErrorKind> {
+    let invalid = || AssembleErrorKind::InvalidOperand {
+        instruction: template.name.to_string(),
+        operand: param.to_string(),
+    };
+    let transformers = template.param_transformers(param_id);
+    let value = match (transformers.as_slice(), param) {
+        ([ParamTransformer::Register(_)], Param::Decided(Decided::Register(_)))
+        | ([ParamTransformer::Csr(_)], Param::Decided(Decided::Csr(_))) => return Ok(()),
+        ([ParamTransformer::Register(_) | ParamTransformer::Csr(_)], _) => return Err(invalid()),
+        (_, Param::Unresolved(_) | Param::Relocated(..)) => {
+            return symbol_relocation_kind(param, template.relocation_kind(param_id))
+                .map(|_| ())
+                .ok_or_else(invalid);
+        }
+        (_, Param::Decided(Decided::Immediate(value))) => *value as i64,
+        _ => return Err(invalid()),
+    };
+    let bits = transformers
+        .iter()
+        .filter_map(|it| match it {
+            ParamTransformer::BitsAt(bits_at) => Some(bits_at.end as u32),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let relocation_kind = template.relocation_kind(param_id);
+    if matches!(
+        relocation_kind,
+        Some(RelocationKind::Jal | RelocationKind::Branch)
+    ) && value % 2 != 0
+    {
+        return Err(AssembleErrorKind::MisalignedOffset {
+            instruction: template.name.to_string(),
+            value,
+        });
+    }
+    let in_range = match relocation_kind {
+        // offset from this instruction
+        Some(RelocationKind::Jal) => (-(1 << 20)..1 << 20).contains(&value),
+        // branch targets are decided when rendering
+        Some(RelocationKind::Branch) => true,
+        Some(RelocationKind::Lo12I | RelocationKind::Lo12S) => {
+            (-(1 << 11)..1 << 11).contains(&value)
+        }
+        // the higher part of an address, which can be written as either signed or unsigned
+        Some(RelocationKind::Hi20 | RelocationKind::PcrelHi20) => {
+            (-(1 << 19)..1 << 20).contains(&value)
+        }
+        _ => (0..1 << bits).contains(&value),
+    };
+    if in_range {
+        Ok(())
+    } else {
+        Err(AssembleErrorKind::ImmediateOutOfRange {
+            instruction: template.name.to_string(),
+            value,
+        })
+    }
+}
+
 impl TryFrom<UnparsedInstruction> for SimpleInstruction {
-    type Error = ();
+    type Error = AssembleErrorKind;
 
     fn try_from(value: UnparsedInstruction) -> Result<Self, Self::Error> {
-        let template = template::templates().get(value.name.as_str()).ok_or(())?;
+        let template = template::templates()
+            .get(value.name.as_str())
+            .ok_or_else(|| AssembleErrorKind::UnknownInstruction(value.name.clone()))?;
+        if value.params.len() != template.param_count() {
+            return Err(AssembleErrorKind::OperandCountMismatch {
+                instruction: value.name,
+                expected: template.param_count(),
+                found: value.params.len(),
+            });
+        }
+        let params = value
+            .params
+            .iter()
+            .enumerate()
+            .map(|(param_id, code)| {
+                let Ok(("", param)) = param::parse(code) else {
+                    return Err(AssembleErrorKind::InvalidOperand {
+                        instruction: value.name.clone(),
+                        operand: code.clone(),
+                    });
+                };
+                check_param(template, param_id, &param)?;
+                Ok(param)
+            })
+            .collect::<Result<_, _>>()?;
         Ok(Self {
             template,
-            params: value
-                .params
-                .into_iter()
-                .map(|it| param::parse(&it).unwrap().1)
-                .collect(),
+            params,
             offset_bytes: None,
         })
     }
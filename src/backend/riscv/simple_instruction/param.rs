@@ -2,14 +2,15 @@ use std::{collections::HashMap, fmt::Display, sync::OnceLock};
 
 use nom::{
     branch::alt,
-    bytes::complete::take_while,
+    bytes::complete::{tag, take_while},
     character::{is_alphanumeric, is_space},
-    combinator::map,
+    combinator::{map, opt, recognize},
+    sequence::{delimited, pair},
     AsBytes, IResult,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{binary_format::clef::Symbol, utility::parsing};
+use crate::utility::parsing;
 
 /// A decided param.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -33,6 +34,31 @@ impl Display for Decided {
     }
 }
 
+/// An operator which chooses the part of a symbol's address used by an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum RelocationOperator {
+    /// `%hi(symbol)`, higher 20 bits of the address.
+    Hi,
+    /// `%lo(symbol)`, lower 12 bits of the address.
+    Lo,
+    /// `%pcrel_hi(symbol)`, higher 20 bits of the distance from this instruction to the symbol.
+    PcrelHi,
+    /// `%pcrel_lo(label)`, lower 12 bits of the distance used by the `%pcrel_hi` instruction at
+    /// `label`.
+    PcrelLo,
+}
+
+impl Display for RelocationOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelocationOperator::Hi => write!(f, "%hi"),
+            RelocationOperator::Lo => write!(f, "%lo"),
+            RelocationOperator::PcrelHi => write!(f, "%pcrel_hi"),
+            RelocationOperator::PcrelLo => write!(f, "%pcrel_lo"),
+        }
+    }
+}
+
 /// Parameter of an instruction.
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum Param {
@@ -40,6 +66,8 @@ pub enum Param {
     Decided(Decided),
     /// An unresolved symbol.
     Unresolved(String),
+    /// An unresolved symbol with a relocation operator.
+    Relocated(RelocationOperator, String),
     /// A resolved symbol.
     Resolved(String, Decided),
 }
@@ -48,6 +76,7 @@ impl Display for Param {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Param::Unresolved(s) => write!(f, "{s}"),
+            Param::Relocated(operator, s) => write!(f, "{operator}({s})"),
             Param::Resolved(s, content) => write!(f, "{s} <{content}>"),
             Param::Decided(x) => write!(f, "{x}"),
         }
@@ -60,7 +89,7 @@ impl Param {
             Param::Decided(Decided::Immediate(i)) => *i,
             Param::Resolved(_, Decided::Immediate(i)) => *i,
             // todo: maybe create a separated function and panic here when meet up with Unresolved
-            Param::Unresolved(_) => 0,
+            Param::Unresolved(_) | Param::Relocated(..) => 0,
             _ => panic!("Expected immediate!"),
         }
     }
@@ -79,20 +108,11 @@ impl Param {
     pub fn unwrap_symbol(&self) -> &str {
         match self {
             Param::Unresolved(s) => s,
+            Param::Relocated(_, s) => s,
             Param::Resolved(s, _) => s,
             _ => panic!("Expected symbol!"),
         }
     }
-    pub fn fill_symbol(&mut self, symbol: &Symbol, instruction_offset_bytes: u32) {
-        let symbol_offset_bytes = symbol.offset_bytes as i64;
-        let instruction_offset_bytes = instruction_offset_bytes as i64;
-        let distance_from_instruction_to_symbol = symbol_offset_bytes - instruction_offset_bytes;
-        assert_eq!(self.unwrap_symbol(), symbol.name);
-        *self = Param::Resolved(
-            symbol.name.clone(),
-            Decided::Immediate(distance_from_instruction_to_symbol as _),
-        );
-    }
 }
 
 fn parse_csr_bytes(code: &[u8]) -> IResult<&[u8], u16> {
@@ -169,6 +189,22 @@ fn parse_register(code: &str) -> IResult<&str, u8> {
         .map_err(|_| nom::Err::Error(nom::error::Error::new(code, nom::error::ErrorKind::Tag)))
 }
 
+/// Parse a symbol name, which is an ident, or an ident prefixed by `.` for local labels.
+pub fn parse_symbol(code: &str) -> IResult<&str, String> {
+    map(recognize(pair(opt(tag(".")), parsing::ident)), |it| {
+        it.to_string()
+    })(code)
+}
+
+fn parse_relocation_operator(code: &str) -> IResult<&str, RelocationOperator> {
+    alt((
+        map(tag("%hi"), |_| RelocationOperator::Hi),
+        map(tag("%lo"), |_| RelocationOperator::Lo),
+        map(tag("%pcrel_hi"), |_| RelocationOperator::PcrelHi),
+        map(tag("%pcrel_lo"), |_| RelocationOperator::PcrelLo),
+    ))(code)
+}
+
 /// Parses asm code to get a [`Param`] instance.
 pub fn parse(code: &str) -> IResult<&str, Param> {
     alt((
@@ -177,7 +213,14 @@ pub fn parse(code: &str) -> IResult<&str, Param> {
         map(parsing::in_multispace(parsing::integer), |it| {
             Param::Decided(Decided::Immediate(it))
         }),
-        map(parsing::ident, Param::Unresolved),
+        map(
+            pair(
+                parse_relocation_operator,
+                delimited(tag("("), parsing::in_multispace(parse_symbol), tag(")")),
+            ),
+            |(operator, symbol)| Param::Relocated(operator, symbol),
+        ),
+        map(parse_symbol, Param::Unresolved),
     ))(code)
 }
 
@@ -264,6 +307,21 @@ mod tests {
             parse("stupid_function"),
             Ok(("", Param::Unresolved("stupid_function".to_string())))
         );
+        assert_eq!(parse(".L0"), Ok(("", Param::Unresolved(".L0".to_string()))));
+        assert_eq!(
+            parse("%hi(stupid_function)"),
+            Ok((
+                "",
+                Param::Relocated(RelocationOperator::Hi, "stupid_function".to_string())
+            ))
+        );
+        assert_eq!(
+            parse("%pcrel_lo(.L0)"),
+            Ok((
+                "",
+                Param::Relocated(RelocationOperator::PcrelLo, ".L0".to_string())
+            ))
+        );
         assert!(parse(",").is_err());
     }
 }
@@ -2,7 +2,7 @@ use std::{collections::HashMap, sync::OnceLock};
 
 use crate::{
     backend::riscv::simple_instruction::template,
-    binary_format::clef::PendingSymbol,
+    binary_format::clef::{PendingSymbol, RelocationKind},
     utility::parsing::{self, in_multispace},
 };
 use bitvec::prelude::*;
@@ -18,7 +18,7 @@ use nom::{
 
 use super::{
     param::Decided,
-    param_transformer::{self, IsParamTransformer, ParamTransformer},
+    param_transformer::{self, BitsAt, IsParamTransformer, ParamTransformer},
     Param,
 };
 
@@ -97,6 +97,48 @@ impl Template {
         }
         current_result
     }
+    /// Count of params the instruction takes.
+    pub fn param_count(&self) -> usize {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::ParamTransformer((id, _)) => Some(id + 1),
+                Part::BitPattern(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+    /// The transformers which put the `param_id`th param into the instruction.
+    pub fn param_transformers(&self, param_id: usize) -> Vec<&ParamTransformer> {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::ParamTransformer((id, transformer)) if *id == param_id => Some(transformer),
+                _ => None,
+            })
+            .collect_vec()
+    }
+    /// How the address of a symbol should be filled into the `param_id`th param, returns `None`
+    /// if a symbol cannot be used as this param.
+    pub fn relocation_kind(&self, param_id: usize) -> Option<RelocationKind> {
+        match self.param_transformers(param_id).as_slice() {
+            [ParamTransformer::JalForm(_)] => Some(RelocationKind::Jal),
+            [ParamTransformer::BranchHigh(_), ParamTransformer::BranchLow(_)]
+            | [ParamTransformer::BranchLow(_), ParamTransformer::BranchHigh(_)] => {
+                Some(RelocationKind::Branch)
+            }
+            [ParamTransformer::BitsAt(BitsAt { start: 0, end: 20 })] if self.name == "auipc" => {
+                Some(RelocationKind::PcrelHi20)
+            }
+            [ParamTransformer::BitsAt(BitsAt { start: 0, end: 20 })] => Some(RelocationKind::Hi20),
+            [ParamTransformer::BitsAt(BitsAt { start: 0, end: 12 })] => Some(RelocationKind::Lo12I),
+            [ParamTransformer::BitsAt(BitsAt { start: 0, end: 5 }), ParamTransformer::BitsAt(BitsAt { start: 5, end: 12 })]
+            | [ParamTransformer::BitsAt(BitsAt { start: 5, end: 12 }), ParamTransformer::BitsAt(BitsAt { start: 0, end: 5 })] => {
+                Some(RelocationKind::Lo12S)
+            }
+            _ => None,
+        }
+    }
     pub fn bit_count(&self) -> usize {
         self.parts
             .iter()
@@ -165,6 +207,7 @@ impl Template {
                     if let Some(pending_symbol) = symbol_param {
                         let param = Param::Unresolved(pending_symbol.name.clone());
                         params[param_id] = Some(param);
+                        bits = &bits[transformer.bit_count()..];
                     } else {
                         let param = params
                             .get_mut(param_id)
@@ -226,7 +269,7 @@ pub fn templates() -> &'static HashMap<&'static str, Template> {
 #[cfg(test)]
 mod tests {
 
-    use crate::backend::riscv::simple_instruction::param_transformer::{BitsAt, Register};
+    use crate::backend::riscv::simple_instruction::param_transformer::Register;
 
     use super::*;
 
@@ -281,4 +324,59 @@ mod tests {
             bits![1, 0, 1, 1, 0, 1, 0, 0, 0, 0, 1, 1, 0, 1, 1, 1]
         );
     }
+
+    #[test]
+    fn test_m_extension() {
+        let registers = [
+            Param::Decided(Decided::Register(10)),
+            Param::Decided(Decided::Register(11)),
+            Param::Decided(Decided::Register(12)),
+        ];
+        for (name, binary) in [
+            ("mul", 0x02c5_8533u32),
+            ("mulh", 0x02c5_9533),
+            ("div", 0x02c5_c533),
+            ("divu", 0x02c5_d533),
+            ("rem", 0x02c5_e533),
+            ("remu", 0x02c5_f533),
+        ] {
+            let rendered = templates()[name].render(&registers, 0);
+            assert_eq!(rendered.load_le::<u32>(), binary);
+        }
+    }
+
+    #[test]
+    fn test_relocation_kind() {
+        let templates = templates();
+        assert_eq!(
+            templates["jal"].relocation_kind(1),
+            Some(RelocationKind::Jal)
+        );
+        assert_eq!(
+            templates["bne"].relocation_kind(2),
+            Some(RelocationKind::Branch)
+        );
+        assert_eq!(
+            templates["lui"].relocation_kind(1),
+            Some(RelocationKind::Hi20)
+        );
+        assert_eq!(
+            templates["auipc"].relocation_kind(1),
+            Some(RelocationKind::PcrelHi20)
+        );
+        assert_eq!(
+            templates["addi"].relocation_kind(2),
+            Some(RelocationKind::Lo12I)
+        );
+        assert_eq!(
+            templates["lw"].relocation_kind(1),
+            Some(RelocationKind::Lo12I)
+        );
+        assert_eq!(
+            templates["sw"].relocation_kind(1),
+            Some(RelocationKind::Lo12S)
+        );
+        assert_eq!(templates["slli"].relocation_kind(2), None);
+        assert_eq!(templates["add"].relocation_kind(2), None);
+    }
 }
@@ -35,6 +35,14 @@ srl         0000000{{params[2] | register}}{{params[1] | register}}101{{params[0
 sra         0100000{{params[2] | register}}{{params[1] | register}}101{{params[0] | register}}0110011
 or          0000000{{params[2] | register}}{{params[1] | register}}110{{params[0] | register}}0110011
 and         0000000{{params[2] | register}}{{params[1] | register}}111{{params[0] | register}}0110011
+mul         0000001{{params[2] | register}}{{params[1] | register}}000{{params[0] | register}}0110011
+mulh        0000001{{params[2] | register}}{{params[1] | register}}001{{params[0] | register}}0110011
+mulhsu      0000001{{params[2] | register}}{{params[1] | register}}010{{params[0] | register}}0110011
+mulhu       0000001{{params[2] | register}}{{params[1] | register}}011{{params[0] | register}}0110011
+div         0000001{{params[2] | register}}{{params[1] | register}}100{{params[0] | register}}0110011
+divu        0000001{{params[2] | register}}{{params[1] | register}}101{{params[0] | register}}0110011
+rem         0000001{{params[2] | register}}{{params[1] | register}}110{{params[0] | register}}0110011
+remu        0000001{{params[2] | register}}{{params[1] | register}}111{{params[0] | register}}0110011
 csrrw       {{params[1] | csr}}{{params[2] | register}}001{{params[0] | register}}1110011
 csrrs       {{params[1] | csr}}{{params[2] | register}}010{{params[0] | register}}1110011
 csrrc       {{params[1] | csr}}{{params[2] | register}}011{{params[0] | register}}1110011
@@ -22,17 +22,86 @@ use super::control_flow::{CFSelector, CFSelectorSegment, ControlFlowElement};
 
 fn lower_type(t: &Type) -> ValType {
     match t {
-        crate::utility::data_type::Type::Integer(Integer { signed, width }) => {
-            match (signed, width) {
-                (true, 32) => ValType::I32,
-                (true, 64) => ValType::I64,
-                _ => unimplemented!(),
-            }
-        }
+        crate::utility::data_type::Type::Integer(Integer { width, .. }) => match width {
+            8 | 16 | 32 => ValType::I32,
+            64 => ValType::I64,
+            _ => unimplemented!(),
+        },
+        crate::utility::data_type::Type::Address => ValType::I32,
         _ => unimplemented!(),
     }
 }
 
+/// Size of a value of type `t` in the linear memory, in bytes.
+fn type_bytes(t: &Type) -> i32 {
+    match t {
+        Type::Address => 4,
+        Type::Integer(Integer { width, .. }) => *width as i32 / 8,
+        Type::Array(element_type, length) => type_bytes(element_type) * *length as i32,
+        Type::StructRef(_) => unimplemented!(),
+        Type::None => unreachable!(),
+    }
+}
+
+fn load_instruction(t: &Type) -> Instruction<'static> {
+    let memarg = MemArg {
+        offset: 0,
+        align: 0,
+        memory_index: 0,
+    };
+    match t {
+        Type::Integer(Integer {
+            signed: true,
+            width: 8,
+        }) => Instruction::I32Load8S(memarg),
+        Type::Integer(Integer {
+            signed: false,
+            width: 8,
+        }) => Instruction::I32Load8U(memarg),
+        Type::Integer(Integer {
+            signed: true,
+            width: 16,
+        }) => Instruction::I32Load16S(memarg),
+        Type::Integer(Integer {
+            signed: false,
+            width: 16,
+        }) => Instruction::I32Load16U(memarg),
+        Type::Integer(Integer { width: 64, .. }) => Instruction::I64Load(memarg),
+        _ => Instruction::I32Load(memarg),
+    }
+}
+
+fn store_instruction(t: &Type) -> Instruction<'static> {
+    let memarg = MemArg {
+        offset: 0,
+        align: 0,
+        memory_index: 0,
+    };
+    match t {
+        Type::Integer(Integer { width: 8, .. }) => Instruction::I32Store8(memarg),
+        Type::Integer(Integer { width: 16, .. }) => Instruction::I32Store16(memarg),
+        Type::Integer(Integer { width: 64, .. }) => Instruction::I64Store(memarg),
+        _ => Instruction::I32Store(memarg),
+    }
+}
+
+/// Put the address `register` refers to onto the stack.
+/// The address is either some stack space created by alloca, or the value of a pointer register.
+fn put_address_onto_stack(
+    register: &RegisterName,
+    register_name_id_map: &HashMap<RegisterName, u32>,
+    offset_table: &HashMap<RegisterName, i32>,
+    result: &mut Function,
+) {
+    if let Some(offset) = offset_table.get(register) {
+        result.instruction(&Instruction::GlobalGet(0));
+        result.instruction(&Instruction::I32Const(*offset));
+        result.instruction(&Instruction::I32Sub);
+    } else {
+        result.instruction(&Instruction::LocalGet(register_name_id_map[register]));
+    }
+}
+
 pub fn lower_function_type(header: &FunctionHeader) -> (Vec<ValType>, Vec<ValType>) {
     let parameter_types = header
         .parameters
@@ -76,24 +145,44 @@ fn lower_unary_calculate(
     unary_calculate: &unary::UnaryCalculate,
 ) {
     let data_type = lower_type(&unary_calculate.data_type);
-    match data_type {
-        ValType::I32 => result.instruction(&Instruction::I32Const(0)),
-        ValType::I64 => result.instruction(&Instruction::I64Const(0)),
-        _ => unimplemented!(),
-    };
-    put_value_onto_stack(
-        &unary_calculate.operand,
-        register_name_id_map,
-        result,
-        data_type,
-    );
     match &unary_calculate.operation {
-        unary::UnaryOperation::Neg => match data_type {
-            ValType::I32 => result.instruction(&Instruction::I32Sub),
-            ValType::I64 => result.instruction(&Instruction::I64Sub),
-            _ => unimplemented!(),
-        },
-        unary::UnaryOperation::Not => unimplemented!(),
+        // -x <=> 0 - x
+        unary::UnaryOperation::Neg => {
+            match data_type {
+                ValType::I32 => result.instruction(&Instruction::I32Const(0)),
+                ValType::I64 => result.instruction(&Instruction::I64Const(0)),
+                _ => unimplemented!(),
+            };
+            put_value_onto_stack(
+                &unary_calculate.operand,
+                register_name_id_map,
+                result,
+                data_type,
+            );
+            match data_type {
+                ValType::I32 => result.instruction(&Instruction::I32Sub),
+                ValType::I64 => result.instruction(&Instruction::I64Sub),
+                _ => unimplemented!(),
+            };
+        }
+        // ~x <=> x ^ -1
+        unary::UnaryOperation::Not => {
+            put_value_onto_stack(
+                &unary_calculate.operand,
+                register_name_id_map,
+                result,
+                data_type,
+            );
+            match data_type {
+                ValType::I32 => result
+                    .instruction(&Instruction::I32Const(-1))
+                    .instruction(&Instruction::I32Xor),
+                ValType::I64 => result
+                    .instruction(&Instruction::I64Const(-1))
+                    .instruction(&Instruction::I64Xor),
+                _ => unimplemented!(),
+            };
+        }
     };
     let result_register_id = register_name_id_map[&unary_calculate.to];
     result.instruction(&Instruction::LocalSet(result_register_id));
@@ -143,6 +232,26 @@ fn lower_binary_calculate(
             ValType::I64 => result.instruction(&Instruction::I64GeS),
             _ => unimplemented!(),
         },
+        binary::BinaryOperation::UnsignedLessThan => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32LtU),
+            ValType::I64 => result.instruction(&Instruction::I64LtU),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::UnsignedLessOrEqualThan => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32LeU),
+            ValType::I64 => result.instruction(&Instruction::I64LeU),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::UnsignedGreaterThan => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32GtU),
+            ValType::I64 => result.instruction(&Instruction::I64GtU),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::UnsignedGreaterOrEqualThan => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32GeU),
+            ValType::I64 => result.instruction(&Instruction::I64GeU),
+            _ => unimplemented!(),
+        },
         binary::BinaryOperation::Equal => match data_type {
             ValType::I32 => result.instruction(&Instruction::I32Eq),
             ValType::I64 => result.instruction(&Instruction::I64Eq),
@@ -188,6 +297,31 @@ fn lower_binary_calculate(
             ValType::I64 => result.instruction(&Instruction::I64ShrS),
             _ => unimplemented!(),
         },
+        binary::BinaryOperation::Mul => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32Mul),
+            ValType::I64 => result.instruction(&Instruction::I64Mul),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::SignedDiv => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32DivS),
+            ValType::I64 => result.instruction(&Instruction::I64DivS),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::UnsignedDiv => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32DivU),
+            ValType::I64 => result.instruction(&Instruction::I64DivU),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::SignedRem => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32RemS),
+            ValType::I64 => result.instruction(&Instruction::I64RemS),
+            _ => unimplemented!(),
+        },
+        binary::BinaryOperation::UnsignedRem => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32RemU),
+            ValType::I64 => result.instruction(&Instruction::I64RemU),
+            _ => unimplemented!(),
+        },
     };
     let result_register_id = register_name_id_map[&binary_calculate.to];
     result.instruction(&Instruction::LocalSet(result_register_id));
@@ -286,48 +420,56 @@ fn lower_statement(
             result.instruction(&Instruction::Return);
         }
         IRStatement::Load(load) => {
-            result.instruction(&Instruction::GlobalGet(0));
-            let offset = match &load.from {
-                Quantity::RegisterName(register) => offset_table.get(register).unwrap(),
+            match &load.from {
+                Quantity::RegisterName(register) => {
+                    put_address_onto_stack(register, register_name_id_map, offset_table, result)
+                }
                 Quantity::GlobalVariableName(_) => unimplemented!(),
                 Quantity::NumberLiteral(_) => unimplemented!(),
             };
-            result.instruction(&Instruction::I32Const(*offset));
-            result.instruction(&Instruction::I32Sub);
-            result.instruction(&Instruction::I32Load(MemArg {
-                offset: 0,
-                align: 0,
-                memory_index: 0,
-            }));
+            result.instruction(&load_instruction(&load.data_type));
             let load_target = register_name_id_map.get(&load.to).unwrap();
             result.instruction(&Instruction::LocalSet(*load_target));
         }
         IRStatement::Store(store) => {
-            // todo: handle data types
-            result.instruction(&Instruction::GlobalGet(0));
-            let offset = match &store.target {
-                Quantity::RegisterName(register) => offset_table.get(register).unwrap(),
-                Quantity::GlobalVariableName(_) => unimplemented!(),
-                Quantity::NumberLiteral(_) => unimplemented!(),
-            };
-            result.instruction(&Instruction::I32Const(*offset));
-            result.instruction(&Instruction::I32Sub);
-            match &store.source {
+            match &store.target {
                 Quantity::RegisterName(register) => {
-                    // fixme: register maybe another memory Address
-                    let register_id = register_name_id_map.get(register).unwrap();
-                    result.instruction(&Instruction::LocalGet(*register_id));
-                }
-                Quantity::NumberLiteral(n) => {
-                    result.instruction(&Instruction::I32Const(*n as _));
+                    put_address_onto_stack(register, register_name_id_map, offset_table, result)
                 }
                 Quantity::GlobalVariableName(_) => unimplemented!(),
+                Quantity::NumberLiteral(_) => unimplemented!(),
+            };
+            put_value_onto_stack(
+                &store.source,
+                register_name_id_map,
+                result,
+                lower_type(&store.data_type),
+            );
+            result.instruction(&store_instruction(&store.data_type));
+        }
+        IRStatement::ElementAddress(element_address) => {
+            let Quantity::RegisterName(base) = &element_address.base else {
+                unimplemented!()
+            };
+            put_address_onto_stack(base, register_name_id_map, offset_table, result);
+            put_value_onto_stack(
+                &element_address.index,
+                register_name_id_map,
+                result,
+                ValType::I32,
+            );
+            if let Quantity::RegisterName(index) = &element_address.index
+                && lower_type(&register_type[index]) == ValType::I64
+            {
+                result.instruction(&Instruction::I32WrapI64);
             }
-            result.instruction(&Instruction::I32Store(MemArg {
-                offset: 0,
-                align: 0,
-                memory_index: 0,
-            }));
+            result.instruction(&Instruction::I32Const(type_bytes(
+                &element_address.element_type,
+            )));
+            result.instruction(&Instruction::I32Mul);
+            result.instruction(&Instruction::I32Add);
+            let target = register_name_id_map[&element_address.to];
+            result.instruction(&Instruction::LocalSet(target));
         }
         IRStatement::Alloca(_) => (/* already handled in alloca_stack */),
 
@@ -335,6 +477,7 @@ fn lower_statement(
         IRStatement::Call(_) => unimplemented!(),
         IRStatement::LoadField(_) => unimplemented!(),
         IRStatement::SetField(_) => unimplemented!(),
+        IRStatement::FieldAddress(_) => unimplemented!(),
     }
 }
 
@@ -378,6 +521,16 @@ fn generate_if_condition(
             ValType::I64 => result.instruction(&Instruction::I64GeS),
             _ => unimplemented!(),
         },
+        BranchType::LTU => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32LtU),
+            ValType::I64 => result.instruction(&Instruction::I64LtU),
+            _ => unimplemented!(),
+        },
+        BranchType::GEU => match data_type {
+            ValType::I32 => result.instruction(&Instruction::I32GeU),
+            ValType::I64 => result.instruction(&Instruction::I64GeU),
+            _ => unimplemented!(),
+        },
     };
 }
 
@@ -561,12 +714,7 @@ pub fn alloca_stack(function: &FunctionDefinition) -> (HashMap<RegisterName, i32
     let mut offset_table = HashMap::new();
     let mut current_offset = 0;
     for Alloca { to, alloc_type } in alloca_statements {
-        let bytes = match alloc_type {
-            Type::Address => 4i32,
-            Type::Integer(Integer { width, .. }) => *width as i32 / 8,
-            Type::StructRef(_) => unimplemented!(),
-            Type::None => unreachable!(),
-        };
+        let bytes = type_bytes(alloc_type);
         offset_table.insert(to.clone(), current_offset + bytes);
         current_offset += bytes;
     }
@@ -645,6 +793,7 @@ pub fn lower_function_body(
         Type::Integer(Integer { .. }) => unimplemented!(),
         Type::StructRef(_) => unimplemented!(),
         Type::Address => unimplemented!(),
+        Type::Array(_, _) => unimplemented!(),
         Type::None => (/* pass */),
     }
     result.instruction(&Instruction::End);
@@ -1,6 +1,7 @@
 use std::{fs::File, path::PathBuf};
 
 use bincode::Options;
+use bitvec::field::BitField;
 use clap::Parser;
 use come::{backend::riscv::simple_instruction, binary_format::clef::Clef};
 
@@ -47,10 +48,18 @@ fn main() {
             }
         }
         println!("content:",);
-        let instructions =
-            simple_instruction::parse_whole_binary(&section.content, &section.meta.pending_symbols);
-        for instruction in instructions {
-            println!("  {instruction}");
+        if section.meta.name == ".text" {
+            let instructions = simple_instruction::parse_whole_binary(
+                &section.content,
+                &section.meta.pending_symbols,
+            );
+            for instruction in instructions {
+                println!("  {instruction}");
+            }
+        } else {
+            for word in section.content.chunks(32) {
+                println!("  0x{:08x}", word.load_le::<u32>());
+            }
         }
     }
 }
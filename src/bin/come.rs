@@ -1,4 +1,4 @@
-use std::{fs::File, path::PathBuf};
+use std::{fs::File, path::PathBuf, str::FromStr};
 
 use clap::{Parser, ValueEnum};
 use come::{
@@ -19,6 +19,60 @@ enum Target {
     WASM,
 }
 
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, ValueEnum)]
+enum March {
+    /// RV32I, multiplication and division are done by software routines
+    Rv32i,
+    /// RV32I with the M extension
+    Rv32im,
+}
+
+impl From<March> for riscv::from_ir::Arch {
+    fn from(march: March) -> Self {
+        match march {
+            March::Rv32i => riscv::from_ir::Arch::RV32I,
+            March::Rv32im => riscv::from_ir::Arch::RV32IM,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, ValueEnum)]
+enum RegisterAllocator {
+    /// graph coloring allocator, which uses all allocatable registers
+    GraphColoring,
+    /// the old allocator, which only uses `t2`..`t6`
+    Simple,
+}
+
+impl From<RegisterAllocator> for riscv::from_ir::RegisterAllocator {
+    fn from(register_allocator: RegisterAllocator) -> Self {
+        match register_allocator {
+            RegisterAllocator::GraphColoring => riscv::from_ir::RegisterAllocator::GraphColoring,
+            RegisterAllocator::Simple => riscv::from_ir::RegisterAllocator::Simple,
+        }
+    }
+}
+
+/// An item of the `-O` option, which is either an optimization level or a pass.
+#[derive(Clone, Debug)]
+struct Optimize(Vec<optimize::OptimizePass>);
+
+impl FromStr for Optimize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(level) => optimize::pipeline(level)
+                .map(Self)
+                .ok_or_else(|| format!("unknown optimization level `{level}`")),
+            Err(_) => s
+                .parse()
+                .map(|pass| Self(vec![pass]))
+                .map_err(|()| format!("unknown optimization pass `{s}`")),
+        }
+    }
+}
+
 /// Come language compiler.
 #[derive(Parser, Debug)]
 #[command(version, long_version = build::CLAP_LONG_VERSION, about, long_about = None)]
@@ -35,19 +89,81 @@ struct Args {
     #[arg(short = None, long = "emit-ir")]
     emit_ir_path: Option<PathBuf>,
 
+    /// Optimization level (`0`, `1` or `2`) or passes to run, separated by `,`.
+    /// Passes joined by `+` are run repeatedly until they don't change anything.
     #[arg(short = 'O', long, value_delimiter = ',')]
-    optimize: Vec<ir::optimize::pass::Pass>,
+    optimize: Vec<Optimize>,
+
+    /// Print the ir of each function before these passes to stderr, separated by `,`.
+    #[arg(long, value_delimiter = ',')]
+    print_before: Vec<optimize::pass::Pass>,
+
+    /// Print the ir of each function after these passes to stderr, separated by `,`.
+    #[arg(long, value_delimiter = ',')]
+    print_after: Vec<optimize::pass::Pass>,
+
+    /// Print the ir of a function to stderr after each pass which changed it.
+    #[arg(long)]
+    print_changed: bool,
+
+    /// Print how long each pass took, and how many statements it added and removed to stderr.
+    #[arg(long)]
+    time_passes: bool,
+
+    /// Verify the ir after generating it and after each pass, and report the pass which broke it.
+    #[arg(long)]
+    verify_each: bool,
 
     #[arg(short = 't', long, value_enum)]
     target: Target,
+
+    /// Target RISC-V ISA variant, only used by the riscv backend.
+    #[arg(long, value_enum, default_value = "rv32im")]
+    march: March,
+
+    /// Register allocator, only used by the riscv backend.
+    #[arg(long, value_enum, default_value = "graph-coloring")]
+    register_allocator: RegisterAllocator,
 }
 
 fn main() {
     let args = Args::parse();
-    let code = file::read(args.input);
-    let ast = ast::from_source(&code).unwrap().1;
+    let code = file::read(&args.input);
+    let ast = match ast::from_source(&code) {
+        Ok(ast) => ast,
+        Err(error) => {
+            eprintln!("{}", error.with_file(args.input.display().to_string()));
+            std::process::exit(1);
+        }
+    };
+    if let Err(errors) = ir::semantic_check::check(&ast) {
+        for error in errors {
+            eprintln!("{}: {error}", args.input.display());
+        }
+        std::process::exit(1);
+    }
     let ir = ir::from_ast(&ast);
-    let ir = optimize::optimize(ir, args.optimize);
+    if args.verify_each {
+        if let Err(errors) = ir::verify::verify(&ir) {
+            for error in errors {
+                eprintln!("{error}");
+            }
+            eprintln!("ir generated from {} is broken", args.input.display());
+            std::process::exit(1);
+        }
+    }
+    let passes = args.optimize.into_iter().flat_map(|it| it.0).collect();
+    let mut instrumentation = optimize::Instrumentation {
+        print_before: args.print_before,
+        print_after: args.print_after,
+        print_changed: args.print_changed,
+        verify: args.verify_each,
+        ..Default::default()
+    };
+    let ir = optimize::optimize_instrumented(ir, passes, &mut instrumentation);
+    if args.time_passes {
+        eprint!("{instrumentation}");
+    }
     if let Some(emit_ir_path) = args.emit_ir_path {
         let mut w = file::writer(emit_ir_path);
         for ir in ir.iter() {
@@ -56,7 +172,11 @@ fn main() {
     }
     match args.target {
         Target::RISCV => {
-            let code = riscv::from_ir::emit_asm(&ir);
+            let options = riscv::from_ir::Options {
+                arch: args.march.into(),
+                register_allocator: args.register_allocator.into(),
+            };
+            let code = riscv::from_ir::emit_asm(&ir, options);
             file::write(args.output, &code);
         }
         Target::WASM => {
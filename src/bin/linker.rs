@@ -19,7 +19,7 @@ struct Args {
 
 fn main() {
     let args = Args::parse();
-    let mut result = args
+    let mut result: Clef = args
         .input
         .iter()
         .map(File::open)
@@ -31,12 +31,8 @@ fn main() {
                 .unwrap()
         })
         .fold(Clef::new(Architecture::RiscV, Os::BareMetal), Clef::merge);
-    result
-        .sections
-        .iter_mut()
-        .find(|it| it.meta.name == ".text")
-        .map(|it| it.meta.loadable = Some(0x8000_0000))
-        .unwrap();
+    // absolute addresses can be decided now
+    result.link(0x8000_0000);
     let mut output_file = File::create(args.output).unwrap();
     bincode::DefaultOptions::new()
         .with_fixint_encoding()
@@ -19,6 +19,10 @@ enum Target {
 
 #[derive(Serialize, Deserialize, Debug)]
 struct Config {
+    /// Optimization level, see `come --help`.
+    #[serde(default)]
+    opt_level: Option<usize>,
+    /// Passes to run after the ones of `opt_level`.
     #[serde(default)]
     optimization: Vec<String>,
     #[serde(default)]
@@ -87,13 +91,8 @@ fn main() {
             let project_dir = current_dir.join(name);
             fs::create_dir_all(&project_dir).unwrap();
             let config = Config {
-                optimization: vec![
-                    "RemoveOnlyOnceStore".to_string(),
-                    "RemoveLoadDirectlyAfterStore".to_string(),
-                    "RemoveUnusedRegister".to_string(),
-                    "MemoryToRegister".to_string(),
-                    "RemoveUnusedRegister".to_string(),
-                ],
+                opt_level: Some(1),
+                optimization: Vec::new(),
                 emit_ir: false,
                 target: Target::RISCV,
                 emit_asm: true,
@@ -128,6 +127,9 @@ fn compile_to_asm(
             .arg("--emit-ir")
             .arg(format!("{}.ir", target_dir.join(target_filename).display()));
     }
+    if let Some(opt_level) = config.opt_level {
+        compiler_cmd.arg(format!("-O{opt_level}"));
+    }
     if !config.optimization.is_empty() {
         let optimization = config.optimization.join(",");
         compiler_cmd.arg("-O").arg(&optimization);
@@ -22,8 +22,16 @@ struct Args {
 
 fn main() {
     let args = Args::parse();
-    let asm_code = file::read(args.input);
-    let clef = emit_clef(&asm_code);
+    let asm_code = file::read(&args.input);
+    let clef = match emit_clef(&asm_code) {
+        Ok(clef) => clef,
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}", error.with_file(args.input.display().to_string()));
+            }
+            std::process::exit(1);
+        }
+    };
     let dumper = bincode::DefaultOptions::new().with_fixint_encoding();
     let file_content = dumper.serialize(&clef).unwrap();
     let mut output_file = File::create(args.output).unwrap();
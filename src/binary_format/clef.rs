@@ -21,13 +21,96 @@ impl Display for Symbol {
     }
 }
 
+/// How the address of a symbol is filled into the content.
+///
+/// In the descriptions below, `S` is the address of the symbol, `A` is the addend and `P` is the
+/// address of the place being patched.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationKind {
+    /// `S + A - P` as the immediate of a branch instruction.
+    Branch,
+    /// `S + A - P` as the immediate of a `jal` instruction.
+    Jal,
+    /// Higher 20 bits of `S + A` as the immediate of a `lui` instruction, rounded so that adding
+    /// the sign extended lower 12 bits gives back `S + A`.
+    Hi20,
+    /// Lower 12 bits of `S + A` as the immediate of an I-type instruction.
+    Lo12I,
+    /// Lower 12 bits of `S + A` as the immediate of an S-type instruction.
+    Lo12S,
+    /// `S + A` as a 32-bit word.
+    Abs32,
+    /// Higher 20 bits of `S + A - P` as the immediate of an `auipc` instruction, rounded like
+    /// [`RelocationKind::Hi20`].
+    PcrelHi20,
+    /// Lower 12 bits of `S + A - P` as the immediate of an I-type instruction.
+    /// The addend should include the distance from the paired `auipc` to this instruction, so the
+    /// result is relative to the `auipc`.
+    PcrelLo12I,
+    /// Like [`RelocationKind::PcrelLo12I`], but for an S-type instruction.
+    PcrelLo12S,
+}
+
+impl RelocationKind {
+    /// Whether the result depends on where the section is loaded, and thus can only be decided
+    /// after the section got a load address.
+    pub fn is_absolute(self) -> bool {
+        matches!(
+            self,
+            RelocationKind::Hi20
+                | RelocationKind::Lo12I
+                | RelocationKind::Lo12S
+                | RelocationKind::Abs32
+        )
+    }
+}
+
+impl Display for RelocationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RelocationKind::Branch => write!(f, "branch"),
+            RelocationKind::Jal => write!(f, "jal"),
+            RelocationKind::Hi20 => write!(f, "hi20"),
+            RelocationKind::Lo12I => write!(f, "lo12_i"),
+            RelocationKind::Lo12S => write!(f, "lo12_s"),
+            RelocationKind::Abs32 => write!(f, "abs32"),
+            RelocationKind::PcrelHi20 => write!(f, "pcrel_hi20"),
+            RelocationKind::PcrelLo12I => write!(f, "pcrel_lo12_i"),
+            RelocationKind::PcrelLo12S => write!(f, "pcrel_lo12_s"),
+        }
+    }
+}
+
+/// A place in the content which is waiting for the address of a symbol.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Relocation {
+    /// How to fill the address into the content.
+    pub kind: RelocationKind,
+    /// Offset of the instruction or data to patch in the content part of clef.
+    pub offset_bytes: u32,
+    /// A constant added to the address of the symbol.
+    pub addend: i32,
+}
+
+impl Display for Relocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:08x} {}", self.offset_bytes, self.kind)?;
+        if self.addend != 0 {
+            write!(f, " {:+}", self.addend)?;
+        }
+        Ok(())
+    }
+}
+
 /// An unknown symbol in compile time, which address/offset is waiting to be determined for linking.
+///
+/// A pending symbol with the same name as the section refers to the beginning of the section.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PendingSymbol {
     /// Name of the symbol.
     pub name: String,
-    /// These instructions' nth param are waiting for the address of this symbol.
-    pub pending_instructions_offset_bytes: Vec<u32>,
+    /// Places waiting for the address of this symbol.
+    pub relocations: Vec<Relocation>,
 }
 
 impl Display for PendingSymbol {
@@ -36,12 +119,8 @@ impl Display for PendingSymbol {
         writeln!(
             f,
             "{}",
-            self.pending_instructions_offset_bytes
+            self.relocations
                 .iter()
-                .map(|it| format!("0x{it:08x}"))
-                .chunks(4)
-                .into_iter()
-                .map(|mut it| it.join(", "))
                 .map(|it| format!("    {it}"))
                 .join("\n")
         )
@@ -50,9 +129,9 @@ impl Display for PendingSymbol {
 
 impl PendingSymbol {
     pub fn used_by_instruction_at_offset(&self, offset_bytes: u32) -> bool {
-        self.pending_instructions_offset_bytes
+        self.relocations
             .iter()
-            .any(|&it| it == offset_bytes)
+            .any(|it| it.offset_bytes == offset_bytes)
     }
 }
 
@@ -134,17 +213,23 @@ impl Section {
         other.meta.symbols.iter_mut().for_each(|symbol| {
             symbol.offset_bytes += self_bytes;
         });
-        // add offsets to pending instructions in `other`
+        // add offsets to relocations in `other`
+        // relocations against the beginning of `other` now need to refer to the middle of `self`
+        let section_name = self.meta.name.clone();
         other
             .meta
             .pending_symbols
             .iter_mut()
             .for_each(|pending_symbol| {
+                let refers_to_section = pending_symbol.name == section_name;
                 pending_symbol
-                    .pending_instructions_offset_bytes
+                    .relocations
                     .iter_mut()
-                    .for_each(|offset| {
-                        *offset += self_bytes;
+                    .for_each(|relocation| {
+                        relocation.offset_bytes += self_bytes;
+                        if refers_to_section {
+                            relocation.addend += self_bytes as i32;
+                        }
                     });
             });
         // merge content
@@ -152,30 +237,53 @@ impl Section {
         // merge symbols and pending_symbols
         self.meta.symbols.extend(other.meta.symbols);
         self.meta.pending_symbols.extend(other.meta.pending_symbols);
-        resolve_pending_symbols(&mut self.meta, &mut self.content, architecture);
+        self.resolve_pending_symbols(architecture);
         self
     }
-}
 
-fn resolve_pending_symbols(
-    meta: &mut SectionMeta,
-    content: &mut BitVec<u32>,
-    architecture: Architecture,
-) {
-    match architecture {
-        Architecture::RiscV => {
-            let remaining_pending = backend::riscv::resolve_pending_symbol(
-                &meta.symbols,
-                &meta.pending_symbols,
-                content,
-            );
-            meta.pending_symbols = remaining_pending;
+    /// Fill in the addresses of symbols defined in this section, relocations which need a load
+    /// address are kept until [`SectionMeta::loadable`] is set.
+    pub fn resolve_pending_symbols(&mut self, architecture: Architecture) {
+        self.resolve_pending_symbols_with(architecture, &HashMap::new());
+    }
+
+    /// Like [`Section::resolve_pending_symbols`], and symbols in `external_addresses` are filled
+    /// in by their absolute addresses if this section is loaded.
+    pub fn resolve_pending_symbols_with(
+        &mut self,
+        architecture: Architecture,
+        external_addresses: &HashMap<String, u32>,
+    ) {
+        match architecture {
+            Architecture::RiscV => {
+                let remaining_pending = backend::riscv::resolve_pending_symbol(
+                    &self.meta,
+                    &mut self.content,
+                    external_addresses,
+                );
+                self.meta.pending_symbols = remaining_pending;
+            }
+            Architecture::Arm => todo!(),
+            Architecture::X86 => todo!(),
         }
-        Architecture::Arm => todo!(),
-        Architecture::X86 => todo!(),
     }
 }
 
+/// Sections are loaded at addresses aligned to this.
+const SECTION_ALIGN_BYTES: u32 = 0x1000;
+
+/// Add `shift_bytes` to the addends of relocations in `sections` against the beginning of the
+/// section named `name`, except the ones in that section itself.
+fn shift_section_references(sections: &mut [Section], name: &str, shift_bytes: u32) {
+    sections
+        .iter_mut()
+        .filter(|section| section.meta.name != name)
+        .flat_map(|section| section.meta.pending_symbols.iter_mut())
+        .filter(|pending_symbol| pending_symbol.name == name)
+        .flat_map(|pending_symbol| pending_symbol.relocations.iter_mut())
+        .for_each(|relocation| relocation.addend += shift_bytes as i32);
+}
+
 /// A clef file.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Clef {
@@ -200,27 +308,75 @@ impl Clef {
     pub fn merge(mut self, mut other: Self) -> Self {
         assert!(self.architecture == other.architecture);
         assert!(self.os == other.os);
+        // when two sections are merged, one of them is moved back, and so are the references to
+        // it from other sections
+        let mut shifts = Vec::new();
+        for other_section in &other.sections {
+            let name = &other_section.meta.name;
+            let Some(self_section) = self.sections.iter().find(|it| &it.meta.name == name) else {
+                continue;
+            };
+            // the same rule as in `Section::merge`
+            let other_goes_first = other_section.meta.loadable.is_some();
+            let first = if other_goes_first {
+                other_section
+            } else {
+                self_section
+            };
+            shifts.push((
+                other_goes_first,
+                name.clone(),
+                first.content.len() as u32 / 8,
+            ));
+        }
+        for (other_goes_first, name, shift_bytes) in shifts {
+            let moved_sections = if other_goes_first {
+                &mut self.sections
+            } else {
+                &mut other.sections
+            };
+            shift_section_references(moved_sections, &name, shift_bytes);
+        }
         for other_section in mem::take(&mut other.sections) {
-            let mut found = false;
-            let mut result_sections = Vec::new();
-            for self_section in mem::take(&mut self.sections) {
-                if self_section.meta.name == other_section.meta.name {
-                    found = true;
-                    result_sections.push(Section::merge(
-                        self_section,
-                        other_section.clone(),
-                        self.architecture,
-                    ));
-                    break;
+            match self
+                .sections
+                .iter()
+                .position(|it| it.meta.name == other_section.meta.name)
+            {
+                Some(position) => {
+                    let self_section = self.sections.remove(position);
+                    let merged = Section::merge(self_section, other_section, self.architecture);
+                    self.sections.insert(position, merged);
                 }
-            }
-            self.sections = result_sections;
-            if !found {
-                self.sections.push(other_section);
+                None => self.sections.push(other_section),
             }
         }
         self
     }
+
+    /// Give each section which is not loaded yet a load address, `.text` goes to `text_address`
+    /// and other sections follow it, then fill in the addresses of symbols used across sections.
+    pub fn link(&mut self, text_address: u32) {
+        // `sort_by_key` is stable, so the order of other sections is kept
+        self.sections.sort_by_key(|it| it.meta.name != ".text");
+        let mut next_address = text_address;
+        for section in &mut self.sections {
+            let address = *section.meta.loadable.get_or_insert(next_address);
+            next_address =
+                (address + section.content.len() as u32 / 8).next_multiple_of(SECTION_ALIGN_BYTES);
+        }
+        let mut addresses = HashMap::new();
+        for section in &self.sections {
+            let address = section.meta.loadable.unwrap();
+            addresses.insert(section.meta.name.clone(), address);
+            for symbol in &section.meta.symbols {
+                addresses.insert(symbol.name.clone(), address + symbol.offset_bytes);
+            }
+        }
+        for section in &mut self.sections {
+            section.resolve_pending_symbols_with(self.architecture, &addresses);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -250,7 +406,11 @@ mod tests {
                 ],
                 pending_symbols: vec![PendingSymbol {
                     name: "test1".to_string(),
-                    pending_instructions_offset_bytes: vec![0],
+                    relocations: vec![Relocation {
+                        kind: RelocationKind::Jal,
+                        offset_bytes: 0,
+                        addend: 0,
+                    }],
                 }],
                 linkable: true,
             },
@@ -275,7 +435,11 @@ mod tests {
                 ],
                 pending_symbols: vec![PendingSymbol {
                     name: "f".to_string(),
-                    pending_instructions_offset_bytes: vec![8],
+                    relocations: vec![Relocation {
+                        kind: RelocationKind::Jal,
+                        offset_bytes: 8,
+                        addend: 0,
+                    }],
                 }],
                 linkable: true,
             },
@@ -303,4 +467,57 @@ mod tests {
         assert!(clef.sections[0].meta.pending_symbols.is_empty());
         assert_eq!(clef.sections[0].meta.symbols.len(), 4);
     }
+
+    #[test]
+    fn test_link_data() {
+        let clef1 = backend::riscv::emit_clef(
+            r#"
+.section .text
+.globl main
+.globl msg
+main:
+    la a0, msg
+    lw a1, 0(a0)
+.section .data
+msg:
+    .word 42"#,
+        )
+        .unwrap();
+        let clef2 = backend::riscv::emit_clef(
+            r#"
+.section .data
+other:
+    .word 7, other
+.section .text
+f:
+    la a0, other"#,
+        )
+        .unwrap();
+        let mut clef = clef1.merge(clef2);
+        assert_eq!(clef.sections.len(), 2);
+        clef.link(0x8000_0000);
+        let (text, data) = (&clef.sections[0], &clef.sections[1]);
+        assert_eq!(text.meta.name, ".text");
+        assert_eq!(text.meta.loadable, Some(0x8000_0000));
+        assert_eq!(data.meta.name, ".data");
+        assert_eq!(data.meta.loadable, Some(0x8000_1000));
+        assert_eq!(data.meta.symbols[0].name, "msg");
+        assert!(text.meta.pending_symbols.is_empty());
+        assert!(data.meta.pending_symbols.is_empty());
+        let word = |section: &Section, index: usize| -> u32 {
+            section.content[32 * index..32 * (index + 1)].load_le()
+        };
+        assert_eq!(word(data, 0), 42);
+        assert_eq!(word(data, 1), 7);
+        assert_eq!(word(data, 2), 0x8000_1004);
+        // the address `auipc` + `addi` at `index` gives
+        let address = |index: usize| {
+            let pc = 0x8000_0000u32 + index as u32 * 4;
+            let hi = word(text, index) & 0xfffff000;
+            let lo = (word(text, index + 1) as i32) >> 20;
+            pc.wrapping_add(hi).wrapping_add_signed(lo)
+        };
+        assert_eq!(address(0), 0x8000_1000);
+        assert_eq!(address(3), 0x8000_1004);
+    }
 }
@@ -106,7 +106,7 @@ impl ControlFlowGraphContent {
         }
     }
 
-    fn immediately_dominates(&self, node: usize) -> Vec<usize> {
+    pub fn immediately_dominates(&self, node: usize) -> Vec<usize> {
         self.dominators
             .immediately_dominated_by(node.into())
             .map(|it| it.index())
@@ -178,6 +178,13 @@ impl ControlFlowGraph {
     fn dominate(&self, content: &ir::FunctionDefinition, bb_index: usize) -> Vec<usize> {
         self.content(content).dominates(bb_index)
     }
+    fn immediately_dominates(
+        &self,
+        content: &ir::FunctionDefinition,
+        bb_index: usize,
+    ) -> Vec<usize> {
+        self.content(content).immediately_dominates(bb_index)
+    }
     fn branch_direction(
         &self,
         content: &FunctionDefinition,
@@ -251,6 +258,15 @@ impl<'item, 'bind: 'item> BindedControlFlowGraph<'item, 'bind> {
     pub fn dominates(&self, bb_index: usize) -> Vec<usize> {
         self.item.dominate(self.bind_on, bb_index)
     }
+    /// Children of the basic block indexed by `bb_index` in the dominator tree.
+    pub fn immediately_dominates(&self, bb_index: usize) -> Vec<usize> {
+        // the entry block is its own immediate dominator, and the exit node is not a real block
+        self.item
+            .immediately_dominates(self.bind_on, bb_index)
+            .into_iter()
+            .filter(|&it| it != bb_index && it < self.bind_on.content.len())
+            .collect()
+    }
     pub fn is_dominated_by(&self, node: usize, dominator_suspect: usize) -> bool {
         self.dominates(dominator_suspect).contains(&node)
     }
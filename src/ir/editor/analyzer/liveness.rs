@@ -0,0 +1,424 @@
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+};
+
+use crate::ir::{
+    self,
+    editor::action::Action,
+    function::FunctionDefinitionIndex,
+    quantity::Quantity,
+    statement::{IRStatement, IsIRStatement},
+    FunctionDefinition, RegisterName,
+};
+
+use super::IsAnalyzer;
+
+/// [`LiveRange`] is the part of a basic block in which a register is alive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveRange {
+    /// Index of the basic block.
+    pub block: usize,
+    /// Index of the statement which defines the register,
+    /// `None` if the register is already alive when entering the block.
+    pub from: Option<usize>,
+    /// Index of the last statement which uses the register,
+    /// `None` if the register is still alive when leaving the block.
+    /// For a register which is defined but never used, this is the same as `from`.
+    pub to: Option<usize>,
+}
+
+impl LiveRange {
+    /// Map the range to points in the block, every statement has a point for reading its operands
+    /// and a point after it for writing its result.
+    fn points(&self) -> (usize, usize) {
+        let begin = self.from.map_or(0, |from| from * 2 + 1);
+        let end = match self.to {
+            None => usize::MAX,
+            // a dead definition still takes the register for a moment
+            Some(to) if Some(to) == self.from => to * 2 + 2,
+            Some(to) => to * 2,
+        };
+        (begin, end)
+    }
+
+    /// Whether the register is alive right after the statement indexed by `statement_index` is executed.
+    pub fn contains_after(&self, statement_index: usize) -> bool {
+        self.from.map_or(true, |from| from <= statement_index)
+            && self.to.map_or(true, |to| to > statement_index)
+    }
+
+    /// Whether two ranges overlap, ie. registers with these ranges cannot share a hardware register.
+    pub fn overlaps(&self, other: &LiveRange) -> bool {
+        if self.block != other.block {
+            return false;
+        }
+        let (self_begin, self_end) = self.points();
+        let (other_begin, other_end) = other.points();
+        self_begin < other_end && other_begin < self_end
+    }
+}
+
+#[derive(Debug, Default)]
+struct LivenessContent {
+    live_in: Vec<HashSet<RegisterName>>,
+    live_out: Vec<HashSet<RegisterName>>,
+    live_ranges: HashMap<RegisterName, Vec<LiveRange>>,
+}
+
+impl LivenessContent {
+    fn new(function: &FunctionDefinition) -> Self {
+        let block_count = function.content.len();
+        let block_index: HashMap<&str, usize> = function
+            .content
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (block.name.as_ref().unwrap().as_str(), index))
+            .collect();
+        // where registers are defined and last used (not including uses in phis) in each block
+        let mut defines = vec![HashMap::new(); block_count];
+        let mut last_uses = vec![HashMap::new(); block_count];
+        // registers used by phis in successors, which must be alive when leaving the block
+        let mut phi_uses = vec![HashSet::new(); block_count];
+        let mut successors = vec![Vec::new(); block_count];
+        for (index, statement) in function.iter().function_definition_index_enumerate() {
+            let FunctionDefinitionIndex(block, statement_index) = index;
+            if let Some((register, _)) = statement.generate_register() {
+                defines[block].insert(register, statement_index);
+            }
+            match statement {
+                IRStatement::Phi(phi) => {
+                    for source in &phi.from {
+                        if let Quantity::RegisterName(register) = &source.value {
+                            phi_uses[block_index[source.block.as_str()]].insert(register.clone());
+                        }
+                    }
+                }
+                IRStatement::Jump(jump) => successors[block].push(block_index[jump.label.as_str()]),
+                IRStatement::Branch(branch) => {
+                    successors[block].push(block_index[branch.success_label.as_str()]);
+                    successors[block].push(block_index[branch.failure_label.as_str()]);
+                }
+                _ => {}
+            }
+            if !matches!(statement, IRStatement::Phi(_)) {
+                for register in statement.use_register() {
+                    last_uses[block].insert(register, statement_index);
+                }
+            }
+        }
+        let upward_exposed: Vec<HashSet<RegisterName>> = (0..block_count)
+            .map(|block| {
+                last_uses[block]
+                    .keys()
+                    .filter(|it| !defines[block].contains_key(*it))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+        let mut live_in = upward_exposed.clone();
+        let mut live_out = phi_uses.clone();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in (0..block_count).rev() {
+                let mut new_live_out = phi_uses[block].clone();
+                for &successor in &successors[block] {
+                    new_live_out.extend(live_in[successor].iter().cloned());
+                }
+                let mut new_live_in = upward_exposed[block].clone();
+                new_live_in.extend(
+                    new_live_out
+                        .iter()
+                        .filter(|it| !defines[block].contains_key(*it))
+                        .cloned(),
+                );
+                if new_live_in != live_in[block] || new_live_out != live_out[block] {
+                    live_in[block] = new_live_in;
+                    live_out[block] = new_live_out;
+                    changed = true;
+                }
+            }
+        }
+        let mut live_ranges: HashMap<RegisterName, Vec<LiveRange>> = HashMap::new();
+        for block in 0..block_count {
+            let registers: HashSet<_> =
+                live_in[block].iter().chain(defines[block].keys()).collect();
+            for register in registers {
+                let from = defines[block].get(register).cloned();
+                let to = if live_out[block].contains(register) {
+                    None
+                } else {
+                    last_uses[block].get(register).cloned().or(from)
+                };
+                live_ranges
+                    .entry(register.clone())
+                    .or_default()
+                    .push(LiveRange { block, from, to });
+            }
+        }
+        for ranges in live_ranges.values_mut() {
+            ranges.sort_by_key(|it| it.block);
+        }
+        Self {
+            live_in,
+            live_out,
+            live_ranges,
+        }
+    }
+
+    /// Forget everything about `register`, used when it's replaced by a constant.
+    fn remove_register(&mut self, register: &RegisterName) {
+        for live in self.live_in.iter_mut().chain(self.live_out.iter_mut()) {
+            live.remove(register);
+        }
+        self.live_ranges.remove(register);
+    }
+}
+
+/// [`Liveness`] is for analyzing where each register is alive, in the precision of statements.
+#[derive(Debug, Default)]
+pub struct Liveness(OnceCell<LivenessContent>);
+
+impl Liveness {
+    /// Create a new [`Liveness`].
+    pub fn new() -> Self {
+        Self(OnceCell::new())
+    }
+
+    fn content(&self, function: &FunctionDefinition) -> &LivenessContent {
+        self.0.get_or_init(|| LivenessContent::new(function))
+    }
+
+    /// Registers alive when entering the basic block indexed by `block`.
+    fn live_in(&self, function: &FunctionDefinition, block: usize) -> &HashSet<RegisterName> {
+        &self.content(function).live_in[block]
+    }
+
+    /// Registers alive when leaving the basic block indexed by `block`.
+    fn live_out(&self, function: &FunctionDefinition, block: usize) -> &HashSet<RegisterName> {
+        &self.content(function).live_out[block]
+    }
+
+    /// Ranges in which `register` is alive, ordered by block.
+    fn live_ranges(&self, function: &FunctionDefinition, register: &RegisterName) -> &[LiveRange] {
+        self.content(function)
+            .live_ranges
+            .get(register)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether `register` is alive right after the statement indexed by `index` is executed.
+    fn is_live_after(
+        &self,
+        function: &FunctionDefinition,
+        register: &RegisterName,
+        index: &FunctionDefinitionIndex,
+    ) -> bool {
+        self.live_ranges(function, register)
+            .iter()
+            .any(|range| range.block == index.0 && range.contains_after(index.1))
+    }
+
+    /// Whether `register1` and `register2` are alive at the same time somewhere.
+    fn interfere(
+        &self,
+        function: &FunctionDefinition,
+        register1: &RegisterName,
+        register2: &RegisterName,
+    ) -> bool {
+        let ranges2 = self.live_ranges(function, register2);
+        self.live_ranges(function, register1)
+            .iter()
+            .any(|range1| ranges2.iter().any(|range2| range1.overlaps(range2)))
+    }
+}
+
+pub struct BindedLiveness<'item, 'bind: 'item> {
+    bind_on: &'bind FunctionDefinition,
+    item: &'item Liveness,
+}
+
+impl<'item, 'bind: 'item> BindedLiveness<'item, 'bind> {
+    pub fn live_in(&self, block: usize) -> &HashSet<RegisterName> {
+        self.item.live_in(self.bind_on, block)
+    }
+    pub fn live_out(&self, block: usize) -> &HashSet<RegisterName> {
+        self.item.live_out(self.bind_on, block)
+    }
+    pub fn live_ranges(&self, register: &RegisterName) -> &[LiveRange] {
+        self.item.live_ranges(self.bind_on, register)
+    }
+    pub fn is_live_after(&self, register: &RegisterName, index: &FunctionDefinitionIndex) -> bool {
+        self.item.is_live_after(self.bind_on, register, index)
+    }
+    pub fn interfere(&self, register1: &RegisterName, register2: &RegisterName) -> bool {
+        self.item.interfere(self.bind_on, register1, register2)
+    }
+}
+
+impl<'item, 'bind: 'item> IsAnalyzer<'item, 'bind> for Liveness {
+    fn on_action(&mut self, action: &Action) {
+        match (action, self.0.get_mut()) {
+            // replacing a register with a constant only makes the register disappear
+            (Action::RenameLocal(rename), Some(content))
+                if !matches!(rename.to, Quantity::RegisterName(_)) =>
+            {
+                content.remove_register(&rename.from);
+            }
+            _ => {
+                self.0.take();
+            }
+        }
+    }
+
+    type Binded = BindedLiveness<'item, 'bind>;
+
+    fn bind(&'item self, content: &'bind ir::FunctionDefinition) -> Self::Binded {
+        BindedLiveness {
+            bind_on: content,
+            item: self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ir::{
+            editor::action::{IsAction, RenameLocal},
+            function::{basic_block::BasicBlock, parameter::Parameter, test_util::*},
+            statement::Ret,
+        },
+        utility::data_type::{self, Type},
+    };
+
+    fn register(name: &str) -> RegisterName {
+        RegisterName(name.to_string())
+    }
+
+    fn registers(names: &[&str]) -> HashSet<RegisterName> {
+        names.iter().map(|it| register(it)).collect()
+    }
+
+    fn function_definition() -> FunctionDefinition {
+        FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: vec![Parameter {
+                    name: register("r"),
+                    data_type: data_type::I32.clone(),
+                }],
+                return_type: Type::None,
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("bb0".to_string()),
+                    content: vec![
+                        binop_constant("m"),
+                        binop_constant("n"),
+                        binop("i0", "m", "m"),
+                        binop_constant("unused"),
+                        jump("bb1"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![
+                        phi("i_bb1", "bb0", "i0", "bb2", "i1"),
+                        binop("c", "i_bb1", "n"),
+                        branch("bb2", "bb3"),
+                    ],
+                },
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![binop("i1", "c", "c"), jump("bb1")],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![Ret {
+                        value: Some(register("r").into()),
+                    }
+                    .into()],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_live_in_out() {
+        let function_definition = function_definition();
+        let liveness = Liveness::new();
+        let liveness = liveness.bind(&function_definition);
+        assert_eq!(liveness.live_in(0), &registers(&["r"]));
+        assert_eq!(liveness.live_out(0), &registers(&["i0", "n", "r"]));
+        assert_eq!(liveness.live_in(1), &registers(&["n", "r"]));
+        assert_eq!(liveness.live_out(1), &registers(&["c", "n", "r"]));
+        assert_eq!(liveness.live_in(2), &registers(&["c", "n", "r"]));
+        assert_eq!(liveness.live_out(2), &registers(&["i1", "n", "r"]));
+        assert_eq!(liveness.live_in(3), &registers(&["r"]));
+        assert_eq!(liveness.live_out(3), &registers(&[]));
+    }
+
+    #[test]
+    fn test_live_ranges() {
+        let function_definition = function_definition();
+        let liveness = Liveness::new();
+        let liveness = liveness.bind(&function_definition);
+        assert_eq!(
+            liveness.live_ranges(&register("m")),
+            &[LiveRange {
+                block: 0,
+                from: Some(0),
+                to: Some(2)
+            }]
+        );
+        assert_eq!(
+            liveness.live_ranges(&register("unused")),
+            &[LiveRange {
+                block: 0,
+                from: Some(3),
+                to: Some(3)
+            }]
+        );
+        assert_eq!(
+            liveness.live_ranges(&register("c")),
+            &[
+                LiveRange {
+                    block: 1,
+                    from: Some(1),
+                    to: None
+                },
+                LiveRange {
+                    block: 2,
+                    from: None,
+                    to: Some(0)
+                }
+            ]
+        );
+        assert!(liveness.is_live_after(&register("m"), &(0usize, 1usize).into()));
+        assert!(!liveness.is_live_after(&register("m"), &(0usize, 2usize).into()));
+        assert!(liveness.is_live_after(&register("r"), &(2usize, 0usize).into()));
+        // `m` dies where `i0` is born
+        assert!(!liveness.interfere(&register("m"), &register("i0")));
+        assert!(liveness.interfere(&register("m"), &register("n")));
+        assert!(liveness.interfere(&register("unused"), &register("r")));
+        assert!(!liveness.interfere(&register("unused"), &register("m")));
+        assert!(!liveness.interfere(&register("c"), &register("i1")));
+    }
+
+    #[test]
+    fn test_on_action() {
+        let mut function_definition = function_definition();
+        let mut liveness = Liveness::new();
+        liveness.bind(&function_definition).live_in(0);
+        let action = RenameLocal::new(register("r"), Quantity::NumberLiteral(1));
+        liveness.on_action(&action.clone().into());
+        action.perform_on_function(&mut function_definition);
+        let binded = liveness.bind(&function_definition);
+        assert!(binded.live_ranges(&register("r")).is_empty());
+        assert_eq!(binded.live_in(0), &registers(&[]));
+        assert_eq!(binded.live_in(3), &registers(&[]));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{cell::OnceCell, collections::HashMap};
+use std::{
+    cell::OnceCell,
+    collections::{HashMap, HashSet},
+};
 
 use itertools::Itertools;
 
@@ -142,6 +145,9 @@ impl MemoryUsage {
         function: &ir::FunctionDefinition,
     ) -> HashMap<RegisterName, MemoryAccessInfo> {
         let mut memory_access: HashMap<RegisterName, MemoryAccessInfo> = HashMap::new();
+        // registers whose address is used other than being loaded from or stored to,
+        // eg. used as the base of an array, we cannot track these memory access
+        let mut escaped = HashSet::new();
         for (index, statement) in function.iter().function_definition_index_enumerate() {
             match statement {
                 IRStatement::Alloca(_) => {
@@ -151,6 +157,9 @@ impl MemoryUsage {
                         .alloca = Some(index.clone());
                 }
                 IRStatement::Store(store) => {
+                    if let Quantity::RegisterName(source) = &store.source {
+                        escaped.insert(source.clone());
+                    }
                     if let Quantity::RegisterName(local) = &store.target {
                         memory_access
                             .entry(local.clone())
@@ -168,9 +177,10 @@ impl MemoryUsage {
                             .push(index);
                     }
                 }
-                _ => (),
+                _ => escaped.extend(statement.use_register()),
             }
         }
+        memory_access.retain(|register, info| info.alloca.is_some() && !escaped.contains(register));
         memory_access
     }
 }
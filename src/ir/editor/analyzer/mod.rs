@@ -3,12 +3,14 @@ use crate::ir::{self, FunctionDefinition};
 use self::register_usage::RegisterUsageAnalyzer;
 pub use self::{
     control_flow::{BindedControlFlowGraph, BindedScc, ControlFlowGraph},
+    liveness::{BindedLiveness, LiveRange, Liveness},
     memory_usage::{BindedMemoryUsage, MemoryUsage},
     register_usage::{BindedRegisterUsage, BindedRegisterUsageAnalyzer},
 };
 use super::action::Action;
 
 pub mod control_flow;
+mod liveness;
 mod memory_usage;
 pub mod register_usage;
 
@@ -24,6 +26,7 @@ pub struct Analyzer {
     pub register_usage: RegisterUsageAnalyzer,
     pub memory_usage: MemoryUsage,
     pub control_flow_graph: ControlFlowGraph,
+    pub liveness: Liveness,
 }
 
 impl Analyzer {
@@ -32,6 +35,7 @@ impl Analyzer {
             register_usage: RegisterUsageAnalyzer::new(),
             memory_usage: MemoryUsage::new(),
             control_flow_graph: ControlFlowGraph::new(),
+            liveness: Liveness::new(),
         }
     }
 
@@ -55,6 +59,9 @@ impl<'item, 'bind: 'item> BindedAnalyzer<'item, 'bind> {
     pub fn control_flow_graph(&self) -> BindedControlFlowGraph {
         self.item.control_flow_graph.bind(self.bind_on)
     }
+    pub fn liveness(&self) -> BindedLiveness {
+        self.item.liveness.bind(self.bind_on)
+    }
 }
 
 impl<'item, 'bind: 'item> IsAnalyzer<'item, 'bind> for Analyzer {
@@ -62,6 +69,7 @@ impl<'item, 'bind: 'item> IsAnalyzer<'item, 'bind> for Analyzer {
         self.register_usage.on_action(action);
         self.memory_usage.on_action(action);
         self.control_flow_graph.on_action(action);
+        self.liveness.on_action(action);
     }
 
     type Binded = BindedAnalyzer<'item, 'bind>;
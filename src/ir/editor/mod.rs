@@ -1,4 +1,4 @@
-use std::mem;
+use std::{collections::HashMap, mem};
 
 use self::{
     action::{
@@ -64,10 +64,33 @@ impl Editor {
         }
     }
 
+    pub fn replace_statement(
+        &mut self,
+        index: impl Into<FunctionDefinitionIndex>,
+        statement: impl Into<IRStatement>,
+    ) {
+        let index = index.into();
+        self.remove_statement(index.clone());
+        self.insert_statement(index, statement);
+    }
+
     pub fn rename_local(&mut self, from: RegisterName, to: impl Into<Quantity>) {
         self.perform_action(RenameLocal::new(from, to));
     }
 
+    /// Rename several registers, a register may be renamed to another register which is renamed
+    /// later, eg. `%0` to `%1` and `%1` to `%2`, in which case `%0` is renamed to `%2`.
+    pub fn rename_locals(&mut self, renames: impl IntoIterator<Item = (RegisterName, Quantity)>) {
+        let renames: HashMap<_, _> = renames.into_iter().collect();
+        for (from, to) in &renames {
+            let mut to = to;
+            while let Some(next) = to.as_local().and_then(|it| renames.get(it)) {
+                to = next;
+            }
+            self.rename_local(from.clone(), to.clone());
+        }
+    }
+
     fn perform_action(&mut self, action: impl Into<action::Action>) {
         let action = action.into();
         self.analyzer.on_action(&action);
@@ -1,7 +1,7 @@
 use super::{rvalue_from_ast, IRGeneratingContext};
 use crate::{
     ast::{self, expression::LValue},
-    ir::function::statement,
+    ir::function::statement::{self, element_address},
     utility::data_type::Type,
 };
 
@@ -16,6 +16,9 @@ pub fn from_ast(ast: &ast::statement::Assign, ctx: &mut IRGeneratingContext) {
         ast::expression::LValue::FieldAccess(field_access) => {
             to_field_access(field_access, ctx, rvalue_register);
         }
+        ast::expression::LValue::Subscript(subscript) => {
+            to_subscript(subscript, ctx, rvalue_register);
+        }
     }
 }
 
@@ -47,20 +50,22 @@ fn to_field_access(
         field_names.push(field_access.name.clone());
         root = field_access.from.as_ref();
     }
-    let root_variable = if let LValue::VariableRef(x) = root {
-        x
-    } else {
-        unreachable!()
+    let (root_variable_addr, root_variable_type) = match root {
+        LValue::VariableRef(root_variable) => (
+            ctx.address_of_variable(root_variable),
+            ctx.type_of_variable(root_variable),
+        ),
+        LValue::Subscript(subscript) => {
+            let (address, data_type) = element_address::from_ast(subscript, ctx);
+            (address.into(), data_type)
+        }
+        LValue::FieldAccess(_) => unreachable!(),
     };
-    let root_variable_addr = ctx
-        .symbol_table
-        .current_variable_address_register(root_variable);
-    let root_variable_type = ctx.type_of_variable(root_variable);
     let root_variable_register = ctx.next_register_with_type(&root_variable_type);
     ctx.current_basic_block.append_statement(statement::Load {
         to: root_variable_register.clone(),
         data_type: root_variable_type.clone(),
-        from: root_variable_addr.clone().into(),
+        from: root_variable_addr.clone(),
     });
     let (field_chain, leaf_type) = field_chain_from_ast(
         root_variable_type.clone(),
@@ -81,7 +86,20 @@ fn to_field_access(
     ctx.current_basic_block.append_statement(statement::Store {
         data_type: root_variable_type,
         source: set_field_result.into(),
-        target: root_variable_addr.into(),
+        target: root_variable_addr,
+    });
+}
+
+fn to_subscript(
+    subscript: &ast::expression::Subscript,
+    ctx: &mut IRGeneratingContext,
+    rvalue_register: crate::ir::quantity::Quantity,
+) {
+    let (element_address, element_type) = element_address::from_ast(subscript, ctx);
+    ctx.current_basic_block.append_statement(statement::Store {
+        data_type: element_type,
+        source: rvalue_register,
+        target: element_address.into(),
     });
 }
 
@@ -90,14 +108,12 @@ fn to_variable(
     variable_ref: &ast::expression::VariableRef,
     rvalue_register: &crate::ir::quantity::Quantity,
 ) {
-    let data_type = ctx.symbol_table.type_of_variable(variable_ref);
-    let lhs_address_register = ctx
-        .symbol_table
-        .current_variable_address_register(variable_ref);
+    let data_type = ctx.type_of_variable(variable_ref);
+    let lhs_address = ctx.address_of_variable(variable_ref);
     // generate store code
     ctx.current_basic_block.append_statement(statement::Store {
         source: rvalue_register.clone(),
-        target: lhs_address_register.into(),
+        target: lhs_address,
         data_type,
     });
 }
@@ -142,6 +158,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_subscript() {
+        let mut parent_ctx = crate::ir::IRGeneratingContext::new();
+        let mut ctx = IRGeneratingContext::new(&mut parent_ctx);
+        ctx.symbol_table
+            .variable_types_stack
+            .last_mut()
+            .unwrap()
+            .insert(
+                VariableRef("a".to_string()),
+                (Type::Array(Box::new(data_type::U32.clone()), 4), 0),
+            );
+        let ast = ast::statement::Assign {
+            lhs: LValue::Subscript(ast::expression::Subscript {
+                from: Box::new(LValue::VariableRef(VariableRef("a".to_string()))),
+                index: Box::new(IntegerLiteral(2).into()),
+            }),
+            rhs: IntegerLiteral(42).into(),
+        };
+        from_ast(&ast, &mut ctx);
+        let basic_blocks = ctx.done();
+        assert_eq!(
+            basic_blocks[0].content[0],
+            statement::ElementAddress {
+                to: RegisterName("0".to_string()),
+                element_type: data_type::U32.clone(),
+                base: RegisterName("a_0_addr".to_string()).into(),
+                index: 2.into(),
+            }
+            .into()
+        );
+        assert_eq!(
+            basic_blocks[0].content[1],
+            statement::Store {
+                data_type: data_type::U32.clone(),
+                source: 42.into(),
+                target: RegisterName("0".to_string()).into(),
+            }
+            .into()
+        );
+    }
+
     #[test]
     fn test_to_field_access() {
         let mut parent_ctx = crate::ir::IRGeneratingContext::new();
@@ -4,7 +4,10 @@ use super::{
 };
 use crate::{
     ast::{self, expression::VariableRef, statement::Statement},
-    ir::{quantity::Quantity, RegisterName},
+    ir::{
+        quantity::{GlobalVariableName, Quantity},
+        RegisterName,
+    },
     utility::data_type::{Integer, Type},
 };
 use std::{collections::HashMap, vec};
@@ -33,6 +36,13 @@ impl SymbolTable {
         self.variable_types_stack.pop();
     }
 
+    /// Whether `variable` is a local variable visible in current scope.
+    pub fn is_local(&self, variable: &VariableRef) -> bool {
+        self.variable_types_stack
+            .iter()
+            .any(|frame| frame.contains_key(variable))
+    }
+
     fn variable_id(&self, variable: &VariableRef) -> usize {
         for frame in self.variable_types_stack.iter().rev() {
             if let Some(entry) = frame.get(variable) {
@@ -145,7 +155,25 @@ impl<'a> IRGeneratingContext<'a> {
 
     /// Decide a variable's type.
     pub fn type_of_variable(&self, variable: &VariableRef) -> Type {
-        self.symbol_table.type_of_variable(variable)
+        if self.symbol_table.is_local(variable) {
+            self.symbol_table.type_of_variable(variable)
+        } else {
+            self.parent_context.global_definitions[&variable.0]
+                .data_type
+                .clone()
+        }
+    }
+
+    /// Decide the address of a variable.
+    /// Local variables shadow global ones.
+    pub fn address_of_variable(&self, variable: &VariableRef) -> Quantity {
+        if self.symbol_table.is_local(variable) {
+            self.symbol_table
+                .current_variable_address_register(variable)
+                .into()
+        } else {
+            GlobalVariableName(variable.0.clone()).into()
+        }
     }
 
     /// Decide a field's type.
@@ -154,6 +182,7 @@ impl<'a> IRGeneratingContext<'a> {
         let parent_type = match field_access.from.as_ref() {
             ast::expression::LValue::VariableRef(variable) => self.type_of_variable(variable),
             ast::expression::LValue::FieldAccess(field_access) => self.type_of_field(field_access),
+            ast::expression::LValue::Subscript(subscript) => self.type_of_subscript(subscript),
         };
         match parent_type {
             Type::StructRef(s) => {
@@ -165,6 +194,19 @@ impl<'a> IRGeneratingContext<'a> {
         }
     }
 
+    /// Decide an array element's type.
+    pub fn type_of_subscript(&self, subscript: &ast::expression::Subscript) -> Type {
+        let array_type = match subscript.from.as_ref() {
+            ast::expression::LValue::VariableRef(variable) => self.type_of_variable(variable),
+            ast::expression::LValue::FieldAccess(field_access) => self.type_of_field(field_access),
+            ast::expression::LValue::Subscript(subscript) => self.type_of_subscript(subscript),
+        };
+        match array_type {
+            Type::Array(element_type, _) => *element_type,
+            _ => panic!("Cannot subscript a non-array type"),
+        }
+    }
+
     /// Decide a local variable's type.
     pub fn type_of_quantity(&self, variable: &Quantity) -> Type {
         match variable {
@@ -223,20 +223,25 @@ pub fn parse(code: &str) -> IResult<&str, FunctionDefinition> {
     )(code)
 }
 
+/// Generate [`FunctionHeader`] from [`ast::function_definition::FunctionDefinition`].
+pub fn header_from_ast(ast: &ast::function_definition::FunctionDefinition) -> FunctionHeader {
+    FunctionHeader {
+        name: ast.name.clone(),
+        parameters: ast.parameters.iter().map(parameter::from_ast).collect(),
+        return_type: ast.return_type.clone(),
+    }
+}
+
 /// Generate [`FunctionDefinition`] from [`ast::function_definition::FunctionDefinition`].
 pub fn from_ast(
     ast: &ast::function_definition::FunctionDefinition,
     ctx: &mut crate::ir::IRGeneratingContext,
 ) -> FunctionDefinition {
-    let ast::function_definition::FunctionDefinition {
-        name,
-        parameters,
-        return_type,
-        content,
-    } = ast;
+    let header = header_from_ast(ast);
+    ctx.function_definitions
+        .insert(header.name.clone(), header.clone());
     let mut ctx = IRGeneratingContext::new(ctx);
-    let parameters: Vec<_> = parameters.iter().map(parameter::from_ast).collect();
-    for param in &parameters {
+    for param in &header.parameters {
         let variable = VariableRef(param.name.0.clone());
         let param_register = RegisterName(variable.0.clone());
         ctx.symbol_table
@@ -255,15 +260,7 @@ pub fn from_ast(
             target: address_register.into(),
         });
     }
-    compound_from_ast(content, &mut ctx);
-    let header = FunctionHeader {
-        name: name.clone(),
-        parameters,
-        return_type: return_type.clone(),
-    };
-    ctx.parent_context
-        .function_definitions
-        .insert(name.clone(), header.clone());
+    compound_from_ast(&ast.content, &mut ctx);
     formalize(FunctionDefinition {
         header,
         content: ctx.done(),
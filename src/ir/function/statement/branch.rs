@@ -31,6 +31,10 @@ pub enum BranchType {
     NE,
     LT,
     GE,
+    /// Unsigned `LT`.
+    LTU,
+    /// Unsigned `GE`.
+    GEU,
 }
 
 impl Display for BranchType {
@@ -43,6 +47,8 @@ fn branch_type(code: &str) -> IResult<&str, BranchType> {
     alt((
         map(tag("eq"), |_| BranchType::EQ),
         map(tag("ne"), |_| BranchType::NE),
+        map(tag("ltu"), |_| BranchType::LTU),
+        map(tag("geu"), |_| BranchType::GEU),
         map(tag("lt"), |_| BranchType::LT),
         map(tag("ge"), |_| BranchType::GE),
     ))(code)
@@ -70,6 +76,8 @@ impl BranchType {
             BranchType::NE => BinaryOperation::NotEqual,
             BranchType::LT => BinaryOperation::LessThan,
             BranchType::GE => BinaryOperation::GreaterOrEqualThan,
+            BranchType::LTU => BinaryOperation::UnsignedLessThan,
+            BranchType::GEU => BinaryOperation::UnsignedGreaterOrEqualThan,
         }
     }
     pub fn inverse(&self) -> Self {
@@ -78,6 +86,8 @@ impl BranchType {
             BranchType::NE => BranchType::EQ,
             BranchType::LT => BranchType::GE,
             BranchType::GE => BranchType::LT,
+            BranchType::LTU => BranchType::GEU,
+            BranchType::GEU => BranchType::LTU,
         }
     }
 }
@@ -219,5 +229,13 @@ mod tests {
                 }
             ))
         );
+        assert_eq!(
+            parse("bltu 1, 2, success, failure").unwrap().1.branch_type,
+            BranchType::LTU
+        );
+        assert_eq!(
+            parse("bgeu 1, 2, success, failure").unwrap().1.branch_type,
+            BranchType::GEU
+        );
     }
 }
@@ -3,6 +3,7 @@ use crate::{
     ir::{
         function::{
             ir_generator::{rvalue_from_ast, IRGeneratingContext},
+            statement::{branch::BranchType, Alloca, Branch, Jump, Load, Store},
             IsIRStatement,
         },
         quantity::{self, local, Quantity},
@@ -29,6 +30,10 @@ pub enum BinaryOperation {
     LessOrEqualThan,
     GreaterThan,
     GreaterOrEqualThan,
+    UnsignedLessThan,
+    UnsignedLessOrEqualThan,
+    UnsignedGreaterThan,
+    UnsignedGreaterOrEqualThan,
     Equal,
     NotEqual,
     Sub,
@@ -38,6 +43,12 @@ pub enum BinaryOperation {
     LogicalShiftLeft,
     LogicalShiftRight,
     AthematicShiftRight,
+    /// Lower 32 bits of the product, which is the same for signed and unsigned integers.
+    Mul,
+    SignedDiv,
+    UnsignedDiv,
+    SignedRem,
+    UnsignedRem,
 }
 
 impl BinaryOperation {
@@ -47,6 +58,10 @@ impl BinaryOperation {
             BinaryOperation::LessOrEqualThan => Some(BinaryOperation::GreaterThan),
             BinaryOperation::GreaterThan => Some(BinaryOperation::LessOrEqualThan),
             BinaryOperation::GreaterOrEqualThan => Some(BinaryOperation::LessThan),
+            BinaryOperation::UnsignedLessThan => Some(BinaryOperation::UnsignedGreaterOrEqualThan),
+            BinaryOperation::UnsignedLessOrEqualThan => Some(BinaryOperation::UnsignedGreaterThan),
+            BinaryOperation::UnsignedGreaterThan => Some(BinaryOperation::UnsignedLessOrEqualThan),
+            BinaryOperation::UnsignedGreaterOrEqualThan => Some(BinaryOperation::UnsignedLessThan),
             BinaryOperation::Equal => Some(BinaryOperation::NotEqual),
             BinaryOperation::NotEqual => Some(BinaryOperation::Equal),
             _ => None,
@@ -58,8 +73,19 @@ static BINARY_OPERATION_MAP: phf::Map<&'static str, BinaryOperation> = phf_map!
     "+" => BinaryOperation::Add,
     "-" => BinaryOperation::Sub,
     "==" => BinaryOperation::Equal,
+    "!=" => BinaryOperation::NotEqual,
     "<" => BinaryOperation::LessThan,
-    "&&" => BinaryOperation::And,
+    "<=" => BinaryOperation::LessOrEqualThan,
+    ">" => BinaryOperation::GreaterThan,
+    ">=" => BinaryOperation::GreaterOrEqualThan,
+    "|" => BinaryOperation::Or,
+    "^" => BinaryOperation::Xor,
+    "&" => BinaryOperation::And,
+    "<<" => BinaryOperation::LogicalShiftLeft,
+    ">>" => BinaryOperation::AthematicShiftRight,
+    "*" => BinaryOperation::Mul,
+    "/" => BinaryOperation::SignedDiv,
+    "%" => BinaryOperation::SignedRem,
 };
 
 impl fmt::Display for BinaryOperation {
@@ -70,6 +96,10 @@ impl fmt::Display for BinaryOperation {
             BinaryOperation::LessOrEqualThan => write!(f, "sle"),
             BinaryOperation::GreaterThan => write!(f, "sgt"),
             BinaryOperation::GreaterOrEqualThan => write!(f, "sge"),
+            BinaryOperation::UnsignedLessThan => write!(f, "ult"),
+            BinaryOperation::UnsignedLessOrEqualThan => write!(f, "ule"),
+            BinaryOperation::UnsignedGreaterThan => write!(f, "ugt"),
+            BinaryOperation::UnsignedGreaterOrEqualThan => write!(f, "uge"),
             BinaryOperation::Equal => write!(f, "eq"),
             BinaryOperation::NotEqual => write!(f, "ne"),
             BinaryOperation::Sub => write!(f, "sub"),
@@ -79,6 +109,11 @@ impl fmt::Display for BinaryOperation {
             BinaryOperation::LogicalShiftLeft => write!(f, "shl"),
             BinaryOperation::LogicalShiftRight => write!(f, "shr"),
             BinaryOperation::AthematicShiftRight => write!(f, "sra"),
+            BinaryOperation::Mul => write!(f, "mul"),
+            BinaryOperation::SignedDiv => write!(f, "sdiv"),
+            BinaryOperation::UnsignedDiv => write!(f, "udiv"),
+            BinaryOperation::SignedRem => write!(f, "srem"),
+            BinaryOperation::UnsignedRem => write!(f, "urem"),
         }
     }
 }
@@ -91,15 +126,30 @@ fn binary_operation(code: &str) -> IResult<&str, BinaryOperation> {
         map(tag("sle"), |_| BinaryOperation::LessOrEqualThan),
         map(tag("sgt"), |_| BinaryOperation::GreaterThan),
         map(tag("sge"), |_| BinaryOperation::GreaterOrEqualThan),
+        alt((
+            map(tag("ult"), |_| BinaryOperation::UnsignedLessThan),
+            map(tag("ule"), |_| BinaryOperation::UnsignedLessOrEqualThan),
+            map(tag("ugt"), |_| BinaryOperation::UnsignedGreaterThan),
+            map(tag("uge"), |_| BinaryOperation::UnsignedGreaterOrEqualThan),
+        )),
         map(tag("eq"), |_| BinaryOperation::Equal),
         map(tag("ne"), |_| BinaryOperation::NotEqual),
         map(tag("sub"), |_| BinaryOperation::Sub),
         map(tag("or"), |_| BinaryOperation::Or),
         map(tag("xor"), |_| BinaryOperation::Xor),
         map(tag("and"), |_| BinaryOperation::And),
-        map(tag("sll"), |_| BinaryOperation::LogicalShiftLeft),
-        map(tag("srl"), |_| BinaryOperation::LogicalShiftRight),
+        map(alt((tag("shl"), tag("sll"))), |_| {
+            BinaryOperation::LogicalShiftLeft
+        }),
+        map(alt((tag("shr"), tag("srl"))), |_| {
+            BinaryOperation::LogicalShiftRight
+        }),
         map(tag("sra"), |_| BinaryOperation::AthematicShiftRight),
+        map(tag("mul"), |_| BinaryOperation::Mul),
+        map(tag("sdiv"), |_| BinaryOperation::SignedDiv),
+        map(tag("udiv"), |_| BinaryOperation::UnsignedDiv),
+        map(tag("srem"), |_| BinaryOperation::SignedRem),
+        map(tag("urem"), |_| BinaryOperation::UnsignedRem),
     ))(code)
 }
 
@@ -196,13 +246,31 @@ pub fn from_ast(
     ctx: &mut IRGeneratingContext,
 ) -> RegisterName {
     let ast::expression::BinaryOperatorResult { operator, lhs, rhs } = ast;
+    if operator == "&&" || operator == "||" {
+        return short_circuit_from_ast(ast, ctx);
+    }
     let result_register = ctx.next_register_with_type(&Type::Integer(Integer {
         signed: true,
         width: 32,
     }));
     let left_register = rvalue_from_ast(lhs.as_ref(), ctx);
     let right_register = rvalue_from_ast(rhs.as_ref(), ctx);
-    let operation = BINARY_OPERATION_MAP[operator.as_str()];
+    let unsigned = matches!(
+        ctx.type_of_quantity(&left_register),
+        Type::Integer(Integer { signed: false, .. }) | Type::Address
+    );
+    let operation = match BINARY_OPERATION_MAP[operator.as_str()] {
+        BinaryOperation::AthematicShiftRight if unsigned => BinaryOperation::LogicalShiftRight,
+        BinaryOperation::SignedDiv if unsigned => BinaryOperation::UnsignedDiv,
+        BinaryOperation::SignedRem if unsigned => BinaryOperation::UnsignedRem,
+        BinaryOperation::LessThan if unsigned => BinaryOperation::UnsignedLessThan,
+        BinaryOperation::LessOrEqualThan if unsigned => BinaryOperation::UnsignedLessOrEqualThan,
+        BinaryOperation::GreaterThan if unsigned => BinaryOperation::UnsignedGreaterThan,
+        BinaryOperation::GreaterOrEqualThan if unsigned => {
+            BinaryOperation::UnsignedGreaterOrEqualThan
+        }
+        operation => operation,
+    };
     ctx.current_basic_block.append_statement(BinaryCalculate {
         operation,
         operand1: left_register,
@@ -216,6 +284,77 @@ pub fn from_ast(
     result_register
 }
 
+/// Generate IR for `&&` and `||`, the right hand side is only evaluated when the left hand side
+/// cannot decide the result.
+/// The result is always `0` or `1`.
+fn short_circuit_from_ast(
+    ast: &ast::expression::BinaryOperatorResult,
+    ctx: &mut IRGeneratingContext,
+) -> RegisterName {
+    let ast::expression::BinaryOperatorResult { operator, lhs, rhs } = ast;
+    let data_type = Type::Integer(Integer {
+        signed: true,
+        width: 32,
+    });
+    let logic_id = ctx.parent_context.next_logic_id;
+    ctx.parent_context.next_logic_id += 1;
+    let rhs_label = format!("logic_{logic_id}_rhs");
+    let end_label = format!("logic_{logic_id}_end");
+    let result_address = RegisterName(format!("logic_{logic_id}_addr"));
+    ctx.symbol_table
+        .register_type
+        .insert(result_address.clone(), data_type.clone());
+    ctx.current_basic_block.append_statement(Alloca {
+        to: result_address.clone(),
+        alloc_type: data_type.clone(),
+    });
+    // the result if rhs is skipped
+    let (skipped_value, success_label, failure_label) = if operator == "&&" {
+        (0, rhs_label.clone(), end_label.clone())
+    } else {
+        (1, end_label.clone(), rhs_label.clone())
+    };
+    ctx.current_basic_block.append_statement(Store {
+        data_type: data_type.clone(),
+        source: skipped_value.into(),
+        target: result_address.clone().into(),
+    });
+    let left_register = rvalue_from_ast(lhs.as_ref(), ctx);
+    ctx.end_current_basic_block_with(Branch {
+        branch_type: BranchType::NE,
+        operand1: left_register,
+        operand2: 0.into(),
+        success_label,
+        failure_label,
+    });
+    ctx.current_basic_block.name = Some(rhs_label);
+    let right_register = rvalue_from_ast(rhs.as_ref(), ctx);
+    let rhs_result = ctx.next_register_with_type(&data_type);
+    ctx.current_basic_block.append_statement(BinaryCalculate {
+        operation: BinaryOperation::NotEqual,
+        operand1: right_register,
+        operand2: 0.into(),
+        to: rhs_result.clone(),
+        data_type: data_type.clone(),
+    });
+    ctx.current_basic_block.append_statement(Store {
+        data_type: data_type.clone(),
+        source: rhs_result.into(),
+        target: result_address.clone().into(),
+    });
+    ctx.end_current_basic_block_with(Jump {
+        label: end_label.clone(),
+    });
+    ctx.current_basic_block.name = Some(end_label);
+    let result_register = ctx.next_register_with_type(&data_type);
+    ctx.current_basic_block.append_statement(Load {
+        to: result_register.clone(),
+        data_type,
+        from: result_address.into(),
+    });
+    result_register
+}
+
 #[cfg(test)]
 pub mod test_util {
     #![allow(clippy::borrow_interior_mutable_const)]
@@ -276,4 +415,80 @@ mod tests {
         let result = from_ast(&ast, &mut ctx);
         assert_eq!(result, RegisterName("0".to_string()));
     }
+
+    #[test]
+    fn test_parse_mul_div_rem() {
+        for (code, operation) in [
+            ("%t0 = mul i32 %a, %b", BinaryOperation::Mul),
+            ("%t0 = sdiv i32 %a, %b", BinaryOperation::SignedDiv),
+            ("%t0 = udiv i32 %a, %b", BinaryOperation::UnsignedDiv),
+            ("%t0 = srem i32 %a, %b", BinaryOperation::SignedRem),
+            ("%t0 = urem i32 %a, %b", BinaryOperation::UnsignedRem),
+        ] {
+            let (_, binary_calculate) = parse(code).unwrap();
+            assert_eq!(binary_calculate.operation, operation);
+            assert_eq!(format!("{binary_calculate}"), code);
+        }
+    }
+
+    #[test]
+    fn test_parse_unsigned_comparison() {
+        for (code, operation) in [
+            ("%t0 = ult i32 %a, %b", BinaryOperation::UnsignedLessThan),
+            (
+                "%t0 = ule i32 %a, %b",
+                BinaryOperation::UnsignedLessOrEqualThan,
+            ),
+            ("%t0 = ugt i32 %a, %b", BinaryOperation::UnsignedGreaterThan),
+            (
+                "%t0 = uge i32 %a, %b",
+                BinaryOperation::UnsignedGreaterOrEqualThan,
+            ),
+        ] {
+            let (_, binary_calculate) = parse(code).unwrap();
+            assert_eq!(binary_calculate.operation, operation);
+            assert_eq!(format!("{binary_calculate}"), code);
+        }
+    }
+
+    #[test]
+    fn test_from_ast_short_circuit() {
+        let ast = ast::expression::BinaryOperatorResult {
+            operator: "||".to_string(),
+            lhs: Box::new(ast::expression::IntegerLiteral(1).into()),
+            rhs: Box::new(ast::expression::IntegerLiteral(2).into()),
+        };
+        let mut parent_ctx = crate::ir::IRGeneratingContext::new();
+        let mut ctx = super::IRGeneratingContext::new(&mut parent_ctx);
+        let result = from_ast(&ast, &mut ctx);
+        assert_eq!(ctx.done_basic_blocks.len(), 2);
+        assert_eq!(
+            ctx.done_basic_blocks[0].content.last().unwrap(),
+            &Branch {
+                branch_type: BranchType::NE,
+                operand1: 1.into(),
+                operand2: 0.into(),
+                success_label: "logic_0_end".to_string(),
+                failure_label: "logic_0_rhs".to_string(),
+            }
+            .into()
+        );
+        assert_eq!(
+            ctx.done_basic_blocks[1].name,
+            Some("logic_0_rhs".to_string())
+        );
+        assert_eq!(
+            ctx.current_basic_block.name,
+            Some("logic_0_end".to_string())
+        );
+        assert_eq!(
+            ctx.current_basic_block.content[0],
+            Load {
+                to: result.clone(),
+                data_type: data_type::I32.clone(),
+                from: RegisterName("logic_0_addr".to_string()).into(),
+            }
+            .into()
+        );
+    }
 }
@@ -3,6 +3,7 @@ use crate::{
     ir::{
         function::{
             ir_generator::{rvalue_from_ast, IRGeneratingContext},
+            statement::calculate::{binary::BinaryOperation, BinaryCalculate},
             IsIRStatement,
         },
         quantity::{self, local, Quantity},
@@ -30,7 +31,7 @@ pub enum UnaryOperation {
 
 static UNARY_OPERATION_MAP: phf::Map<&'static str, UnaryOperation> = phf_map! {
     "-" => UnaryOperation::Neg,
-    "!" => UnaryOperation::Not,
+    "~" => UnaryOperation::Not,
 };
 
 /// Parse ir code to get a [`UnaryOperation`].
@@ -89,7 +90,7 @@ impl fmt::Display for UnaryCalculate {
         write!(
             f,
             "{} = {} {} {}",
-            self.to, self.operand, self.data_type, self.operation
+            self.to, self.operation, self.data_type, self.operand
         )
     }
 }
@@ -134,6 +135,16 @@ pub fn from_ast(
             ctx.symbol_table.register_type.remove(&result_register);
             return rvalue_register;
         }
+        "!" => {
+            // logical not, the result is always `0` or `1`
+            ctx.current_basic_block.append_statement(BinaryCalculate {
+                operation: BinaryOperation::Equal,
+                operand1: rvalue_register,
+                operand2: 0.into(),
+                to: result_register.clone(),
+                data_type,
+            })
+        }
         operator => {
             let operation = UNARY_OPERATION_MAP[operator];
             ctx.current_basic_block.append_statement(UnaryCalculate {
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use super::field_address;
+use crate::{
+    ast::{self, expression::LValue},
+    ir::{
+        function::{
+            ir_generator::{rvalue_from_ast, IRGeneratingContext},
+            IsIRStatement,
+        },
+        quantity::{self, local, Quantity, RegisterName},
+    },
+    utility::{data_type, data_type::Type},
+};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{space0, space1},
+    combinator::map,
+    sequence::tuple,
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`ElementAddress`] instruction, calculates the address of an element in an array.
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
+pub struct ElementAddress {
+    /// Where to store the address.
+    pub to: RegisterName,
+    /// Type of the elements in the array.
+    pub element_type: Type,
+    /// Address of the array.
+    pub base: Quantity,
+    /// Index of the element.
+    pub index: Quantity,
+}
+
+impl IsIRStatement for ElementAddress {
+    fn on_register_change(&mut self, from: &RegisterName, to: Quantity) {
+        if let Quantity::RegisterName(base) = &self.base
+            && base == from
+        {
+            self.base = to.clone();
+        }
+        if let Quantity::RegisterName(index) = &self.index
+            && index == from
+        {
+            self.index = to.clone();
+        }
+        if &self.to == from {
+            self.to = to.unwrap_local();
+        }
+    }
+    fn generate_register(&self) -> Option<(RegisterName, Type)> {
+        Some((self.to.clone(), Type::Address))
+    }
+    fn use_register(&self) -> Vec<RegisterName> {
+        let mut result = Vec::new();
+        if let Quantity::RegisterName(base) = &self.base {
+            result.push(base.clone());
+        }
+        if let Quantity::RegisterName(index) = &self.index {
+            result.push(index.clone());
+        }
+        result
+    }
+}
+
+impl fmt::Display for ElementAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = element_address {} {}, {}",
+            self.to, self.element_type, self.base, self.index
+        )
+    }
+}
+
+/// Parse ir code to get an [`ElementAddress`] instruction.
+pub fn parse(code: &str) -> IResult<&str, ElementAddress> {
+    map(
+        tuple((
+            local::parse,
+            space0,
+            tag("="),
+            space0,
+            tag("element_address"),
+            space1,
+            data_type::parse,
+            space1,
+            quantity::parse,
+            space0,
+            tag(","),
+            space0,
+            quantity::parse,
+        )),
+        |(to, _, _, _, _, _, element_type, _, base, _, _, _, index)| ElementAddress {
+            to,
+            element_type,
+            base,
+            index,
+        },
+    )(code)
+}
+
+/// Generate IR for calculating the address of an [`ast::expression::Subscript`].
+/// Return the register contains the address and the type of the element.
+pub fn from_ast(
+    ast: &ast::expression::Subscript,
+    ctx: &mut IRGeneratingContext,
+) -> (RegisterName, Type) {
+    let ast::expression::Subscript { from, index } = ast;
+    let (base, array_type) = match from.as_ref() {
+        LValue::VariableRef(variable) => (
+            ctx.address_of_variable(variable),
+            ctx.type_of_variable(variable),
+        ),
+        LValue::Subscript(subscript) => {
+            let (address, data_type) = from_ast(subscript, ctx);
+            (address.into(), data_type)
+        }
+        LValue::FieldAccess(field_access) => {
+            let (address, data_type) = field_address::from_ast(field_access, ctx);
+            (address.into(), data_type)
+        }
+    };
+    let element_type = if let Type::Array(element_type, _) = array_type {
+        *element_type
+    } else {
+        panic!("Cannot subscript a non-array type")
+    };
+    let index = rvalue_from_ast(index, ctx);
+    let to = ctx.next_register_with_type(&Type::Address);
+    ctx.current_basic_block.append_statement(ElementAddress {
+        to: to.clone(),
+        element_type: element_type.clone(),
+        base,
+        index,
+    });
+    (to, element_type)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+    use super::*;
+    use crate::ast::expression::{IntegerLiteral, VariableRef};
+
+    #[test]
+    fn test_parse() {
+        let result = parse("%1 = element_address i32 %a_0_addr, %0").unwrap().1;
+        assert_eq!(
+            result,
+            ElementAddress {
+                to: RegisterName("1".to_string()),
+                element_type: data_type::I32.clone(),
+                base: RegisterName("a_0_addr".to_string()).into(),
+                index: RegisterName("0".to_string()).into(),
+            }
+        );
+        assert_eq!(
+            format!("{result}"),
+            "%1 = element_address i32 %a_0_addr, %0"
+        );
+    }
+
+    #[test]
+    fn test_from_ast() {
+        let mut parent_ctx = crate::ir::IRGeneratingContext::new();
+        let mut ctx = IRGeneratingContext::new(&mut parent_ctx);
+        let array_type = Type::Array(
+            Box::new(Type::Array(Box::new(data_type::I32.clone()), 4)),
+            2,
+        );
+        ctx.symbol_table
+            .variable_types_stack
+            .last_mut()
+            .unwrap()
+            .insert(VariableRef("a".to_string()), (array_type, 0));
+        let ast = ast::expression::Subscript {
+            from: Box::new(LValue::Subscript(ast::expression::Subscript {
+                from: Box::new(LValue::VariableRef(VariableRef("a".to_string()))),
+                index: Box::new(IntegerLiteral(1).into()),
+            })),
+            index: Box::new(IntegerLiteral(3).into()),
+        };
+        let (address, element_type) = from_ast(&ast, &mut ctx);
+        assert_eq!(element_type, data_type::I32.clone());
+        assert_eq!(
+            ctx.current_basic_block.content,
+            vec![
+                ElementAddress {
+                    to: RegisterName("0".to_string()),
+                    element_type: Type::Array(Box::new(data_type::I32.clone()), 4),
+                    base: RegisterName("a_0_addr".to_string()).into(),
+                    index: 1.into(),
+                }
+                .into(),
+                ElementAddress {
+                    to: address,
+                    element_type: data_type::I32.clone(),
+                    base: RegisterName("0".to_string()).into(),
+                    index: 3.into(),
+                }
+                .into(),
+            ]
+        );
+    }
+}
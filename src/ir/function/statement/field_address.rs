@@ -0,0 +1,208 @@
+use std::fmt;
+
+use super::{element_address, load_field::parse_field};
+use crate::{
+    ast::expression::{FieldAccess, LValue},
+    ir::{
+        function::{ir_generator::IRGeneratingContext, IsIRStatement},
+        quantity::{self, local, Quantity, RegisterName},
+    },
+    utility::{data_type::Type, parsing::in_multispace},
+};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{space0, space1},
+    combinator::map,
+    multi::separated_list1,
+    sequence::{delimited, tuple},
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`FieldAddress`] instruction, calculates the address of a field in a struct in memory.
+#[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
+pub struct FieldAddress {
+    /// Where to store the address.
+    pub to: RegisterName,
+    /// Address of the struct.
+    pub base: Quantity,
+    /// Access `.1`th field of the struct, which is `.0` type.
+    pub field_chain: Vec<(Type, usize)>,
+}
+
+impl IsIRStatement for FieldAddress {
+    fn on_register_change(&mut self, from: &RegisterName, to: Quantity) {
+        if let Quantity::RegisterName(base) = &self.base
+            && base == from
+        {
+            self.base = to.clone();
+        }
+        if &self.to == from {
+            self.to = to.unwrap_local();
+        }
+    }
+    fn generate_register(&self) -> Option<(RegisterName, Type)> {
+        Some((self.to.clone(), Type::Address))
+    }
+    fn use_register(&self) -> Vec<RegisterName> {
+        if let Quantity::RegisterName(base) = &self.base {
+            vec![base.clone()]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for FieldAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = field_address {}.[{}]",
+            self.to,
+            self.base,
+            self.field_chain
+                .iter()
+                .map(|(t, i)| format!("{t}.{i}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+/// Parse ir code to get a [`FieldAddress`] instruction.
+pub fn parse(code: &str) -> IResult<&str, FieldAddress> {
+    map(
+        tuple((
+            local::parse,
+            space0,
+            tag("="),
+            space0,
+            tag("field_address"),
+            space1,
+            quantity::parse,
+            tag("."),
+            delimited(
+                tag("["),
+                separated_list1(tag(","), in_multispace(parse_field)),
+                tag("]"),
+            ),
+        )),
+        |(to, _, _, _, _, _, base, _, field_chain)| FieldAddress {
+            to,
+            base,
+            field_chain,
+        },
+    )(code)
+}
+
+/// Generate IR for calculating the address of a [`FieldAccess`].
+/// Return the register contains the address and the type of the field.
+pub fn from_ast(ast: &FieldAccess, ctx: &mut IRGeneratingContext) -> (RegisterName, Type) {
+    let FieldAccess { from, name } = ast;
+    let mut current = from.as_ref();
+    let mut field_chain_rev = vec![name.clone()];
+    while let LValue::FieldAccess(FieldAccess { from, name }) = current {
+        field_chain_rev.push(name.clone());
+        current = from.as_ref();
+    }
+    let (base, mut current_type) = match current {
+        LValue::VariableRef(root) => (ctx.address_of_variable(root), ctx.type_of_variable(root)),
+        LValue::Subscript(subscript) => {
+            let (address, data_type) = element_address::from_ast(subscript, ctx);
+            (address.into(), data_type)
+        }
+        LValue::FieldAccess(_) => unreachable!(),
+    };
+    let mut field_chain = Vec::new();
+    for field in field_chain_rev.into_iter().rev() {
+        let Type::StructRef(struct_name) = &current_type else {
+            unreachable!()
+        };
+        let mapping = ctx
+            .parent_context
+            .type_definitions
+            .get(struct_name)
+            .unwrap();
+        let index = mapping.field_names[&field];
+        let data_type = mapping.field_types[index].clone();
+        field_chain.push((current_type, index));
+        current_type = data_type;
+    }
+    let to = ctx.next_register_with_type(&Type::Address);
+    ctx.current_basic_block.append_statement(FieldAddress {
+        to: to.clone(),
+        base,
+        field_chain,
+    });
+    (to, current_type)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        ast::expression::VariableRef, ir::type_definition::TypeDefinitionMapping,
+        utility::data_type,
+    };
+
+    #[test]
+    fn test_parse() {
+        let code = "%1 = field_address %s_0_addr.[SS.0, S.1]";
+        let result = parse(code).unwrap().1;
+        assert_eq!(
+            result,
+            FieldAddress {
+                to: RegisterName("1".to_string()),
+                base: RegisterName("s_0_addr".to_string()).into(),
+                field_chain: vec![
+                    (Type::StructRef("SS".to_string()), 0),
+                    (Type::StructRef("S".to_string()), 1)
+                ],
+            }
+        );
+        assert_eq!(format!("{result}"), code);
+    }
+
+    #[test]
+    fn test_from_ast() {
+        let mut parent_ctx = crate::ir::IRGeneratingContext::new();
+        let mut field_names = HashMap::new();
+        field_names.insert("len".to_string(), 0);
+        field_names.insert("buf".to_string(), 1);
+        let buffer_type = Type::Array(Box::new(data_type::U32.clone()), 4);
+        parent_ctx.type_definitions.insert(
+            "S".to_string(),
+            TypeDefinitionMapping {
+                field_names,
+                field_types: vec![data_type::U32.clone(), buffer_type.clone()],
+            },
+        );
+        let mut ctx = IRGeneratingContext::new(&mut parent_ctx);
+        ctx.symbol_table
+            .variable_types_stack
+            .last_mut()
+            .unwrap()
+            .insert(
+                VariableRef("s".to_string()),
+                (Type::StructRef("S".to_string()), 0),
+            );
+        let ast = FieldAccess {
+            from: Box::new(LValue::VariableRef(VariableRef("s".to_string()))),
+            name: "buf".to_string(),
+        };
+        let (address, field_type) = from_ast(&ast, &mut ctx);
+        assert_eq!(field_type, buffer_type);
+        assert_eq!(
+            ctx.current_basic_block.content,
+            vec![FieldAddress {
+                to: address,
+                base: RegisterName("s_0_addr".to_string()).into(),
+                field_chain: vec![(Type::StructRef("S".to_string()), 1)],
+            }
+            .into()]
+        );
+    }
+}
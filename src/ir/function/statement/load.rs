@@ -25,7 +25,7 @@ pub struct Load {
 
 impl IsIRStatement for Load {
     fn on_register_change(&mut self, from: &RegisterName, to: Quantity) {
-        if &self.from.clone().unwrap_local() == from {
+        if self.from.as_local() == Some(from) {
             self.from = to.clone();
         }
         if &self.to == from {
@@ -1,4 +1,4 @@
-use super::Load;
+use super::{element_address, Load};
 use crate::{
     ast::{
         self,
@@ -15,6 +15,7 @@ use crate::{
     },
 };
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::{space0, space1},
     combinator::map,
@@ -72,7 +73,7 @@ impl IsIRStatement for LoadField {
     }
 }
 
-fn parse_field(code: &str) -> IResult<&str, (Type, usize)> {
+pub(super) fn parse_field(code: &str) -> IResult<&str, (Type, usize)> {
     map(
         tuple((data_type::parse, tag("."), parsing::integer)),
         |(t, _, i)| (t, i),
@@ -87,7 +88,7 @@ pub fn parse(code: &str) -> IResult<&str, LoadField> {
             space0,
             tag("="),
             space0,
-            tag("loadfield"),
+            alt((tag("load_field"), tag("loadfield"))),
             space1,
             data_type::parse,
             space1,
@@ -120,12 +121,15 @@ pub fn from_ast(ast: &ast::expression::FieldAccess, ctx: &mut IRGeneratingContex
         field_chain_rev.push(name);
         current = *from.clone();
     }
-    let root = if let LValue::VariableRef(root) = from.as_ref() {
-        root
-    } else {
-        unreachable!()
+    let (root_address, root_type) = match current {
+        LValue::VariableRef(root) => (ctx.address_of_variable(&root), ctx.type_of_variable(&root)),
+        LValue::Subscript(subscript) => {
+            let (address, data_type) = element_address::from_ast(&subscript, ctx);
+            (address.into(), data_type)
+        }
+        LValue::FieldAccess(_) => unreachable!(),
     };
-    let mut current_type = ctx.symbol_table.type_of_variable(root);
+    let mut current_type = root_type;
     let mut field_chain = vec![];
     for field in field_chain_rev.into_iter().rev() {
         let current_type_name = if let Type::StructRef(name) = &current_type {
@@ -143,12 +147,11 @@ pub fn from_ast(ast: &ast::expression::FieldAccess, ctx: &mut IRGeneratingContex
         field_chain.push((current_type, *index));
         current_type = data_type;
     }
-    let root_variable_addr = ctx.symbol_table.current_variable_address_register(root);
     let load_to = ctx.next_register_with_type(&field_chain[0].0);
     ctx.current_basic_block.append_statement(Load {
         to: load_to.clone(),
         data_type: field_chain[0].0.clone(),
-        from: root_variable_addr.into(),
+        from: root_address,
     });
     let target = ctx.next_register_with_type(&field_chain[0].0);
     ctx.current_basic_block.append_statement(LoadField {
@@ -191,5 +194,8 @@ mod tests {
                 leaf_type: data_type::I32.clone()
             },
         );
+        // the form it is printed in
+        let code = "%1 = load_field i32 %0.[S.0]";
+        assert_eq!(format!("{}", parse(code).unwrap().1), code);
     }
 }
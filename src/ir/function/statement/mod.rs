@@ -13,6 +13,10 @@ pub mod branch;
 pub mod calculate;
 /// Data structure, parser and ir generator for `call` statement.
 pub mod call;
+/// Data structure, parser and ir generator for `element_address` statement.
+pub mod element_address;
+/// Data structure, parser and ir generator for `field_address` statement.
+pub mod field_address;
 /// Data structure, parser and ir generator for `j` statement.
 mod jump;
 /// Data structure, parser and ir generator for `load` statement.
@@ -36,6 +40,8 @@ pub use alloca::Alloca;
 pub use branch::Branch;
 pub use calculate::{BinaryCalculate, UnaryCalculate};
 pub use call::Call;
+pub use element_address::ElementAddress;
+pub use field_address::FieldAddress;
 pub use jump::Jump;
 pub use load::Load;
 pub use load_field::LoadField;
@@ -65,6 +71,8 @@ pub enum IRStatement {
     Store,
     LoadField,
     SetField,
+    ElementAddress,
+    FieldAddress,
     Branch,
     Jump,
     Ret,
@@ -107,6 +115,8 @@ variant!(load, Load);
 variant!(store, Store);
 variant!(load_field, LoadField);
 variant!(set_field, SetField);
+variant!(element_address, ElementAddress);
+variant!(field_address, FieldAddress);
 variant!(branch, Branch);
 variant!(jump, Jump);
 variant!(ret, Ret);
@@ -119,8 +129,12 @@ pub fn parse(code: &str) -> IResult<&str, IRStatement> {
         map(calculate::unary::parse, IRStatement::UnaryCalculate),
         map(calculate::binary::parse, IRStatement::BinaryCalculate),
         map(load_field::parse, IRStatement::LoadField),
+        map(set_field::parse, IRStatement::SetField),
+        map(element_address::parse, IRStatement::ElementAddress),
+        map(field_address::parse, IRStatement::FieldAddress),
         map(load::parse, IRStatement::Load),
         map(store::parse, IRStatement::Store),
+        map(call::parse, IRStatement::Call),
         map(branch::parse, IRStatement::Branch),
         map(jump::parse, IRStatement::Jump),
         map(ret::parse, IRStatement::Ret),
@@ -138,6 +152,8 @@ impl fmt::Display for IRStatement {
             IRStatement::Store(x) => x.fmt(f),
             IRStatement::LoadField(x) => x.fmt(f),
             IRStatement::SetField(x) => x.fmt(f),
+            IRStatement::ElementAddress(x) => x.fmt(f),
+            IRStatement::FieldAddress(x) => x.fmt(f),
             IRStatement::Branch(x) => x.fmt(f),
             IRStatement::Jump(x) => x.fmt(f),
             IRStatement::Ret(x) => x.fmt(f),
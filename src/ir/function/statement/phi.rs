@@ -66,10 +66,8 @@ impl IsIRStatement for Phi {
             self.to = to.clone().unwrap_local();
         }
         for source in &mut self.from {
-            if let Quantity::RegisterName(local) = &mut source.value {
-                if local == from {
-                    *local = to.clone().unwrap_local();
-                }
+            if source.value.as_local() == Some(from) {
+                source.value = to.clone();
             }
         }
     }
@@ -23,9 +23,9 @@ pub struct Ret {
 
 impl IsIRStatement for Ret {
     fn on_register_change(&mut self, from: &RegisterName, to: Quantity) {
-        if let Some(Quantity::RegisterName(local)) = &mut self.value {
-            if local == from {
-                *local = to.unwrap_local();
+        if let Some(value @ Quantity::RegisterName(_)) = &mut self.value {
+            if value.as_local() == Some(from) {
+                *value = to;
             }
         }
     }
@@ -41,10 +41,8 @@ impl IsIRStatement for SetField {
         if &self.target == from {
             self.target = to.clone().unwrap_local();
         }
-        if let Quantity::RegisterName(local) = &mut self.source {
-            if local == from {
-                *local = to.clone().unwrap_local();
-            }
+        if self.source.as_local() == Some(from) {
+            self.source = to.clone();
         }
         if &self.origin_root == from {
             self.origin_root = to.unwrap_local();
@@ -54,11 +52,11 @@ impl IsIRStatement for SetField {
         Some((self.target.clone(), self.field_chain[0].0.clone()))
     }
     fn use_register(&self) -> Vec<RegisterName> {
+        let mut result = vec![self.origin_root.clone()];
         if let Quantity::RegisterName(register) = &self.source {
-            vec![register.clone()]
-        } else {
-            vec![]
+            result.push(register.clone());
         }
+        result
     }
 }
 
@@ -138,6 +136,7 @@ mod tests {
     #![allow(clippy::borrow_interior_mutable_const)]
 
     use super::*;
+    use crate::ir::function::statement;
 
     #[test]
     fn test_parse() {
@@ -156,5 +155,9 @@ mod tests {
                 target: RegisterName("2".to_string())
             }
         );
+        assert_eq!(
+            statement::parse(code).unwrap().1,
+            statement::IRStatement::SetField(set_field)
+        );
     }
 }
@@ -28,15 +28,11 @@ pub struct Store {
 
 impl IsIRStatement for Store {
     fn on_register_change(&mut self, from: &RegisterName, to: Quantity) {
-        if let Quantity::RegisterName(local) = &mut self.source {
-            if local == from {
-                *local = to.clone().unwrap_local();
-            }
+        if self.source.as_local() == Some(from) {
+            self.source = to.clone();
         }
-        if let Quantity::RegisterName(local) = &mut self.target {
-            if local == from {
-                *local = to.unwrap_local();
-            }
+        if self.target.as_local() == Some(from) {
+            self.target = to;
         }
     }
     fn generate_register(&self) -> Option<(RegisterName, Type)> {
@@ -35,7 +35,7 @@ impl fmt::Display for GlobalDefinition {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
             f,
-            "@{}: {} = {}",
+            "{} = global {} {}",
             self.name, self.data_type, self.initial_value
         )
     }
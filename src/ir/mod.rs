@@ -14,7 +14,11 @@ pub mod quantity;
 mod global_definition;
 mod integer_literal;
 pub mod optimize;
+/// Semantic checks on the AST, which should pass before generating IR.
+pub mod semantic_check;
 mod type_definition;
+/// Checks of invariants the ir should hold, mainly used to find broken passes.
+pub mod verify;
 
 use crate::{
     ast::{ASTNode, Ast},
@@ -22,6 +26,7 @@ use crate::{
 };
 pub use function::{statement, FunctionDefinition, FunctionHeader};
 pub use global_definition::GlobalDefinition;
+pub use integer_literal::IntegerLiteral;
 use nom::{
     branch::alt, character::complete::multispace0, combinator::map, multi::many0,
     sequence::delimited, IResult,
@@ -36,7 +41,7 @@ pub use editor::analyzer;
 
 /// The root nodes of IR.
 #[enum_dispatch]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IR {
     TypeDefinition,
     FunctionDefinition,
@@ -94,6 +99,8 @@ pub struct IRGeneratingContext {
     pub next_if_id: usize,
     /// Next `while` statement's id, used in generating label.
     pub next_loop_id: usize,
+    /// Next `&&` or `||` expression's id, used in generating label.
+    pub next_logic_id: usize,
 }
 
 impl IRGeneratingContext {
@@ -142,6 +149,7 @@ impl IRGeneratingContext {
             next_register_id: 0,
             next_if_id: 0,
             next_loop_id: 0,
+            next_logic_id: 0,
             function_definitions: built_in_functions,
         }
     }
@@ -163,13 +171,32 @@ impl Default for IRGeneratingContext {
 /// Generate IR from AST.
 pub fn from_ast(ast: &Ast) -> Vec<IR> {
     let mut context = IRGeneratingContext::new();
+    // register all function headers and global variables first,
+    // so they can be used regardless of order
+    for node in ast {
+        match node {
+            ASTNode::FunctionDefinition(function_definition) => {
+                let header = function::header_from_ast(function_definition);
+                context
+                    .function_definitions
+                    .insert(header.name.clone(), header);
+            }
+            ASTNode::GlobalVariableDefinition(global_variable_definition) => {
+                let global = global_definition::from_ast(global_variable_definition, &mut context);
+                context
+                    .global_definitions
+                    .insert(global.name.0.clone(), global);
+            }
+            ASTNode::TypeDefinition(_) => {}
+        }
+    }
     ast.iter()
         .map(|node| match node {
             ASTNode::TypeDefinition(type_definition) => {
                 IR::TypeDefinition(type_definition::from_ast(type_definition, &mut context))
             }
             ASTNode::GlobalVariableDefinition(global_variable_definition) => IR::GlobalDefinition(
-                global_definition::from_ast(global_variable_definition, &mut context),
+                context.global_definitions[&global_variable_definition.0.variable_name].clone(),
             ),
             ASTNode::FunctionDefinition(ast) => {
                 IR::FunctionDefinition(function::from_ast(ast, &mut context))
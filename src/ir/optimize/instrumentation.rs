@@ -0,0 +1,176 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
+use super::{module_pass::ModulePass, pass::Pass};
+use crate::ir::{statement::IRStatement, verify, FunctionDefinition, IR};
+
+/// What a pass did, summed over all its runs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PassStatistics {
+    pub runs: usize,
+    pub time: Duration,
+    /// Count of statements which were not there before the pass.
+    pub added: usize,
+    /// Count of statements which were there before the pass, but not after.
+    pub removed: usize,
+}
+
+/// [`Instrumentation`] records what each pass does while optimizing, and prints the ir of
+/// functions around the chosen passes to stderr.
+#[derive(Debug, Default)]
+pub struct Instrumentation {
+    /// Print the function before running these passes.
+    pub print_before: Vec<Pass>,
+    /// Print the function after running these passes.
+    pub print_after: Vec<Pass>,
+    /// Print the function after running any pass which changed it.
+    pub print_changed: bool,
+    /// Verify the ir after each pass, and panic if some pass broke it.
+    pub verify: bool,
+    /// Statistics of passes, in the order they first ran.
+    pub statistics: Vec<(Pass, PassStatistics)>,
+}
+
+/// Panic with the errors found by the verifier if `pass` broke the ir.
+fn report_broken(pass: &dyn fmt::Display, errors: Vec<verify::VerifyError>) -> ! {
+    for error in errors {
+        eprintln!("{error}");
+    }
+    panic!("ir is broken after {pass}");
+}
+
+/// Count how many statements are added and removed when `before` becomes `after`.
+fn statement_changes(before: &FunctionDefinition, after: &FunctionDefinition) -> (usize, usize) {
+    let mut counts: HashMap<&IRStatement, isize> = HashMap::new();
+    for statement in before.iter() {
+        *counts.entry(statement).or_default() -= 1;
+    }
+    for statement in after.iter() {
+        *counts.entry(statement).or_default() += 1;
+    }
+    let added = counts.values().filter(|it| **it > 0).sum::<isize>();
+    let removed = -counts.values().filter(|it| **it < 0).sum::<isize>();
+    (added as usize, removed as usize)
+}
+
+impl Instrumentation {
+    /// Called before `pass` runs on `function`.
+    pub(super) fn before_pass(&self, pass: &Pass, function: &FunctionDefinition) {
+        if self.print_before.contains(pass) {
+            eprintln!("; ir before {pass} on {}\n{function}", function.header.name);
+        }
+    }
+
+    /// Called after `pass` ran on `before` and got `after`, which took `time`.
+    pub(super) fn after_pass(
+        &mut self,
+        pass: &Pass,
+        before: &FunctionDefinition,
+        after: &FunctionDefinition,
+        time: Duration,
+    ) {
+        if self.verify {
+            if let Err(errors) = verify::verify_function(after) {
+                report_broken(pass, errors);
+            }
+        }
+        let changed = before != after;
+        if self.print_after.contains(pass) || (self.print_changed && changed) {
+            eprintln!("; ir after {pass} on {}\n{after}", after.header.name);
+        }
+        let (added, removed) = if changed {
+            statement_changes(before, after)
+        } else {
+            (0, 0)
+        };
+        let statistics =
+            if let Some((_, statistics)) = self.statistics.iter_mut().find(|(it, _)| it == pass) {
+                statistics
+            } else {
+                self.statistics
+                    .push((pass.clone(), PassStatistics::default()));
+                &mut self.statistics.last_mut().unwrap().1
+            };
+        statistics.runs += 1;
+        statistics.time += time;
+        statistics.added += added;
+        statistics.removed += removed;
+    }
+
+    /// Called after the module pass `pass` ran and got `ir`.
+    pub(super) fn after_module_pass(&self, pass: &ModulePass, ir: &[IR]) {
+        if self.verify {
+            if let Err(errors) = verify::verify(ir) {
+                report_broken(pass, errors);
+            }
+        }
+    }
+}
+
+/// The statistics as a table.
+impl fmt::Display for Instrumentation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40}{:>8}{:>14}{:>10}{:>10}",
+            "pass", "runs", "time", "added", "removed"
+        )?;
+        for (pass, statistics) in &self.statistics {
+            writeln!(
+                f,
+                "{:<40}{:>8}{:>14}{:>10}{:>10}",
+                pass.to_string(),
+                statistics.runs,
+                format!("{:.3?}", statistics.time),
+                statistics.added,
+                statistics.removed
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir;
+
+    #[test]
+    fn count_statement_changes() {
+        let before = ir::function::parse(
+            "fn f(i32 %a) -> i32 {
+  f_entry:
+    %0 = add i32 %a, 0
+    %1 = add i32 %0, 1
+    ret %1
+}",
+        )
+        .unwrap()
+        .1;
+        let after = ir::function::parse(
+            "fn f(i32 %a) -> i32 {
+  f_entry:
+    %1 = add i32 %a, 1
+    ret %1
+}",
+        )
+        .unwrap()
+        .1;
+        assert_eq!(statement_changes(&before, &after), (1, 2));
+        let mut instrumentation = Instrumentation::default();
+        let pass = Pass::from("InstructionCombine");
+        instrumentation.after_pass(&pass, &before, &after, Duration::from_millis(1));
+        instrumentation.after_pass(&pass, &after, &after, Duration::from_millis(2));
+        assert_eq!(
+            instrumentation.statistics,
+            vec![(
+                pass,
+                PassStatistics {
+                    runs: 2,
+                    time: Duration::from_millis(3),
+                    added: 1,
+                    removed: 2,
+                }
+            )]
+        );
+    }
+}
@@ -1,12 +1,58 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, mem, str::FromStr, time::Instant};
 
-use self::pass::IsPass;
+use self::{module_pass::IsModulePass, pass::IsPass};
 
 use super::{editor::Editor, IR};
 
+/// Recording and printing what passes do.
+mod instrumentation;
+/// Optimizing passes to be executed on the whole module.
+pub mod module_pass;
 /// Optimizing passes to be executed on a function.
 pub mod pass;
+pub use instrumentation::{Instrumentation, PassStatistics};
+use module_pass::ModulePass;
 use pass::Pass;
+use serde::{Deserialize, Serialize};
+
+/// Fixpoint groups are given up after running this many times, in case the passes in it keep
+/// undoing each other's changes.
+const MAX_FIXPOINT_ITERATIONS: usize = 16;
+
+/// A pass which can be selected by the user, it works either on the whole module or on each
+/// function.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum OptimizePass {
+    Module(ModulePass),
+    Function(Pass),
+    /// Passes which are run again and again until none of them changes the ir, written as the
+    /// pass names joined by `+`.
+    Fixpoint(Vec<OptimizePass>),
+}
+
+impl FromStr for OptimizePass {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('+') {
+            return s
+                .split('+')
+                .map(Self::from_str)
+                .collect::<Result<_, _>>()
+                .map(Self::Fixpoint);
+        }
+        ModulePass::from_str(s)
+            .map(Self::Module)
+            .or_else(|_| Pass::from_str(s).map(Self::Function))
+    }
+}
+
+impl From<&str> for OptimizePass {
+    fn from(s: &str) -> Self {
+        Self::from_str(s).unwrap()
+    }
+}
 /// [`FunctionOptimizer`] can manage passes and optimize the ir function.
 #[derive(Default)]
 pub struct FunctionOptimizer {
@@ -24,7 +70,21 @@ impl FunctionOptimizer {
     }
 
     /// Run all passes on the ir function.
-    pub fn optimize(mut self, ir: super::FunctionDefinition) -> super::FunctionDefinition {
+    ///
+    /// Passes [`needed`](IsPass::need) by a pass are run before it if they haven't been run yet,
+    /// and passes [`invalidated`](IsPass::invalidate) by a pass are run again after it if they
+    /// have been run before.
+    pub fn optimize(self, ir: super::FunctionDefinition) -> super::FunctionDefinition {
+        self.optimize_instrumented(ir, &mut Instrumentation::default())
+    }
+
+    /// Run all passes on the ir function like [`FunctionOptimizer::optimize`], and let
+    /// `instrumentation` watch each pass.
+    pub fn optimize_instrumented(
+        mut self,
+        ir: super::FunctionDefinition,
+        instrumentation: &mut Instrumentation,
+    ) -> super::FunctionDefinition {
         let mut editor = Editor::new(ir);
         let mut executed = HashSet::new();
         self.passes.reverse();
@@ -41,21 +101,100 @@ impl FunctionOptimizer {
                 continue;
             }
             let pass = self.passes.pop().unwrap();
+            let before = editor.content.clone();
+            instrumentation.before_pass(&pass, &before);
+            let start = Instant::now();
             pass.run(&mut editor);
+            instrumentation.after_pass(&pass, &before, &editor.content, start.elapsed());
+            for invalidated in pass.invalidate() {
+                if executed.remove(&invalidated) && !self.passes.contains(&invalidated) {
+                    self.passes.push(invalidated);
+                }
+            }
             executed.insert(pass);
         }
         editor.content
     }
 }
 
-pub fn optimize(ir: Vec<IR>, passes: Vec<Pass>) -> Vec<IR> {
+/// Run `passes` in order, function passes between two module passes are run on each function.
+pub fn optimize(ir: Vec<IR>, passes: Vec<OptimizePass>) -> Vec<IR> {
+    optimize_instrumented(ir, passes, &mut Instrumentation::default())
+}
+
+/// Run `passes` like [`optimize`], and let `instrumentation` watch each function pass.
+pub fn optimize_instrumented(
+    mut ir: Vec<IR>,
+    passes: Vec<OptimizePass>,
+    instrumentation: &mut Instrumentation,
+) -> Vec<IR> {
+    let mut function_passes = Vec::new();
+    for pass in passes {
+        match pass {
+            OptimizePass::Function(pass) => function_passes.push(pass),
+            OptimizePass::Module(pass) => {
+                ir = optimize_functions(ir, mem::take(&mut function_passes), instrumentation);
+                pass.run(&mut ir);
+                instrumentation.after_module_pass(&pass, &ir);
+            }
+            OptimizePass::Fixpoint(passes) => {
+                ir = optimize_functions(ir, mem::take(&mut function_passes), instrumentation);
+                for _ in 0..MAX_FIXPOINT_ITERATIONS {
+                    let optimized =
+                        optimize_instrumented(ir.clone(), passes.clone(), instrumentation);
+                    let changed = optimized != ir;
+                    ir = optimized;
+                    if !changed {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    optimize_functions(ir, function_passes, instrumentation)
+}
+
+/// The passes run with optimization level `level`, ie. `-O<level>` in `come`, returns `None` if
+/// there is no such level.
+/// - `0`: no optimization
+/// - `1`: promote variables to registers
+/// - `2`: also inline functions, split structs, and repeat the scalar optimizations until
+///   nothing changes
+pub fn pipeline(level: usize) -> Option<Vec<OptimizePass>> {
+    let passes: &[&str] = match level {
+        0 => &[],
+        1 => &[
+            "RemoveOnlyOnceStore",
+            "RemoveLoadDirectlyAfterStore",
+            "RemoveUnusedRegister",
+            "MemoryToRegister",
+        ],
+        2 => &[
+            "ScalarReplacementOfAggregates",
+            "RemoveUnusedRegister",
+            "MemoryToRegister",
+            "Inline",
+            "SparseConditionalConstantPropagation+InstructionCombine+GlobalValueNumbering+AggressiveDeadCodeElimination+SimplifyCFG",
+            "LoopInvariantCodeMotion",
+            "RemoveUnusedRegister",
+        ],
+        _ => return None,
+    };
+    Some(passes.iter().map(|&it| OptimizePass::from(it)).collect())
+}
+
+fn optimize_functions(
+    ir: Vec<IR>,
+    passes: Vec<Pass>,
+    instrumentation: &mut Instrumentation,
+) -> Vec<IR> {
     let mut result = Vec::new();
     for ir in ir {
         match ir {
             IR::FunctionDefinition(function_definition) => {
                 let function_optimizer = FunctionOptimizer::from_passes(passes.clone());
                 let optimized_function_definition =
-                    function_optimizer.optimize(function_definition);
+                    function_optimizer.optimize_instrumented(function_definition, instrumentation);
                 result.push(IR::FunctionDefinition(optimized_function_definition));
             }
             ir => result.push(ir),
@@ -63,3 +202,47 @@ pub fn optimize(ir: Vec<IR>, passes: Vec<Pass>) -> Vec<IR> {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement::IRStatement};
+
+    #[test]
+    fn run_invalidated_pass_again() {
+        let function = ir::function::parse(
+            "fn f(i32 %a) -> i32 {
+  f_entry:
+    %x_addr = alloca i32
+    store i32 %a, address %x_addr
+    store i32 1, address %x_addr
+    %0 = load i32 %x_addr
+    ret %0
+}",
+        )
+        .unwrap()
+        .1;
+        let optimizer = FunctionOptimizer::from_passes(vec![
+            Pass::from("RemoveUnusedRegister"),
+            Pass::from("MemoryToRegister"),
+        ]);
+        let function = optimizer.optimize(function);
+        // `MemoryToRegister` leaves the `alloca` unused, and `RemoveUnusedRegister` removes it
+        assert!(!function
+            .iter()
+            .any(|it| matches!(it, IRStatement::Alloca(_))));
+    }
+
+    #[test]
+    fn parse_fixpoint() {
+        assert_eq!(
+            OptimizePass::from("Inline+SimplifyCFG"),
+            OptimizePass::Fixpoint(vec![
+                OptimizePass::Module(ModulePass::from("Inline")),
+                OptimizePass::Function(Pass::from("SimplifyCFG")),
+            ])
+        );
+        assert_eq!(pipeline(0), Some(Vec::new()));
+        assert_eq!(pipeline(3), None);
+    }
+}
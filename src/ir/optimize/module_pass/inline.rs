@@ -0,0 +1,377 @@
+use std::collections::{HashMap, HashSet};
+
+use super::IsModulePass;
+use crate::ir::{
+    editor::Editor,
+    function::FunctionDefinitionIndex,
+    quantity::Quantity,
+    statement::{phi::PhiSource, Call, IRStatement, IsIRStatement, Jump, Phi},
+    FunctionDefinition, RegisterName, IR,
+};
+use serde::{Deserialize, Serialize};
+
+/// Functions with more statements than this won't be inlined.
+const INLINE_THRESHOLD: usize = 24;
+
+/// [`Inline`] replaces calls to small functions with the body of the called function.
+///
+/// A function is inlined if it has at most [`INLINE_THRESHOLD`] statements and it can never
+/// call itself, directly or through other functions.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Inline;
+
+fn callees(function: &FunctionDefinition) -> impl Iterator<Item = &str> {
+    function.iter().filter_map(|statement| {
+        if let IRStatement::Call(call) = statement {
+            Some(call.name.as_str())
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether calling the function named `name` may lead to calling itself again.
+fn is_recursive(name: &str, functions: &HashMap<&str, &FunctionDefinition>) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending: Vec<_> = callees(functions[name]).collect();
+    while let Some(current) = pending.pop() {
+        if current == name {
+            return true;
+        }
+        if let Some(function) = functions.get(current)
+            && visited.insert(current)
+        {
+            pending.extend(callees(function));
+        }
+    }
+    false
+}
+
+/// Whether `register` is used where a constant cannot be put, eg. the base of `getelementptr`.
+fn used_as_register_only(function: &FunctionDefinition, register: &RegisterName) -> bool {
+    function.iter().any(|statement| match statement {
+        IRStatement::ElementAddress(element_address) => {
+            element_address.base == register.clone().into()
+        }
+        IRStatement::FieldAddress(field_address) => field_address.base == register.clone().into(),
+        IRStatement::LoadField(load_field) => &load_field.source == register,
+        IRStatement::SetField(set_field) => &set_field.origin_root == register,
+        _ => false,
+    })
+}
+
+fn should_inline(callee: &FunctionDefinition, call: &Call, recursive: bool) -> bool {
+    let entry_name = callee.content[0].name.as_deref();
+    let entry_is_jumped_to = callee.iter().any(|statement| match statement {
+        IRStatement::Jump(jump) => Some(jump.label.as_str()) == entry_name,
+        IRStatement::Branch(branch) => {
+            Some(branch.success_label.as_str()) == entry_name
+                || Some(branch.failure_label.as_str()) == entry_name
+        }
+        _ => false,
+    });
+    let constant_to_register_only =
+        callee
+            .header
+            .parameters
+            .iter()
+            .zip(&call.params)
+            .any(|(parameter, argument)| {
+                !matches!(argument, Quantity::RegisterName(_))
+                    && used_as_register_only(callee, &parameter.name)
+            });
+    !recursive
+        && !entry_is_jumped_to
+        && !constant_to_register_only
+        && callee.iter().count() <= INLINE_THRESHOLD
+}
+
+/// Find a prefix for names in the inlined function body which is not used in the caller yet.
+fn unique_prefix(caller: &FunctionDefinition, callee_name: &str) -> String {
+    (0..)
+        .map(|id| format!("{callee_name}_inline{id}"))
+        .find(|prefix| {
+            !caller
+                .content
+                .iter()
+                .any(|block| block.name.as_ref().unwrap().starts_with(prefix))
+        })
+        .unwrap()
+}
+
+/// Replace the call statement at `index` with the body of `callee`.
+fn inline_call(editor: &mut Editor, index: FunctionDefinitionIndex, callee: &FunctionDefinition) {
+    let FunctionDefinitionIndex(block_index, statement_index) = index.clone();
+    let IRStatement::Call(call) = editor.content[index].clone() else {
+        unreachable!()
+    };
+    let prefix = unique_prefix(&editor.content, &callee.header.name);
+    let rename_register =
+        |register: &RegisterName| RegisterName(format!("{prefix}_{}", register.0));
+    let rename_label = |label: &str| format!("{prefix}_{label}");
+
+    // give registers in the callee new names, and replace parameters with the arguments
+    let mut body = Editor::new(callee.clone());
+    let registers: Vec<_> = callee
+        .header
+        .parameters
+        .iter()
+        .map(|it| it.name.clone())
+        .chain(
+            callee
+                .iter()
+                .filter_map(|it| it.generate_register().map(|(register, _)| register)),
+        )
+        .collect();
+    for register in registers {
+        body.rename_local(register.clone(), rename_register(&register));
+    }
+    for (parameter, argument) in callee.header.parameters.iter().zip(&call.params) {
+        body.rename_local(rename_register(&parameter.name), argument.clone());
+    }
+
+    // rename labels, and let `ret`s go to the block after the call
+    let end_label = rename_label("end");
+    let mut returned = Vec::new();
+    let mut blocks = body.content.content;
+    for block in &mut blocks {
+        let block_name = rename_label(block.name.as_ref().unwrap());
+        for statement in &mut block.content {
+            match statement {
+                IRStatement::Jump(jump) => jump.label = rename_label(&jump.label),
+                IRStatement::Branch(branch) => {
+                    branch.success_label = rename_label(&branch.success_label);
+                    branch.failure_label = rename_label(&branch.failure_label);
+                }
+                IRStatement::Phi(phi) => {
+                    for source in &mut phi.from {
+                        source.block = rename_label(&source.block);
+                    }
+                }
+                IRStatement::Ret(ret) => {
+                    if let Some(value) = &ret.value {
+                        returned.push(PhiSource {
+                            value: value.clone(),
+                            block: block_name.clone(),
+                        });
+                    }
+                    *statement = Jump {
+                        label: end_label.clone(),
+                    }
+                    .into();
+                }
+                _ => (),
+            }
+        }
+        block.name = Some(block_name);
+    }
+
+    // split the caller block at the call
+    let caller_block = &editor.content.content[block_index];
+    let caller_block_name = caller_block.name.clone().unwrap();
+    let rest = caller_block.content[statement_index + 1..].to_vec();
+    let to_remove: Vec<_> = (statement_index..caller_block.content.len())
+        .map(|it| (block_index, it))
+        .collect();
+    editor.remove_statements(to_remove);
+    editor.push_back_statement(
+        block_index,
+        Jump {
+            label: blocks[0].name.clone().unwrap(),
+        },
+    );
+    let end_block_index = block_index + blocks.len() + 1;
+    for (offset, block) in blocks.into_iter().enumerate() {
+        let inserted_index = block_index + offset + 1;
+        editor.insert_basic_block(block.name.unwrap(), inserted_index);
+        for statement in block.content {
+            editor.push_back_statement(inserted_index, statement);
+        }
+    }
+    editor.insert_basic_block(end_label.clone(), end_block_index);
+    if let Some(to) = call.to {
+        editor.push_back_statement(
+            end_block_index,
+            Phi {
+                to,
+                data_type: call.data_type,
+                from: returned,
+            },
+        );
+    }
+    let successors = match rest.last() {
+        Some(IRStatement::Jump(jump)) => vec![jump.label.clone()],
+        Some(IRStatement::Branch(branch)) => {
+            vec![branch.success_label.clone(), branch.failure_label.clone()]
+        }
+        _ => Vec::new(),
+    };
+    for statement in rest {
+        editor.push_back_statement(end_block_index, statement);
+    }
+
+    // phis in the successors now see the control flow coming from the end block
+    let mut to_replace = Vec::new();
+    for (index, statement) in editor.content.iter().function_definition_index_enumerate() {
+        if let IRStatement::Phi(phi) = statement
+            && successors.contains(editor.content[index.0].name.as_ref().unwrap())
+            && phi.from.iter().any(|it| it.block == caller_block_name)
+        {
+            let mut phi = phi.clone();
+            for source in &mut phi.from {
+                if source.block == caller_block_name {
+                    source.block.clone_from(&end_label);
+                }
+            }
+            to_replace.push((index, phi));
+        }
+    }
+    for (index, phi) in to_replace {
+        editor.replace_statement(index, phi);
+    }
+}
+
+/// Find a call in `caller` which should be inlined, and the function it calls.
+fn next_inline_site(
+    caller: &FunctionDefinition,
+    functions: &HashMap<&str, &FunctionDefinition>,
+) -> Option<(FunctionDefinitionIndex, FunctionDefinition)> {
+    caller
+        .iter()
+        .function_definition_index_enumerate()
+        .find_map(|(index, statement)| {
+            let IRStatement::Call(call) = statement else {
+                return None;
+            };
+            let callee = functions.get(call.name.as_str())?;
+            let recursive = is_recursive(&call.name, functions);
+            should_inline(callee, call, recursive).then(|| (index, (*callee).clone()))
+        })
+}
+
+impl IsModulePass for Inline {
+    fn run(&self, module: &mut Vec<IR>) {
+        for caller_index in 0..module.len() {
+            while let IR::FunctionDefinition(caller) = &module[caller_index] {
+                let functions: HashMap<_, _> = module
+                    .iter()
+                    .filter_map(|it| {
+                        if let IR::FunctionDefinition(function) = it {
+                            Some((function.header.name.as_str(), function))
+                        } else {
+                            None
+                        }
+                    })
+                    .collect();
+                let Some((index, callee)) = next_inline_site(caller, &functions) else {
+                    break;
+                };
+                let mut editor = Editor::new(caller.clone());
+                inline_call(&mut editor, index, &callee);
+                module[caller_index] = IR::FunctionDefinition(editor.content);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement};
+
+    #[test]
+    fn run() {
+        let mut module = ir::from_ir_code(
+            "fn abs(i32 %a) -> i32 {
+  abs_entry:
+    blt %a, 0, negative, positive
+  negative:
+    %0 = neg i32 %a
+    ret %0
+  positive:
+    ret %a
+}
+fn main() -> i32 {
+  main_entry:
+    %0 = call i32 abs(-3)
+    %1 = add i32 %0, 1
+    j main_end
+  main_end:
+    %2 = phi i32 [%1, main_entry]
+    ret %2
+}",
+        )
+        .unwrap()
+        .1;
+        Inline.run(&mut module);
+        let main = module[1].as_function_definition();
+        let parse = |code| statement::parse(code).unwrap().1;
+        let names: Vec<_> = main
+            .content
+            .iter()
+            .map(|it| it.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "main_entry",
+                "abs_inline0_abs_entry",
+                "abs_inline0_negative",
+                "abs_inline0_positive",
+                "abs_inline0_end",
+                "main_end",
+            ]
+        );
+        assert_eq!(
+            main.content[0].content,
+            vec![parse("j abs_inline0_abs_entry")]
+        );
+        assert_eq!(
+            main.content[1].content,
+            vec![parse(
+                "blt -3, 0, abs_inline0_negative, abs_inline0_positive"
+            )]
+        );
+        assert_eq!(
+            main.content[2].content,
+            vec![
+                parse("%abs_inline0_0 = neg i32 -3"),
+                parse("j abs_inline0_end")
+            ]
+        );
+        assert_eq!(
+            main.content[4].content,
+            vec![
+                parse("%0 = phi i32 [%abs_inline0_0, abs_inline0_negative], [-3, abs_inline0_positive]"),
+                parse("%1 = add i32 %0, 1"),
+                parse("j main_end"),
+            ]
+        );
+        assert_eq!(
+            main.content[5].content[0],
+            parse("%2 = phi i32 [%1, abs_inline0_end]")
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        let mut module = ir::from_ir_code(
+            "fn f(i32 %a) -> i32 {
+  f_entry:
+    %0 = call i32 g(%a)
+    ret %0
+}
+fn g(i32 %a) -> i32 {
+  g_entry:
+    %0 = call i32 f(%a)
+    ret %0
+}",
+        )
+        .unwrap()
+        .1;
+        let origin: Vec<_> = module.iter().map(|it| it.to_string()).collect();
+        Inline.run(&mut module);
+        let result: Vec<_> = module.iter().map(|it| it.to_string()).collect();
+        assert_eq!(origin, result);
+    }
+}
@@ -0,0 +1,52 @@
+mod inline;
+mod scalar_replacement_of_aggregates;
+use crate::ir::IR;
+use enum_dispatch::enum_dispatch;
+pub use inline::Inline;
+pub use scalar_replacement_of_aggregates::ScalarReplacementOfAggregates;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// This trait should be implemented by all passes which need to look at or change more than one
+/// function at a time.
+#[enum_dispatch]
+pub trait IsModulePass {
+    fn run(&self, module: &mut Vec<IR>);
+}
+
+/// All passes which can do optimizing on the whole ir module.
+#[enum_dispatch(IsModulePass)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ModulePass {
+    Inline,
+    ScalarReplacementOfAggregates,
+}
+
+impl FromStr for ModulePass {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Inline" => Ok(Self::Inline(Inline)),
+            "ScalarReplacementOfAggregates" => Ok(Self::ScalarReplacementOfAggregates(
+                ScalarReplacementOfAggregates,
+            )),
+            _ => Err(()),
+        }
+    }
+}
+
+impl From<&str> for ModulePass {
+    fn from(s: &str) -> Self {
+        Self::from_str(s).unwrap()
+    }
+}
+
+impl fmt::Display for ModulePass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inline(_) => write!(f, "Inline"),
+            Self::ScalarReplacementOfAggregates(_) => write!(f, "ScalarReplacementOfAggregates"),
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::IsModulePass;
+use crate::{
+    ir::{
+        editor::Editor,
+        function::FunctionDefinitionIndex,
+        quantity::Quantity,
+        statement::{Alloca, IRStatement, Load, LoadField, SetField, Store},
+        FunctionDefinition, RegisterName, TypeDefinition, IR,
+    },
+    utility::data_type::Type,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`ScalarReplacementOfAggregates`] splits a struct variable on the stack into one variable per
+/// field, `load_field`s and `setfield`s on the struct become plain `load`s and `store`s of the
+/// fields.
+///
+/// A struct variable is split only if its address doesn't escape, and the struct values loaded
+/// from it are only used by `load_field`, `setfield` and `store`s back into it.
+/// The field variables are not promoted to registers, run `MemoryToRegister` after this pass for
+/// that.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ScalarReplacementOfAggregates;
+
+/// Value of a field of a struct value.
+#[derive(Clone)]
+enum FieldValue {
+    /// The field of the struct loaded into this register.
+    Loaded(RegisterName),
+    /// The field was set to this value.
+    Value(Quantity),
+}
+
+fn field_register(register: &RegisterName, field: usize) -> RegisterName {
+    RegisterName(format!("field{field}_{}", register.0))
+}
+
+/// Split the struct variable `variable` into its fields, returns whether it is split.
+fn split(
+    editor: &mut Editor,
+    variable: &RegisterName,
+    types: &HashMap<String, TypeDefinition>,
+) -> bool {
+    let function = &editor.content;
+    let analyzer = editor.binded_analyzer();
+    let register_usage = analyzer.register_usage();
+    let memory_usage = analyzer.memory_usage();
+    if !memory_usage
+        .memory_access_variables()
+        .any(|it| it == variable)
+    {
+        return false;
+    }
+    let info = memory_usage.memory_access_info(variable);
+    let alloca_index = info.alloca.clone().unwrap();
+    let struct_type = function[alloca_index.clone()]
+        .as_alloca()
+        .alloc_type
+        .clone();
+    let Some(field_types) = (match &struct_type {
+        Type::StructRef(name) => types.get(name).map(|it| &it.fields),
+        _ => None,
+    }) else {
+        return false;
+    };
+
+    // fields of the struct values built from the variable
+    let mut values: HashMap<RegisterName, Vec<FieldValue>> = HashMap::new();
+    for load_index in &info.load {
+        let load = function[load_index.clone()].as_load();
+        let fields = (0..field_types.len())
+            .map(|_| FieldValue::Loaded(load.to.clone()))
+            .collect();
+        values.insert(load.to.clone(), fields);
+    }
+    let set_fields: Vec<_> = function
+        .iter()
+        .function_definition_index_enumerate()
+        .filter_map(|(index, statement)| {
+            if let IRStatement::SetField(set_field) = statement {
+                Some((index, set_field))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (_, set_field) in &set_fields {
+            if values.contains_key(&set_field.target) {
+                continue;
+            }
+            let Some(origin) = values.get(&set_field.origin_root) else {
+                continue;
+            };
+            let mut fields = origin.clone();
+            let field = set_field.field_chain[0].1;
+            fields[field] = if set_field.field_chain.len() == 1 {
+                FieldValue::Value(set_field.source.clone())
+            } else {
+                FieldValue::Value(field_register(&set_field.target, field).into())
+            };
+            values.insert(set_field.target.clone(), fields);
+            changed = true;
+        }
+    }
+    for value in values.keys() {
+        for use_index in register_usage.get(value).use_indexes() {
+            let used_as_struct = match &function[use_index.clone()] {
+                IRStatement::LoadField(load_field) => &load_field.source == value,
+                IRStatement::SetField(set_field) => {
+                    &set_field.origin_root == value && set_field.source.as_local() != Some(value)
+                }
+                IRStatement::Store(store) => store.target.as_local() == Some(variable),
+                _ => false,
+            };
+            if !used_as_struct {
+                return false;
+            }
+        }
+    }
+    for store_index in &info.store {
+        if function[store_index.clone()]
+            .as_store()
+            .source
+            .as_local()
+            .is_none()
+        {
+            return false;
+        }
+    }
+
+    let mut replacements: HashMap<FunctionDefinitionIndex, Vec<IRStatement>> = HashMap::new();
+    let mut renames = Vec::new();
+    // which fields of the loaded struct values are used
+    let mut used_fields: HashMap<RegisterName, BTreeSet<usize>> = HashMap::new();
+    let mut value_of = |field_value: &FieldValue, field: usize| match field_value {
+        FieldValue::Loaded(loaded) => {
+            used_fields.entry(loaded.clone()).or_default().insert(field);
+            Quantity::from(field_register(loaded, field))
+        }
+        FieldValue::Value(value) => value.clone(),
+    };
+    replacements.insert(
+        alloca_index,
+        field_types
+            .iter()
+            .enumerate()
+            .map(|(field, field_type)| {
+                Alloca {
+                    to: field_register(variable, field),
+                    alloc_type: field_type.clone(),
+                }
+                .into()
+            })
+            .collect(),
+    );
+    for (index, set_field) in &set_fields {
+        let Some(origin) = values.get(&set_field.origin_root) else {
+            continue;
+        };
+        let field = set_field.field_chain[0].1;
+        let replacement = if set_field.field_chain.len() == 1 {
+            Vec::new()
+        } else {
+            let inner_origin = value_of(&origin[field], field);
+            vec![SetField {
+                target: field_register(&set_field.target, field),
+                source: set_field.source.clone(),
+                origin_root: inner_origin.unwrap_local(),
+                field_chain: set_field.field_chain[1..].to_vec(),
+                final_type: set_field.final_type.clone(),
+            }
+            .into()]
+        };
+        replacements.insert(index.clone(), replacement);
+    }
+    for (index, statement) in function.iter().function_definition_index_enumerate() {
+        let IRStatement::LoadField(load_field) = statement else {
+            continue;
+        };
+        let Some(fields) = values.get(&load_field.source) else {
+            continue;
+        };
+        let field = load_field.field_chain[0].1;
+        let field_value = value_of(&fields[field], field);
+        let replacement = if load_field.field_chain.len() == 1 {
+            renames.push((load_field.target.clone(), field_value));
+            Vec::new()
+        } else {
+            vec![LoadField {
+                target: load_field.target.clone(),
+                source: field_value.unwrap_local(),
+                field_chain: load_field.field_chain[1..].to_vec(),
+                leaf_type: load_field.leaf_type.clone(),
+            }
+            .into()]
+        };
+        replacements.insert(index, replacement);
+    }
+    for (store_ordinal, store_index) in info.store.iter().enumerate() {
+        let source = function[store_index.clone()]
+            .as_store()
+            .source
+            .clone()
+            .unwrap_local();
+        let mut replacement = Vec::new();
+        for (field, field_type) in field_types.iter().enumerate() {
+            let field_value = if let Some(fields) = values.get(&source) {
+                // storing a field back to where it was just loaded from can be skipped
+                if let FieldValue::Loaded(loaded) = &fields[field] {
+                    let load_index = register_usage
+                        .get(loaded)
+                        .define_position()
+                        .unwrap_body()
+                        .clone();
+                    let stored_in_between = info.store.iter().any(|it| {
+                        it.0 == store_index.0 && load_index.1 < it.1 && it.1 < store_index.1
+                    });
+                    if load_index.0 == store_index.0
+                        && load_index.1 < store_index.1
+                        && !stored_in_between
+                    {
+                        continue;
+                    }
+                }
+                value_of(&fields[field], field)
+            } else {
+                let loaded = RegisterName(format!(
+                    "{}_{store_ordinal}",
+                    field_register(variable, field).0
+                ));
+                replacement.push(
+                    LoadField {
+                        target: loaded.clone(),
+                        source: source.clone(),
+                        field_chain: vec![(struct_type.clone(), field)],
+                        leaf_type: field_type.clone(),
+                    }
+                    .into(),
+                );
+                loaded.into()
+            };
+            replacement.push(
+                Store {
+                    data_type: field_type.clone(),
+                    source: field_value,
+                    target: field_register(variable, field).into(),
+                }
+                .into(),
+            );
+        }
+        replacements.insert(store_index.clone(), replacement);
+    }
+    for load_index in &info.load {
+        let loaded = &function[load_index.clone()].as_load().to;
+        let replacement = used_fields
+            .get(loaded)
+            .into_iter()
+            .flatten()
+            .map(|&field| {
+                Load {
+                    to: field_register(loaded, field),
+                    data_type: field_types[field].clone(),
+                    from: field_register(variable, field).into(),
+                }
+                .into()
+            })
+            .collect();
+        replacements.insert(load_index.clone(), replacement);
+    }
+
+    let mut replacements: Vec<_> = replacements.into_iter().collect();
+    replacements.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (index, statements) in replacements {
+        editor.remove_statement(index.clone());
+        for statement in statements.into_iter().rev() {
+            editor.insert_statement(index.clone(), statement);
+        }
+    }
+    editor.rename_locals(renames);
+    true
+}
+
+fn run_on_function(
+    function: FunctionDefinition,
+    types: &HashMap<String, TypeDefinition>,
+) -> FunctionDefinition {
+    let mut editor = Editor::new(function);
+    let mut visited = HashSet::new();
+    // the fields may be structs which can be split further
+    while let Some(variable) = editor
+        .content
+        .iter()
+        .filter_map(|statement| {
+            if let IRStatement::Alloca(alloca) = statement
+                && matches!(alloca.alloc_type, Type::StructRef(_))
+                && !visited.contains(&alloca.to)
+            {
+                Some(alloca.to.clone())
+            } else {
+                None
+            }
+        })
+        .next()
+    {
+        split(&mut editor, &variable, types);
+        visited.insert(variable);
+    }
+    editor.content
+}
+
+impl IsModulePass for ScalarReplacementOfAggregates {
+    fn run(&self, module: &mut Vec<IR>) {
+        let types: HashMap<_, _> = module
+            .iter()
+            .filter_map(|it| {
+                if let IR::TypeDefinition(type_definition) = it {
+                    Some((type_definition.name.clone(), type_definition.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        for ir in module.iter_mut() {
+            if let IR::FunctionDefinition(function) = ir {
+                *function = run_on_function(function.clone(), &types);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{
+        self,
+        optimize::{pass::Pass, FunctionOptimizer},
+        statement,
+    };
+
+    #[test]
+    fn run() {
+        let mut module = ir::from_ir_code(
+            "%Foo = type { i32, i32 }
+fn f(Foo %foo) -> i32 {
+  f_entry:
+    %foo_addr = alloca Foo
+    store Foo %foo, address %foo_addr
+    %1 = load Foo %foo_addr
+    %2 = load_field i32 %1.[Foo.0]
+    %3 = load Foo %foo_addr
+    %4 = load_field i32 %3.[Foo.1]
+    %0 = add i32 %2, %4
+    %5 = load Foo %foo_addr
+    %6 = setfield i32 %5.[Foo.0] %0
+    store Foo %6, address %foo_addr
+    %7 = load Foo %foo_addr
+    %8 = load_field i32 %7.[Foo.0]
+    ret %8
+}",
+        )
+        .unwrap()
+        .1;
+        ScalarReplacementOfAggregates.run(&mut module);
+        let function = module[1].as_function_definition();
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(
+            function.content[0].content,
+            vec![
+                parse("%field0_foo_addr = alloca i32"),
+                parse("%field1_foo_addr = alloca i32"),
+                parse("%field0_foo_addr_0 = load_field i32 %foo.[Foo.0]"),
+                parse("store i32 %field0_foo_addr_0, address %field0_foo_addr"),
+                parse("%field1_foo_addr_0 = load_field i32 %foo.[Foo.1]"),
+                parse("store i32 %field1_foo_addr_0, address %field1_foo_addr"),
+                parse("%field0_1 = load i32 %field0_foo_addr"),
+                parse("%field1_3 = load i32 %field1_foo_addr"),
+                parse("%0 = add i32 %field0_1, %field1_3"),
+                parse("store i32 %0, address %field0_foo_addr"),
+                parse("%field0_7 = load i32 %field0_foo_addr"),
+                parse("ret %field0_7"),
+            ]
+        );
+        let function = FunctionOptimizer::from_passes(vec![
+            Pass::from("MemoryToRegister"),
+            Pass::from("RemoveUnusedRegister"),
+        ])
+        .optimize(function.clone());
+        assert!(!function
+            .iter()
+            .any(|it| matches!(it, IRStatement::Alloca(_) | IRStatement::Load(_))));
+    }
+
+    #[test]
+    fn escaped() {
+        let mut module = ir::from_ir_code(
+            "%Foo = type { i32, i32 }
+fn f(Foo %foo) -> Foo {
+  f_entry:
+    %foo_addr = alloca Foo
+    store Foo %foo, address %foo_addr
+    %1 = load Foo %foo_addr
+    %2 = setfield i32 %1.[Foo.0] 1
+    ret %2
+}",
+        )
+        .unwrap()
+        .1;
+        let origin = module[1].to_string();
+        ScalarReplacementOfAggregates.run(&mut module);
+        assert_eq!(module[1].to_string(), origin);
+    }
+}
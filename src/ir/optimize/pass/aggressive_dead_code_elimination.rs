@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use super::IsPass;
+use crate::ir::{
+    editor::{analyzer::register_usage::RegisterDefinePosition, Editor},
+    function::FunctionDefinitionIndex,
+    quantity::Quantity,
+    statement::{IRStatement, IsIRStatement},
+    FunctionDefinition,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`AggressiveDeadCodeElimination`] assumes all statements are dead unless proven otherwise.
+///
+/// Statements with side effects (stores which may be observed, calls and control flow) are alive,
+/// and so are the statements they depend on; all other statements are removed.
+/// Basic blocks which cannot be reached from the entry are removed as well.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AggressiveDeadCodeElimination;
+
+/// Indexes of blocks which can be reached from the entry block.
+fn reachable_blocks(function: &FunctionDefinition) -> HashSet<usize> {
+    let block_index = |name: &str| {
+        function
+            .content
+            .iter()
+            .position(|it| it.name.as_deref() == Some(name))
+            .unwrap()
+    };
+    let mut reachable = HashSet::from([0]);
+    let mut pending = vec![0];
+    while let Some(block) = pending.pop() {
+        let successors = match function.content[block].content.last() {
+            Some(IRStatement::Jump(jump)) => vec![block_index(&jump.label)],
+            Some(IRStatement::Branch(branch)) => vec![
+                block_index(&branch.success_label),
+                block_index(&branch.failure_label),
+            ],
+            _ => Vec::new(),
+        };
+        for successor in successors {
+            if reachable.insert(successor) {
+                pending.push(successor);
+            }
+        }
+    }
+    reachable
+}
+
+/// Remove basic blocks which cannot be reached, and the phi sources from them.
+pub(super) fn remove_unreachable_blocks(editor: &mut Editor) {
+    let reachable = reachable_blocks(&editor.content);
+    let unreachable_names: HashSet<_> = editor
+        .content
+        .content
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !reachable.contains(index))
+        .map(|(_, block)| block.name.clone().unwrap())
+        .collect();
+    if unreachable_names.is_empty() {
+        return;
+    }
+    let mut to_replace = Vec::new();
+    for (index, statement) in editor.content.iter().function_definition_index_enumerate() {
+        if let IRStatement::Phi(phi) = statement
+            && phi
+                .from
+                .iter()
+                .any(|source| unreachable_names.contains(&source.block))
+        {
+            let mut phi = phi.clone();
+            phi.from
+                .retain(|source| !unreachable_names.contains(&source.block));
+            to_replace.push((index, phi));
+        }
+    }
+    for (index, phi) in to_replace {
+        editor.replace_statement(index, phi);
+    }
+    let mut unreachable: Vec<_> = (0..editor.content.content.len())
+        .filter(|it| !reachable.contains(it))
+        .collect();
+    while let Some(index) = unreachable.pop() {
+        editor.remove_basic_block(index);
+    }
+}
+
+impl IsPass for AggressiveDeadCodeElimination {
+    fn run(&self, editor: &mut Editor) {
+        remove_unreachable_blocks(editor);
+        let analyzer = editor.binded_analyzer();
+        let register_usage = analyzer.register_usage();
+        let memory_usage = analyzer.memory_usage();
+        // stores to these allocas can never be observed
+        let never_loaded: HashSet<_> = memory_usage
+            .memory_access_variables()
+            .filter(|it| memory_usage.memory_access_info(it).load.is_empty())
+            .collect();
+        let mut alive = HashSet::new();
+        let mut pending = Vec::new();
+        for (index, statement) in editor.content.iter().function_definition_index_enumerate() {
+            let is_root = match statement {
+                IRStatement::Store(store) => match &store.target {
+                    Quantity::RegisterName(target) => !never_loaded.contains(target),
+                    _ => true,
+                },
+                IRStatement::Call(_)
+                | IRStatement::Jump(_)
+                | IRStatement::Branch(_)
+                | IRStatement::Ret(_) => true,
+                _ => statement
+                    .generate_register()
+                    .is_some_and(|(register, _)| register_usage.get(&register).side_effect()),
+            };
+            if is_root {
+                alive.insert(index.clone());
+                pending.push(index);
+            }
+        }
+        while let Some(index) = pending.pop() {
+            for register in editor.content[index].use_register() {
+                if let RegisterDefinePosition::Body(define_index) =
+                    register_usage.get(&register).define_position()
+                    && alive.insert(define_index.clone())
+                {
+                    pending.push(define_index.clone());
+                }
+            }
+        }
+        let to_remove: Vec<FunctionDefinitionIndex> = editor
+            .content
+            .iter()
+            .function_definition_index_enumerate()
+            .map(|(index, _)| index)
+            .filter(|it| !alive.contains(it))
+            .collect();
+        editor.remove_statements(to_remove);
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        Vec::new()
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement};
+
+    #[test]
+    fn run() {
+        let function = ir::function::parse(
+            "fn f(i32 %a) -> i32 {
+  bb0:
+    %x_addr = alloca i32
+    %y_addr = alloca i32
+    store i32 %a, address %x_addr
+    %0 = add i32 %a, 1
+    %1 = mul i32 %0, 2
+    store i32 %1, address %y_addr
+    %2 = load i32 %x_addr
+    %3 = add i32 %2, %a
+    %4 = sub i32 %3, 1
+    j bb1
+  bb1:
+    %5 = phi i32 [%3, bb0], [%6, bb2]
+    ret %5
+  bb2:
+    %6 = add i32 %a, 2
+    j bb1
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        AggressiveDeadCodeElimination.run(&mut editor);
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(editor.content.content.len(), 2);
+        assert_eq!(
+            editor.content.content[0].content,
+            vec![
+                parse("%x_addr = alloca i32"),
+                parse("store i32 %a, address %x_addr"),
+                parse("%2 = load i32 %x_addr"),
+                parse("%3 = add i32 %2, %a"),
+                parse("j bb1"),
+            ]
+        );
+        assert_eq!(
+            editor.content.content[1].content[0],
+            parse("%5 = phi i32 [%3, bb0]")
+        );
+    }
+}
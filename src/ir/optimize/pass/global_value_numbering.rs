@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use super::{
+    memory_to_register::MemoryToRegister, remove_unused_register::RemoveUnusedRegister, IsPass,
+};
+use crate::{
+    ir::{
+        editor::{analyzer::BindedControlFlowGraph, Editor},
+        function::FunctionDefinitionIndex,
+        quantity::Quantity,
+        statement::{
+            calculate::{binary::BinaryOperation, unary::UnaryOperation},
+            BinaryCalculate, IRStatement, LoadField, SetField, UnaryCalculate,
+        },
+        FunctionDefinition, RegisterName,
+    },
+    utility::data_type::Type,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`GlobalValueNumbering`] finds statements which calculate a value that has already been
+/// calculated by a statement dominating it, and reuses the result of the dominating one.
+///
+/// Loading a field which was just set by a `setfield` is also replaced by the value set.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GlobalValueNumbering;
+
+/// What a statement calculates, two statements with the same [`Expression`] give the same value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Expression {
+    Binary(BinaryOperation, Quantity, Quantity, Type),
+    Unary(UnaryOperation, Quantity, Type),
+    LoadField(Quantity, Vec<(Type, usize)>, Type),
+}
+
+fn is_commutative(operation: BinaryOperation) -> bool {
+    matches!(
+        operation,
+        BinaryOperation::Add
+            | BinaryOperation::Mul
+            | BinaryOperation::And
+            | BinaryOperation::Or
+            | BinaryOperation::Xor
+            | BinaryOperation::Equal
+            | BinaryOperation::NotEqual
+    )
+}
+
+fn leader_of(quantity: &Quantity, leaders: &HashMap<RegisterName, Quantity>) -> Quantity {
+    quantity
+        .as_local()
+        .and_then(|it| leaders.get(it))
+        .unwrap_or(quantity)
+        .clone()
+}
+
+/// Where the value of a field comes from.
+enum FieldSource {
+    /// The field was set to this value by a `setfield`.
+    Value(Quantity),
+    /// The field should be loaded from this struct.
+    Struct(Quantity),
+}
+
+/// Look through the `setfield`s which built the struct `load_field` loads from.
+fn trace_field(
+    load_field: &LoadField,
+    set_fields: &HashMap<&RegisterName, &SetField>,
+    leaders: &HashMap<RegisterName, Quantity>,
+) -> FieldSource {
+    let mut current = leader_of(&load_field.source.clone().into(), leaders);
+    while let Some(set_field) = current.as_local().and_then(|it| set_fields.get(it)) {
+        if set_field.field_chain == load_field.field_chain {
+            return FieldSource::Value(leader_of(&set_field.source, leaders));
+        }
+        let disjoint = set_field
+            .field_chain
+            .iter()
+            .zip(&load_field.field_chain)
+            .any(|(set, load)| set != load);
+        if !disjoint {
+            // one field contains the other
+            break;
+        }
+        current = leader_of(&set_field.origin_root.clone().into(), leaders);
+    }
+    FieldSource::Struct(current)
+}
+
+/// Find the [`Expression`] of a calculating `statement`, with operands replaced by their leaders.
+fn expression_of(
+    statement: &IRStatement,
+    leaders: &HashMap<RegisterName, Quantity>,
+) -> Option<(RegisterName, Expression)> {
+    let leader_of = |quantity: &Quantity| leader_of(quantity, leaders);
+    match statement {
+        IRStatement::BinaryCalculate(BinaryCalculate {
+            operation,
+            operand1,
+            operand2,
+            to,
+            data_type,
+        }) => {
+            let mut operand1 = leader_of(operand1);
+            let mut operand2 = leader_of(operand2);
+            // so `a + b` and `b + a` are the same expression
+            if is_commutative(*operation) && operand1.to_string() > operand2.to_string() {
+                (operand1, operand2) = (operand2, operand1);
+            }
+            Some((
+                to.clone(),
+                Expression::Binary(*operation, operand1, operand2, data_type.clone()),
+            ))
+        }
+        IRStatement::UnaryCalculate(UnaryCalculate {
+            operation,
+            operand,
+            to,
+            data_type,
+        }) => Some((
+            to.clone(),
+            Expression::Unary(*operation, leader_of(operand), data_type.clone()),
+        )),
+        _ => None,
+    }
+}
+
+/// Visit the dominator tree in preorder, an expression is available in all blocks its
+/// calculating block dominates.
+fn visit(
+    block_index: usize,
+    function: &FunctionDefinition,
+    control_flow_graph: &BindedControlFlowGraph,
+    set_fields: &HashMap<&RegisterName, &SetField>,
+    available: &mut HashMap<Expression, RegisterName>,
+    leaders: &mut HashMap<RegisterName, Quantity>,
+    redundant: &mut Vec<FunctionDefinitionIndex>,
+) {
+    let mut introduced = Vec::new();
+    for (statement_index, statement) in function.content[block_index].content.iter().enumerate() {
+        let expression = if let IRStatement::LoadField(load_field) = statement {
+            match trace_field(load_field, set_fields, leaders) {
+                FieldSource::Value(value) => {
+                    leaders.insert(load_field.target.clone(), value);
+                    redundant.push((block_index, statement_index).into());
+                    continue;
+                }
+                FieldSource::Struct(root) => Some((
+                    load_field.target.clone(),
+                    Expression::LoadField(
+                        root,
+                        load_field.field_chain.clone(),
+                        load_field.leaf_type.clone(),
+                    ),
+                )),
+            }
+        } else {
+            expression_of(statement, leaders)
+        };
+        if let Some((register, expression)) = expression {
+            if let Some(leader) = available.get(&expression) {
+                leaders.insert(register, leader.clone().into());
+                redundant.push((block_index, statement_index).into());
+            } else {
+                available.insert(expression.clone(), register);
+                introduced.push(expression);
+            }
+        }
+    }
+    for child in control_flow_graph.immediately_dominates(block_index) {
+        visit(
+            child,
+            function,
+            control_flow_graph,
+            set_fields,
+            available,
+            leaders,
+            redundant,
+        );
+    }
+    for expression in introduced {
+        available.remove(&expression);
+    }
+}
+
+impl IsPass for GlobalValueNumbering {
+    fn run(&self, editor: &mut Editor) {
+        let mut leaders = HashMap::new();
+        let mut redundant = Vec::new();
+        let set_fields = editor
+            .content
+            .iter()
+            .filter_map(|statement| {
+                if let IRStatement::SetField(set_field) = statement {
+                    Some((&set_field.target, set_field))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        visit(
+            0,
+            &editor.content,
+            &editor.binded_analyzer().control_flow_graph(),
+            &set_fields,
+            &mut HashMap::new(),
+            &mut leaders,
+            &mut redundant,
+        );
+        editor.remove_statements(redundant);
+        for (register, leader) in leaders {
+            editor.rename_local(register, leader);
+        }
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        vec![MemoryToRegister.into()]
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        vec![RemoveUnusedRegister.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement};
+
+    #[test]
+    fn run() {
+        let function = ir::function::parse(
+            "fn f(i32 %a, i32 %b, Foo %foo) -> i32 {
+  bb0:
+    %0 = add i32 %a, %b
+    %1 = load_field i32 %foo.[Foo.0]
+    bne %0, 0, bb1, bb2
+  bb1:
+    %2 = add i32 %b, %a
+    %3 = sub i32 %2, %1
+    %4 = load_field i32 %foo.[Foo.0]
+    %5 = sub i32 %0, %4
+    %6 = neg i32 %5
+    j bb3
+  bb2:
+    %7 = sub i32 %a, %b
+    %8 = add i32 %a, %b
+    j bb3
+  bb3:
+    %9 = phi i32 [%6, bb1], [%7, bb2]
+    %10 = sub i32 %a, %b
+    %11 = add i32 %9, %10
+    ret %11
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        GlobalValueNumbering.run(&mut editor);
+        let content = &editor.content.content;
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(
+            content[1].content,
+            vec![
+                parse("%3 = sub i32 %0, %1"),
+                parse("%6 = neg i32 %3"),
+                parse("j bb3"),
+            ]
+        );
+        assert_eq!(content[2].content.len(), 2);
+        // bb2 doesn't dominate bb3, so `%10` cannot reuse `%7`
+        assert_eq!(content[3].content.len(), 4);
+    }
+
+    #[test]
+    fn run_set_field() {
+        let function = ir::function::parse(
+            "fn f(Foo %foo) -> i32 {
+  f_entry:
+    %2 = load_field i32 %foo.[Foo.0]
+    %4 = load_field i32 %foo.[Foo.1]
+    %0 = add i32 %2, %4
+    %6 = setfield i32 %foo.[Foo.0] %0
+    %9 = load_field i32 %6.[Foo.1]
+    %11 = load_field i32 %6.[Foo.0]
+    %7 = add i32 %9, %11
+    ret %7
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        GlobalValueNumbering.run(&mut editor);
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(
+            editor.content.content[0].content[3..],
+            vec![
+                parse("%6 = setfield i32 %foo.[Foo.0] %0"),
+                parse("%7 = add i32 %4, %0"),
+                parse("ret %7"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+
+use super::{remove_unused_register::RemoveUnusedRegister, IsPass};
+use crate::ir::{
+    editor::Editor,
+    function::FunctionDefinitionIndex,
+    quantity::Quantity,
+    statement::{
+        branch::BranchType,
+        calculate::{binary::BinaryOperation, unary::UnaryOperation},
+        BinaryCalculate, Branch, IRStatement, IsIRStatement,
+    },
+    FunctionDefinition, RegisterName,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`InstructionCombine`] does peephole algebraic simplifications and strength reductions on
+/// single statements, eg. `x + 0` becomes `x`, and `x * 8` becomes `x << 3`.
+///
+/// The simplifications are listed in [`RULES`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InstructionCombine;
+
+/// Kind of statements a [`Rule`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Binary(BinaryOperation),
+    Unary(UnaryOperation),
+    Branch(BranchType),
+}
+
+impl Operation {
+    fn is_commutative(self) -> bool {
+        matches!(
+            self,
+            Operation::Binary(
+                BinaryOperation::Add
+                    | BinaryOperation::Mul
+                    | BinaryOperation::And
+                    | BinaryOperation::Or
+                    | BinaryOperation::Xor
+                    | BinaryOperation::Equal
+                    | BinaryOperation::NotEqual
+            ) | Operation::Branch(BranchType::EQ | BranchType::NE)
+        )
+    }
+}
+
+/// What an operand should look like for a [`Rule`] to apply.
+#[derive(Debug, Clone, Copy)]
+enum Pattern {
+    Any,
+    Constant(i64),
+    /// A constant which is `2^k` for some `k > 0`.
+    PowerOfTwo,
+    /// The same value as the first operand.
+    SameAsFirst,
+    /// A register holding the result of a comparison, ie. a binary operation which has an inverse.
+    Comparison,
+    /// A register holding the result of the unary operation.
+    Unary(UnaryOperation),
+}
+
+/// A statement matched by a [`Rule`], with operands in the order of the patterns.
+struct Matched<'a> {
+    statement: &'a IRStatement,
+    operands: Vec<&'a Quantity>,
+    /// Statements which define the operands.
+    definitions: Vec<Option<&'a IRStatement>>,
+}
+
+/// What a matched statement should become.
+#[derive(Debug, PartialEq)]
+enum Combined {
+    /// The statement is removed, and its result is replaced with this value.
+    Value(Quantity),
+    Statement(IRStatement),
+}
+
+struct Rule {
+    operation: Operation,
+    operands: &'static [Pattern],
+    rewrite: fn(&Matched) -> Combined,
+}
+
+/// All simplifications done by [`InstructionCombine`].
+/// Operands of commutative operations are also matched in the swapped order.
+const RULES: &[Rule] = &[
+    // x + 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Add),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x - 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Sub),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x - x -> 0
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Sub),
+        operands: &[Pattern::Any, Pattern::SameAsFirst],
+        rewrite: |_| Combined::Value(0.into()),
+    },
+    // x * 0 -> 0
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Mul),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: |_| Combined::Value(0.into()),
+    },
+    // x * 1 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Mul),
+        operands: &[Pattern::Any, Pattern::Constant(1)],
+        rewrite: first_operand,
+    },
+    // x * 2^k -> x << k
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Mul),
+        operands: &[Pattern::Any, Pattern::PowerOfTwo],
+        rewrite: multiply_to_shift,
+    },
+    // x ^ 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Xor),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x ^ x -> 0
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Xor),
+        operands: &[Pattern::Any, Pattern::SameAsFirst],
+        rewrite: |_| Combined::Value(0.into()),
+    },
+    // x | 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Or),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x | x -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Or),
+        operands: &[Pattern::Any, Pattern::SameAsFirst],
+        rewrite: first_operand,
+    },
+    // x & 0 -> 0
+    Rule {
+        operation: Operation::Binary(BinaryOperation::And),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: |_| Combined::Value(0.into()),
+    },
+    // x & x -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::And),
+        operands: &[Pattern::Any, Pattern::SameAsFirst],
+        rewrite: first_operand,
+    },
+    // x << 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::LogicalShiftLeft),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x >> 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::LogicalShiftRight),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // x >> 0 -> x
+    Rule {
+        operation: Operation::Binary(BinaryOperation::AthematicShiftRight),
+        operands: &[Pattern::Any, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // -(-x) -> x
+    Rule {
+        operation: Operation::Unary(UnaryOperation::Neg),
+        operands: &[Pattern::Unary(UnaryOperation::Neg)],
+        rewrite: inner_operand,
+    },
+    // ~(~x) -> x
+    Rule {
+        operation: Operation::Unary(UnaryOperation::Not),
+        operands: &[Pattern::Unary(UnaryOperation::Not)],
+        rewrite: inner_operand,
+    },
+    // (a < b) == 0 -> a >= b
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Equal),
+        operands: &[Pattern::Comparison, Pattern::Constant(0)],
+        rewrite: inverse_comparison,
+    },
+    // (a < b) == 1 -> a < b
+    Rule {
+        operation: Operation::Binary(BinaryOperation::Equal),
+        operands: &[Pattern::Comparison, Pattern::Constant(1)],
+        rewrite: first_operand,
+    },
+    // (a < b) != 0 -> a < b
+    Rule {
+        operation: Operation::Binary(BinaryOperation::NotEqual),
+        operands: &[Pattern::Comparison, Pattern::Constant(0)],
+        rewrite: first_operand,
+    },
+    // (a < b) != 1 -> a >= b
+    Rule {
+        operation: Operation::Binary(BinaryOperation::NotEqual),
+        operands: &[Pattern::Comparison, Pattern::Constant(1)],
+        rewrite: inverse_comparison,
+    },
+    // branch if (a < b) == 0 -> branch if a >= b
+    Rule {
+        operation: Operation::Branch(BranchType::EQ),
+        operands: &[Pattern::Comparison, Pattern::Constant(0)],
+        rewrite: |matched| branch_on_comparison(matched, true),
+    },
+    // branch if (a < b) != 0 -> branch if a < b
+    Rule {
+        operation: Operation::Branch(BranchType::NE),
+        operands: &[Pattern::Comparison, Pattern::Constant(0)],
+        rewrite: |matched| branch_on_comparison(matched, false),
+    },
+];
+
+fn first_operand(matched: &Matched) -> Combined {
+    Combined::Value(matched.operands[0].clone())
+}
+
+/// The operand of the unary calculation which defines the first operand.
+fn inner_operand(matched: &Matched) -> Combined {
+    let Some(IRStatement::UnaryCalculate(inner)) = matched.definitions[0] else {
+        unreachable!()
+    };
+    Combined::Value(inner.operand.clone())
+}
+
+fn multiply_to_shift(matched: &Matched) -> Combined {
+    let IRStatement::BinaryCalculate(statement) = matched.statement else {
+        unreachable!()
+    };
+    let &Quantity::NumberLiteral(power) = matched.operands[1] else {
+        unreachable!()
+    };
+    Combined::Statement(
+        BinaryCalculate {
+            operation: BinaryOperation::LogicalShiftLeft,
+            operand1: matched.operands[0].clone(),
+            operand2: (power.trailing_zeros() as i64).into(),
+            ..statement.clone()
+        }
+        .into(),
+    )
+}
+
+/// The comparison which defines the first operand.
+fn comparison<'a>(matched: &Matched<'a>) -> &'a BinaryCalculate {
+    let Some(IRStatement::BinaryCalculate(comparison)) = matched.definitions[0] else {
+        unreachable!()
+    };
+    comparison
+}
+
+/// Do the inverse of the comparison which defines the first operand.
+fn inverse_comparison(matched: &Matched) -> Combined {
+    let comparison = comparison(matched);
+    Combined::Statement(
+        BinaryCalculate {
+            operation: comparison.operation.inverse().unwrap(),
+            to: matched.statement.generate_register().unwrap().0,
+            ..comparison.clone()
+        }
+        .into(),
+    )
+}
+
+/// Branch on the comparison which defines the first operand directly, or on its inverse.
+fn branch_on_comparison(matched: &Matched, inverse: bool) -> Combined {
+    let IRStatement::Branch(branch) = matched.statement else {
+        unreachable!()
+    };
+    let comparison = comparison(matched);
+    let operation = if inverse {
+        comparison.operation.inverse().unwrap()
+    } else {
+        comparison.operation
+    };
+    let (operand1, operand2) = (comparison.operand1.clone(), comparison.operand2.clone());
+    // `a > b` is `b < a`, and `a <= b` is `b >= a`
+    let (branch_type, operand1, operand2) = match operation {
+        BinaryOperation::Equal => (BranchType::EQ, operand1, operand2),
+        BinaryOperation::NotEqual => (BranchType::NE, operand1, operand2),
+        BinaryOperation::LessThan => (BranchType::LT, operand1, operand2),
+        BinaryOperation::GreaterOrEqualThan => (BranchType::GE, operand1, operand2),
+        BinaryOperation::GreaterThan => (BranchType::LT, operand2, operand1),
+        BinaryOperation::LessOrEqualThan => (BranchType::GE, operand2, operand1),
+        BinaryOperation::UnsignedLessThan => (BranchType::LTU, operand1, operand2),
+        BinaryOperation::UnsignedGreaterOrEqualThan => (BranchType::GEU, operand1, operand2),
+        BinaryOperation::UnsignedGreaterThan => (BranchType::LTU, operand2, operand1),
+        BinaryOperation::UnsignedLessOrEqualThan => (BranchType::GEU, operand2, operand1),
+        _ => unreachable!(),
+    };
+    Combined::Statement(
+        Branch {
+            branch_type,
+            operand1,
+            operand2,
+            ..branch.clone()
+        }
+        .into(),
+    )
+}
+
+fn operation_and_operands(statement: &IRStatement) -> Option<(Operation, Vec<&Quantity>)> {
+    match statement {
+        IRStatement::BinaryCalculate(binary) => Some((
+            Operation::Binary(binary.operation),
+            vec![&binary.operand1, &binary.operand2],
+        )),
+        IRStatement::UnaryCalculate(unary) => {
+            Some((Operation::Unary(unary.operation), vec![&unary.operand]))
+        }
+        IRStatement::Branch(branch) => Some((
+            Operation::Branch(branch.branch_type),
+            vec![&branch.operand1, &branch.operand2],
+        )),
+        _ => None,
+    }
+}
+
+fn matches_pattern(
+    pattern: Pattern,
+    operand: &Quantity,
+    first: &Quantity,
+    definition: Option<&IRStatement>,
+) -> bool {
+    match (pattern, operand) {
+        (Pattern::Any, _) => true,
+        (Pattern::Constant(expected), Quantity::NumberLiteral(value)) => *value == expected,
+        (Pattern::PowerOfTwo, Quantity::NumberLiteral(value)) => {
+            *value > 1 && (*value as u64).is_power_of_two()
+        }
+        (Pattern::SameAsFirst, _) => operand == first,
+        (Pattern::Comparison, _) => matches!(
+            definition,
+            Some(IRStatement::BinaryCalculate(it)) if it.operation.inverse().is_some()
+        ),
+        (Pattern::Unary(operation), _) => matches!(
+            definition,
+            Some(IRStatement::UnaryCalculate(it)) if it.operation == operation
+        ),
+        _ => false,
+    }
+}
+
+/// Try to apply `rule` on `statement`.
+fn apply<'a>(
+    rule: &Rule,
+    statement: &'a IRStatement,
+    definitions: &HashMap<&RegisterName, &'a IRStatement>,
+) -> Option<Combined> {
+    let (operation, operands) = operation_and_operands(statement)?;
+    if operation != rule.operation {
+        return None;
+    }
+    let mut orders = vec![operands.clone()];
+    if operation.is_commutative() {
+        orders.push(operands.into_iter().rev().collect());
+    }
+    orders.into_iter().find_map(|operands| {
+        let definitions: Vec<_> = operands
+            .iter()
+            .map(|it| it.as_local().and_then(|it| definitions.get(it).copied()))
+            .collect();
+        let all_matched = rule.operands.iter().zip(&operands).zip(&definitions).all(
+            |((&pattern, operand), &definition)| {
+                matches_pattern(pattern, operand, operands[0], definition)
+            },
+        );
+        all_matched.then(|| {
+            (rule.rewrite)(&Matched {
+                statement,
+                operands,
+                definitions,
+            })
+        })
+    })
+}
+
+/// Find a statement which can be simplified, and what it should become.
+fn next_combination(function: &FunctionDefinition) -> Option<(FunctionDefinitionIndex, Combined)> {
+    let definitions: HashMap<_, _> = function
+        .iter()
+        .filter_map(|statement| match statement {
+            IRStatement::BinaryCalculate(it) => Some((&it.to, statement)),
+            IRStatement::UnaryCalculate(it) => Some((&it.to, statement)),
+            _ => None,
+        })
+        .collect();
+    function
+        .iter()
+        .function_definition_index_enumerate()
+        .find_map(|(index, statement)| {
+            RULES
+                .iter()
+                .find_map(|rule| apply(rule, statement, &definitions))
+                .map(|combined| (index, combined))
+        })
+}
+
+impl IsPass for InstructionCombine {
+    fn run(&self, editor: &mut Editor) {
+        while let Some((index, combined)) = next_combination(&editor.content) {
+            match combined {
+                Combined::Value(value) => {
+                    let (register, _) = editor.content[index.clone()].generate_register().unwrap();
+                    editor.remove_statement(index);
+                    editor.rename_local(register, value);
+                }
+                Combined::Statement(statement) => editor.replace_statement(index, statement),
+            }
+        }
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        Vec::new()
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        vec![RemoveUnusedRegister.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement};
+
+    /// Run the pass on a function whose entry block contains `code` and returns `%r`.
+    fn combine(code: &str) -> Vec<IRStatement> {
+        let function = ir::function::parse(&format!(
+            "fn f(i32 %a, i32 %b) -> i32 {{\n  f_entry:\n{code}\n    ret %r\n}}"
+        ))
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        InstructionCombine.run(&mut editor);
+        editor.content.content[0].content.clone()
+    }
+
+    #[test]
+    fn rules() {
+        let parse = |code| statement::parse(code).unwrap().1;
+        let cases = [
+            ("%r = add i32 %a, 0", vec!["ret %a"]),
+            ("%r = add i32 0, %a", vec!["ret %a"]),
+            ("%r = sub i32 %a, %a", vec!["ret 0"]),
+            ("%r = sub i32 0, %a", vec!["%r = sub i32 0, %a", "ret %r"]),
+            ("%r = mul i32 8, %a", vec!["%r = shl i32 %a, 3", "ret %r"]),
+            ("%r = mul i32 %a, 6", vec!["%r = mul i32 %a, 6", "ret %r"]),
+            ("%r = xor i32 %a, %a", vec!["ret 0"]),
+            (
+                "%0 = neg i32 %a\n%r = neg i32 %0",
+                vec!["%0 = neg i32 %a", "ret %a"],
+            ),
+            (
+                "%0 = slt i32 %a, %b\n%r = eq i32 %0, 0",
+                vec!["%0 = slt i32 %a, %b", "%r = sge i32 %a, %b", "ret %r"],
+            ),
+            (
+                "%0 = slt i32 %a, %b\n%r = ne i32 0, %0",
+                vec!["%0 = slt i32 %a, %b", "ret %0"],
+            ),
+            (
+                "%0 = mul i32 %a, 1\n%1 = add i32 %0, 0\n%r = sub i32 %1, %a",
+                vec!["ret 0"],
+            ),
+        ];
+        for (code, expected) in cases {
+            let expected: Vec<_> = expected.into_iter().map(parse).collect();
+            assert_eq!(combine(code), expected, "{code}");
+        }
+    }
+
+    #[test]
+    fn flip_branch() {
+        let function = ir::function::parse(
+            "fn f(i32 %a, i32 %b) -> i32 {
+  f_entry:
+    %0 = sgt i32 %a, %b
+    beq %0, 0, bb1, bb2
+  bb1:
+    ret %a
+  bb2:
+    ret %b
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        InstructionCombine.run(&mut editor);
+        assert_eq!(
+            editor.content.content[0].content[1],
+            statement::parse("bge %b, %a, bb1, bb2").unwrap().1
+        );
+    }
+}
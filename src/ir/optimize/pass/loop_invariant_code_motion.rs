@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+
+use super::{
+    aggressive_dead_code_elimination::remove_unreachable_blocks,
+    remove_unused_register::RemoveUnusedRegister, simplify_cfg::retarget,
+    topological_sort::TopologicalSort, IsPass,
+};
+use crate::ir::{
+    editor::{
+        analyzer::{register_usage::RegisterDefinePosition, BindedControlFlowGraph, BindedScc},
+        Editor,
+    },
+    function::FunctionDefinitionIndex,
+    quantity::Quantity,
+    statement::{
+        calculate::binary::BinaryOperation, phi::PhiSource, BinaryCalculate, IRStatement,
+        IsIRStatement, Jump, Phi,
+    },
+    RegisterName,
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+/// [`LoopInvariantCodeMotion`] moves calculations whose results don't change between iterations
+/// out of loops.
+///
+/// Each natural loop gets a preheader, ie. a block which is the only way to enter the loop and
+/// only jumps to the loop header. Side-effect-free calculations whose operands are defined
+/// outside the loop, and loads from stack variables which are not stored to in the loop, are
+/// moved into the preheader. Divisions are only moved if they cannot trap and they are executed
+/// before the loop is left.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LoopInvariantCodeMotion;
+
+/// A natural loop, the first element is the index of the header block, and the second element is
+/// indexes of all blocks in the loop.
+type Loop = (usize, Vec<usize>);
+
+fn collect_loops(scc: &BindedScc, loops: &mut Vec<Loop>) {
+    let Some(sub_sccs) = scc.top_level_sccs() else {
+        return;
+    };
+    for sub_scc in sub_sccs {
+        if sub_scc.is_trivial() || !sub_scc.reduciable() {
+            continue;
+        }
+        collect_loops(&sub_scc, loops);
+        let header = sub_scc.entry_nodes()[0];
+        let nodes = sub_scc
+            .graph_part
+            .nodes
+            .iter()
+            .map(|it| it.index())
+            .sorted()
+            .collect();
+        // a loop with several back edges may also be found as a smaller loop with the same
+        // header, we want the whole loop
+        if let Some(existing) = loops.iter_mut().find(|(it, _)| *it == header) {
+            existing.1 = nodes;
+        } else {
+            loops.push((header, nodes));
+        }
+    }
+}
+
+/// All natural loops in the function, inner loops come before the outer ones.
+fn natural_loops(control_flow_graph: &BindedControlFlowGraph) -> Vec<Loop> {
+    let mut loops = Vec::new();
+    collect_loops(&control_flow_graph.top_level_scc(), &mut loops);
+    loops
+}
+
+/// Find the preheader of the loop, ie. the only block outside the loop which goes to the header,
+/// and it goes nowhere else.
+fn preheader_of(editor: &Editor, (header, nodes): &Loop) -> Option<usize> {
+    let analyzer = editor.binded_analyzer();
+    let control_flow_graph = analyzer.control_flow_graph();
+    let outside_predecessors: HashSet<_> = control_flow_graph
+        .predecessor(*header)
+        .into_iter()
+        .filter(|it| !nodes.contains(it))
+        .collect();
+    let &predecessor = outside_predecessors.iter().exactly_one().ok()?;
+    matches!(
+        editor.content[predecessor].content.last(),
+        Some(IRStatement::Jump(_))
+    )
+    .then_some(predecessor)
+}
+
+/// Insert a preheader block before the loop header, and let all blocks outside the loop which
+/// go to the header go to the preheader instead.
+fn insert_preheader(editor: &mut Editor, (header, nodes): &Loop) {
+    let header = *header;
+    let analyzer = editor.binded_analyzer();
+    let control_flow_graph = analyzer.control_flow_graph();
+    let header_name = control_flow_graph
+        .basic_block_name_by_index(header)
+        .to_string();
+    let mut outside_predecessors: Vec<_> = control_flow_graph
+        .predecessor(header)
+        .into_iter()
+        .filter(|it| !nodes.contains(it))
+        .collect();
+    outside_predecessors.sort();
+    outside_predecessors.dedup();
+    let outside_predecessor_names: Vec<_> = outside_predecessors
+        .iter()
+        .map(|&it| control_flow_graph.basic_block_name_by_index(it).to_string())
+        .collect();
+    let preheader_name = format!("{header_name}_preheader");
+    // phi sources from outside the loop are merged in the preheader
+    let mut phis_in_preheader = Vec::new();
+    let mut phis_in_header = Vec::new();
+    for (statement_index, statement) in editor.content[header].content.iter().enumerate() {
+        let IRStatement::Phi(phi) = statement else {
+            continue;
+        };
+        let (outside, mut inside): (Vec<_>, Vec<_>) = phi
+            .from
+            .iter()
+            .cloned()
+            .partition(|it| outside_predecessor_names.contains(&it.block));
+        if outside.is_empty() {
+            continue;
+        }
+        let value = if outside.iter().map(|it| &it.value).all_equal() {
+            outside[0].value.clone()
+        } else {
+            let to = RegisterName(format!("{}_{preheader_name}", phi.to.0));
+            phis_in_preheader.push(Phi {
+                to: to.clone(),
+                data_type: phi.data_type.clone(),
+                from: outside,
+            });
+            to.into()
+        };
+        inside.push(PhiSource {
+            value,
+            block: preheader_name.clone(),
+        });
+        phis_in_header.push((
+            statement_index,
+            Phi {
+                from: inside,
+                ..phi.clone()
+            },
+        ));
+    }
+    // the preheader takes the place of the header, blocks from the header on move back by one
+    let preheader = header;
+    let moved = |index: usize| if index >= preheader { index + 1 } else { index };
+    editor.insert_basic_block(preheader_name.clone(), preheader);
+    for phi in phis_in_preheader {
+        editor.push_back_statement(preheader, phi);
+    }
+    editor.push_back_statement(
+        preheader,
+        Jump {
+            label: header_name.clone(),
+        },
+    );
+    for (statement_index, phi) in phis_in_header {
+        editor.replace_statement((moved(header), statement_index), phi);
+    }
+    for predecessor in outside_predecessors {
+        retarget(editor, moved(predecessor), &header_name, &preheader_name);
+    }
+}
+
+/// Move loop invariant statements in the loop into its preheader.
+fn hoist(editor: &mut Editor, preheader: usize, (header, nodes): &Loop) {
+    let analyzer = editor.binded_analyzer();
+    let control_flow_graph = analyzer.control_flow_graph();
+    let register_usage = analyzer.register_usage();
+    let memory_usage = analyzer.memory_usage();
+    let not_stored_in_loop: HashSet<_> = memory_usage
+        .memory_access_variables()
+        .filter(|it| {
+            memory_usage
+                .memory_access_info(it)
+                .store
+                .iter()
+                .all(|store| !nodes.contains(&store.0))
+        })
+        .collect();
+    // blocks in the loop which may jump out of it
+    let exits: Vec<_> = nodes
+        .iter()
+        .copied()
+        .filter(|&it| {
+            control_flow_graph
+                .successors(it)
+                .iter()
+                .any(|successor| !nodes.contains(successor))
+        })
+        .collect();
+    let mut hoisted: Vec<FunctionDefinitionIndex> = Vec::new();
+    let mut hoisted_registers = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for &block_index in nodes {
+            for (statement_index, statement) in
+                editor.content[block_index].content.iter().enumerate()
+            {
+                let index: FunctionDefinitionIndex = (block_index, statement_index).into();
+                let can_move = match statement {
+                    IRStatement::BinaryCalculate(BinaryCalculate {
+                        operation:
+                            BinaryOperation::SignedDiv
+                            | BinaryOperation::UnsignedDiv
+                            | BinaryOperation::SignedRem
+                            | BinaryOperation::UnsignedRem,
+                        operand2,
+                        ..
+                    }) => {
+                        matches!(operand2, Quantity::NumberLiteral(divisor) if *divisor != 0)
+                            && exits
+                                .iter()
+                                .all(|&exit| control_flow_graph.is_dominated_by(exit, block_index))
+                    }
+                    IRStatement::BinaryCalculate(_)
+                    | IRStatement::UnaryCalculate(_)
+                    | IRStatement::LoadField(_)
+                    | IRStatement::ElementAddress(_)
+                    | IRStatement::FieldAddress(_) => true,
+                    IRStatement::Load(load) => match &load.from {
+                        Quantity::RegisterName(from) => not_stored_in_loop.contains(from),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                if !can_move || hoisted.contains(&index) {
+                    continue;
+                }
+                let invariant = statement.use_register().iter().all(|register| {
+                    hoisted_registers.contains(register)
+                        || match register_usage.get(register).define_position() {
+                            RegisterDefinePosition::Parameter(_) => true,
+                            RegisterDefinePosition::Body(define_at) => {
+                                !nodes.contains(&define_at.0)
+                                    && control_flow_graph.is_dominated_by(*header, define_at.0)
+                            }
+                        }
+                });
+                if invariant {
+                    hoisted_registers.insert(statement.generate_register().unwrap().0);
+                    hoisted.push(index);
+                    changed = true;
+                }
+            }
+        }
+    }
+    let statements: Vec<_> = hoisted
+        .iter()
+        .map(|it| editor.content[it.clone()].clone())
+        .collect();
+    editor.remove_statements(hoisted);
+    for statement in statements {
+        let terminator_index = editor.content[preheader].content.len() - 1;
+        editor.insert_statement((preheader, terminator_index), statement);
+    }
+}
+
+impl IsPass for LoopInvariantCodeMotion {
+    fn run(&self, editor: &mut Editor) {
+        // node indexes in the graph don't match block indexes if some blocks are unreachable
+        remove_unreachable_blocks(editor);
+        let mut done = Vec::new();
+        loop {
+            let analyzer = editor.binded_analyzer();
+            let control_flow_graph = analyzer.control_flow_graph();
+            let Some(current) = natural_loops(&control_flow_graph)
+                .into_iter()
+                .find(|(header, _)| !done.contains(header))
+            else {
+                return;
+            };
+            if let Some(preheader) = preheader_of(editor, &current) {
+                hoist(editor, preheader, &current);
+                done.push(current.0);
+            } else {
+                insert_preheader(editor, &current);
+                // block indexes changed, find the loop again
+                done.iter_mut()
+                    .filter(|it| **it >= current.0)
+                    .for_each(|it| *it += 1);
+            }
+        }
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        Vec::new()
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        vec![TopologicalSort.into(), RemoveUnusedRegister.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement, FunctionDefinition};
+
+    fn licm(code: &str) -> FunctionDefinition {
+        let function = ir::function::parse(code).unwrap().1;
+        let mut editor = Editor::new(function);
+        LoopInvariantCodeMotion.run(&mut editor);
+        editor.content
+    }
+
+    #[test]
+    fn hoist_to_existing_preheader() {
+        let result = licm(
+            "fn f(i32 %a, i32 %n) -> i32 {
+  bb0:
+    j bb1
+  bb1:
+    %i = phi i32 [0, bb0], [%i1, bb2]
+    blt %i, %n, bb2, bb3
+  bb2:
+    %0 = mul i32 %a, 4
+    %1 = add i32 %0, 1
+    %i1 = add i32 %i, %1
+    j bb1
+  bb3:
+    ret %i
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(result.content.len(), 4);
+        assert_eq!(
+            result.content[0].content,
+            vec![
+                parse("%0 = mul i32 %a, 4"),
+                parse("%1 = add i32 %0, 1"),
+                parse("j bb1"),
+            ]
+        );
+        assert_eq!(
+            result.content[2].content,
+            vec![parse("%i1 = add i32 %i, %1"), parse("j bb1")]
+        );
+    }
+
+    #[test]
+    fn create_preheader() {
+        let result = licm(
+            "fn f(i32 %a, i32 %n) -> i32 {
+  bb0:
+    %x_addr = alloca i32
+    store i32 %a, address %x_addr
+    %y_addr = alloca i32
+    store i32 %a, address %y_addr
+    blt %a, 0, bb1, bb3
+  bb1:
+    %i = phi i32 [0, bb0], [%i1, bb2]
+    blt %i, %n, bb2, bb3
+  bb2:
+    %x = load i32 %x_addr
+    %y = load i32 %y_addr
+    %i1 = add i32 %x, %y
+    store i32 %i1, address %y_addr
+    j bb1
+  bb3:
+    ret %a
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(result.content.len(), 5);
+        assert_eq!(
+            result.content[0].content[4],
+            parse("blt %a, 0, bb1_preheader, bb3")
+        );
+        assert_eq!(result.content[1].name.as_deref(), Some("bb1_preheader"));
+        // `%y_addr` is stored in the loop, so it cannot be loaded outside
+        assert_eq!(
+            result.content[1].content,
+            vec![parse("%x = load i32 %x_addr"), parse("j bb1")]
+        );
+        assert_eq!(
+            result.content[2].content[0],
+            parse("%i = phi i32 [%i1, bb2], [0, bb1_preheader]")
+        );
+        assert_eq!(result.content[3].content.len(), 4);
+    }
+
+    #[test]
+    fn hoist_division() {
+        let result = licm(
+            "fn f(i32 %a, i32 %n) -> i32 {
+  bb0:
+    j bb1
+  bb1:
+    %i = phi i32 [0, bb0], [%i1, bb2]
+    %0 = sdiv i32 %a, 3
+    %1 = sdiv i32 %a, %n
+    blt %i, %n, bb2, bb3
+  bb2:
+    %2 = srem i32 %a, 5
+    %3 = add i32 %0, %1
+    %4 = add i32 %3, %2
+    %i1 = add i32 %i, %4
+    j bb1
+  bb3:
+    ret %i
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        // `%1` may divide by zero, and `%2` is not executed if the loop is left from `bb1`
+        // right away, so only `%0` is hoisted
+        assert_eq!(
+            result.content[0].content,
+            vec![parse("%0 = sdiv i32 %a, 3"), parse("j bb1")]
+        );
+    }
+
+    #[test]
+    fn skip_unreachable_blocks() {
+        let result = licm(
+            "fn f(i32 %a, i32 %n) -> i32 {
+  bb0:
+    j bb1
+  bb1:
+    %i = phi i32 [0, bb0], [%i1, bb2], [0, bb4]
+    blt %i, %n, bb2, bb3
+  bb2:
+    %0 = mul i32 %a, 4
+    %i1 = add i32 %i, %0
+    j bb1
+  bb3:
+    ret %i
+  bb4:
+    j bb1
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(result.content.len(), 4);
+        assert_eq!(
+            result.content[0].content,
+            vec![parse("%0 = mul i32 %a, 4"), parse("j bb1")]
+        );
+        assert_eq!(
+            result.content[1].content[0],
+            parse("%i = phi i32 [0, bb0], [%i1, bb2]")
+        );
+    }
+}
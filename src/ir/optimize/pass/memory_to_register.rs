@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     ir::{
@@ -24,28 +24,32 @@ pub struct MemoryToRegister;
 
 impl IsPass for MemoryToRegister {
     fn run(&self, editor: &mut Editor) {
-        let insert_phis_at = insert_phi_positions(
+        let variables = promotable_variables(&editor.binded_analyzer().memory_usage());
+        let mut insert_phis_at = insert_phi_positions(
             &editor.binded_analyzer().memory_usage(),
             &editor.binded_analyzer().control_flow_graph(),
         );
+        insert_phis_at
+            .retain(|(variable_name, _)| variables.contains(&RegisterName(variable_name.clone())));
         // There exists two parts of actions:
         // - The first part will remove the load and store statements, and replace the load targets with the "phi"ed results
         // - The second part will insert the phi nodes
         let (to_renames, to_removes, subnodes) = decide_values(
             &editor.content,
             &editor.binded_analyzer().control_flow_graph(),
+            &variables,
             &insert_phis_at,
         );
         editor.remove_statements(to_removes);
-        for (from, to) in to_renames {
-            editor.rename_local(from, to);
-        }
         // let insert_phi_actions = create_phi_node_insertion_actions(
         //     subnodes,
         //     &analyzer.memory_usage.memory_access_variables_and_types(),
         //     analyzer.content,
         // );
         insert_phi_nodes(subnodes, editor);
+        // phi sources may be loaded values too, so rename after the phis are inserted;
+        // a loaded value may be stored into another variable and loaded again
+        editor.rename_locals(to_renames);
     }
 
     fn need(&self) -> Vec<super::Pass> {
@@ -57,6 +61,27 @@ impl IsPass for MemoryToRegister {
     }
 }
 
+/// Variables which can be promoted to registers.
+///
+/// A variable may be loaded before anything is stored into it, and the value loaded is undefined.
+/// We use `0` for such values, but there is no constant for structs, so struct variables are only
+/// promoted if they are always stored before loaded, ie. the first access to it is a store in the
+/// entry block.
+fn promotable_variables(memory_usage: &analyzer::BindedMemoryUsage) -> HashSet<RegisterName> {
+    let types = memory_usage.memory_access_variables_and_types();
+    memory_usage
+        .memory_access_variables()
+        .filter(|variable| {
+            let info = memory_usage.memory_access_info(variable);
+            let stored_before_loaded = info.store.first().is_some_and(|store| {
+                store.0 == 0 && info.load.first().map_or(true, |load| load > store)
+            });
+            matches!(types[*variable], Type::Integer(_) | Type::Address) || stored_before_loaded
+        })
+        .cloned()
+        .collect()
+}
+
 /// Find out where should we insert phi positions.
 /// Return a vector which contains (VariableName, BasicBlockIndex)
 fn insert_phi_positions(
@@ -106,7 +131,8 @@ fn decide_variable_value(
             return value.clone();
         }
     }
-    unreachable!()
+    // the variable is loaded before anything is stored into it
+    (0, 0.into())
 }
 
 // We need to know all incoming "arrows" to a phi node before we can construct it.
@@ -125,10 +151,14 @@ type DecideValueResult = (
 );
 
 /// Returns (Actions to edit the statements, PhiSubNodes to insert)
+/// `predecessor_block_index` is the block the control flow comes from, phis use it as the source.
+#[allow(clippy::too_many_arguments)]
 fn decide_values_start_from(
     function: &FunctionDefinition,
     control_flow_graph: &analyzer::BindedControlFlowGraph,
+    variables: &HashSet<RegisterName>,
     consider_block_index: usize,
+    predecessor_block_index: usize,
     inserted_phi: &[(String, usize)],
     visited: &mut Vec<usize>,
     current_variable_value: &mut Vec<HashMap<String, (usize, Quantity)>>,
@@ -142,12 +172,11 @@ fn decide_values_start_from(
         .filter(|(_, bb_id)| bb_id == &consider_block_index)
         .map(|(variable_name, _)| variable_name);
     for variable_name in phied_variables {
-        let (from_basic_block_index, value) =
-            decide_variable_value(variable_name, current_variable_value);
+        let (_, value) = decide_variable_value(variable_name, current_variable_value);
         subnodes.push(PhiSubNode {
             basic_block_index: consider_block_index,
             variable_name: variable_name.clone(),
-            value_from: from_basic_block_index,
+            value_from: predecessor_block_index,
             value,
         });
         current_variable_value.last_mut().unwrap().insert(
@@ -168,7 +197,7 @@ fn decide_values_start_from(
                 to,
                 from: Quantity::RegisterName(local),
                 ..
-            }) => {
+            }) if variables.contains(local) => {
                 let (_, replace_with_value) =
                     decide_variable_value(&local.0, current_variable_value);
                 to_remove.push((consider_block_index, statement_index));
@@ -178,7 +207,7 @@ fn decide_values_start_from(
                 source,
                 target: Quantity::RegisterName(local),
                 ..
-            }) => {
+            }) if variables.contains(local) => {
                 current_variable_value
                     .last_mut()
                     .unwrap()
@@ -193,7 +222,9 @@ fn decide_values_start_from(
                     decide_values_start_from(
                         function,
                         control_flow_graph,
+                        variables,
                         success_block,
+                        consider_block_index,
                         inserted_phi,
                         visited,
                         current_variable_value,
@@ -209,7 +240,9 @@ fn decide_values_start_from(
                     decide_values_start_from(
                         function,
                         control_flow_graph,
+                        variables,
                         failure_block,
+                        consider_block_index,
                         inserted_phi,
                         visited,
                         current_variable_value,
@@ -225,7 +258,9 @@ fn decide_values_start_from(
                     decide_values_start_from(
                         function,
                         control_flow_graph,
+                        variables,
                         jump_to_block,
+                        consider_block_index,
                         inserted_phi,
                         visited,
                         current_variable_value,
@@ -243,6 +278,7 @@ fn decide_values_start_from(
 fn decide_values(
     function: &FunctionDefinition,
     control_flow_graph: &analyzer::BindedControlFlowGraph,
+    variables: &HashSet<RegisterName>,
     inserted_phi: &[(String, usize)],
 ) -> DecideValueResult {
     let mut visited = Vec::new();
@@ -250,6 +286,8 @@ fn decide_values(
     decide_values_start_from(
         function,
         control_flow_graph,
+        variables,
+        0,
         0,
         inserted_phi,
         &mut visited,
@@ -831,4 +869,87 @@ mod tests {
             block: "bb7".to_string()
         }));
     }
+
+    #[test]
+    fn phi_source_is_predecessor() {
+        let function_definition = FunctionDefinition {
+            header: ir::FunctionHeader {
+                name: "f".to_string(),
+                parameters: Vec::new(),
+                return_type: data_type::I32.clone(),
+            },
+            content: vec![
+                BasicBlock {
+                    name: Some("f_entry".to_string()),
+                    content: vec![alloca("c"), store("c"), branch("bb1", "bb2")],
+                },
+                BasicBlock {
+                    name: Some("bb1".to_string()),
+                    content: vec![store_with_reg("c", "t_0"), jump("bb3")],
+                },
+                // `c` is not stored here, but the value still comes from this block
+                BasicBlock {
+                    name: Some("bb2".to_string()),
+                    content: vec![jump("bb3")],
+                },
+                BasicBlock {
+                    name: Some("bb3".to_string()),
+                    content: vec![
+                        load("c", 0),
+                        Ret {
+                            value: Some(RegisterName("c_0".to_string()).into()),
+                        }
+                        .into(),
+                    ],
+                },
+            ],
+        };
+
+        let mut editor = Editor::new(function_definition);
+        MemoryToRegister.run(&mut editor);
+
+        let the_phi_statement = editor.content[3].content[0].as_phi();
+        let mut source_blocks: Vec<_> = the_phi_statement
+            .from
+            .iter()
+            .map(|it| it.block.as_str())
+            .collect();
+        source_blocks.sort();
+        assert_eq!(source_blocks, vec!["bb1", "bb2"]);
+    }
+
+    #[test]
+    fn load_before_store() {
+        let function_definition = ir::function::parse(
+            "fn f(i32 %a) -> i32 {
+  f_entry:
+    %x_addr = alloca i32
+    %foo_addr = alloca Foo
+    blt %a, 0, bb1, bb2
+  bb1:
+    store i32 %a, address %x_addr
+    j bb2
+  bb2:
+    %0 = load i32 %x_addr
+    %1 = load Foo %foo_addr
+    %2 = load_field i32 %1.[Foo.0]
+    %3 = add i32 %0, %2
+    ret %3
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function_definition);
+        MemoryToRegister.run(&mut editor);
+        // the struct variable is never stored, so it is kept in memory
+        assert_eq!(
+            editor.content[2].content[1],
+            ir::statement::parse("%1 = load Foo %foo_addr").unwrap().1
+        );
+        let the_phi_statement = editor.content[2].content[0].as_phi();
+        assert!(the_phi_statement
+            .from
+            .iter()
+            .any(|it| it.block == "f_entry" && it.value == 0.into()));
+    }
 }
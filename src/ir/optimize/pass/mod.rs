@@ -1,18 +1,30 @@
+mod aggressive_dead_code_elimination;
 mod fix_irreducible;
+mod global_value_numbering;
+mod instruction_combine;
+mod loop_invariant_code_motion;
 mod memory_to_register;
 mod remove_load_directly_after_store;
 mod remove_only_once_store;
 mod remove_unused_register;
+mod simplify_cfg;
+mod sparse_conditional_constant_propagation;
 mod topological_sort;
 use crate::ir::editor::Editor;
+use aggressive_dead_code_elimination::AggressiveDeadCodeElimination;
 use enum_dispatch::enum_dispatch;
 pub use fix_irreducible::FixIrreducible;
+use global_value_numbering::GlobalValueNumbering;
+use instruction_combine::InstructionCombine;
+use loop_invariant_code_motion::LoopInvariantCodeMotion;
 use memory_to_register::MemoryToRegister;
 use remove_load_directly_after_store::RemoveLoadDirectlyAfterStore;
 use remove_only_once_store::RemoveOnlyOnceStore;
 use remove_unused_register::RemoveUnusedRegister;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use simplify_cfg::SimplifyCFG;
+use sparse_conditional_constant_propagation::SparseConditionalConstantPropagation;
+use std::{fmt, str::FromStr};
 pub use topological_sort::TopologicalSort;
 /// This trait should be implemented by all passes which can do optimizing on ir function.
 #[enum_dispatch]
@@ -36,6 +48,12 @@ pub enum Pass {
     MemoryToRegister,
     FixIrreducible,
     TopologicalSort,
+    SparseConditionalConstantPropagation,
+    GlobalValueNumbering,
+    AggressiveDeadCodeElimination,
+    SimplifyCFG,
+    LoopInvariantCodeMotion,
+    InstructionCombine,
 }
 
 impl FromStr for Pass {
@@ -51,11 +69,41 @@ impl FromStr for Pass {
             "MemoryToRegister" => Ok(Self::MemoryToRegister(MemoryToRegister)),
             "FixIrreducible" => Ok(Self::FixIrreducible(FixIrreducible)),
             "TopologicalSort" => Ok(Self::TopologicalSort(TopologicalSort)),
+            "SparseConditionalConstantPropagation" => Ok(
+                Self::SparseConditionalConstantPropagation(SparseConditionalConstantPropagation),
+            ),
+            "GlobalValueNumbering" => Ok(Self::GlobalValueNumbering(GlobalValueNumbering)),
+            "AggressiveDeadCodeElimination" => Ok(Self::AggressiveDeadCodeElimination(
+                AggressiveDeadCodeElimination,
+            )),
+            "SimplifyCFG" => Ok(Self::SimplifyCFG(SimplifyCFG)),
+            "LoopInvariantCodeMotion" => Ok(Self::LoopInvariantCodeMotion(LoopInvariantCodeMotion)),
+            "InstructionCombine" => Ok(Self::InstructionCombine(InstructionCombine)),
             _ => Err(()),
         }
     }
 }
 
+impl fmt::Display for Pass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Pass::RemoveUnusedRegister(_) => "RemoveUnusedRegister",
+            Pass::RemoveOnlyOnceStore(_) => "RemoveOnlyOnceStore",
+            Pass::RemoveLoadDirectlyAfterStore(_) => "RemoveLoadDirectlyAfterStore",
+            Pass::MemoryToRegister(_) => "MemoryToRegister",
+            Pass::FixIrreducible(_) => "FixIrreducible",
+            Pass::TopologicalSort(_) => "TopologicalSort",
+            Pass::SparseConditionalConstantPropagation(_) => "SparseConditionalConstantPropagation",
+            Pass::GlobalValueNumbering(_) => "GlobalValueNumbering",
+            Pass::AggressiveDeadCodeElimination(_) => "AggressiveDeadCodeElimination",
+            Pass::SimplifyCFG(_) => "SimplifyCFG",
+            Pass::LoopInvariantCodeMotion(_) => "LoopInvariantCodeMotion",
+            Pass::InstructionCombine(_) => "InstructionCombine",
+        };
+        write!(f, "{name}")
+    }
+}
+
 impl From<&str> for Pass {
     fn from(s: &str) -> Self {
         Self::from_str(s).unwrap()
@@ -44,9 +44,8 @@ impl IsPass for RemoveOnlyOnceStore {
             }
         }
         editor.remove_statements(to_remove);
-        for (from, to) in to_rename {
-            editor.rename_local(from, to);
-        }
+        // a loaded value may be stored into another variable and loaded again
+        editor.rename_locals(to_rename);
     }
 }
 
@@ -208,4 +207,27 @@ mod tests {
         assert!(registers.contains(&RegisterName("6".to_string())));
         assert!(registers.contains(&RegisterName("7".to_string())));
     }
+
+    #[test]
+    fn run_on_value_stored_twice() {
+        // `%0` is renamed to `1`, and `%1` is renamed to `%0`, which must end up as `1` too
+        let function = ir::function::parse(
+            "fn f() -> i32 {
+  f_entry:
+    %a_addr = alloca i32
+    %b_addr = alloca i32
+    store i32 1, address %a_addr
+    %0 = load i32 %a_addr
+    store i32 %0, address %b_addr
+    %1 = load i32 %b_addr
+    ret %1
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        RemoveOnlyOnceStore.run(&mut editor);
+        let ret = editor.content.content[0].content.last().unwrap().as_ret();
+        assert_eq!(ret.value, Some(ir::quantity::Quantity::NumberLiteral(1)));
+    }
 }
@@ -0,0 +1,311 @@
+use super::{
+    remove_unused_register::RemoveUnusedRegister, topological_sort::TopologicalSort, IsPass,
+};
+use crate::ir::{
+    editor::Editor,
+    function::basic_block::BasicBlock,
+    statement::{phi::PhiSource, Branch, IRStatement, Jump},
+    FunctionDefinition,
+};
+use serde::{Deserialize, Serialize};
+
+/// [`SimplifyCFG`] cleans up the control flow graph:
+/// - a `Branch` whose targets are the same becomes a `Jump`
+/// - a block which only contains a `Jump` is skipped by its predecessors and removed
+/// - a block is merged into its predecessor, if it's the only successor of the predecessor,
+///   and the predecessor is its only predecessor
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SimplifyCFG;
+
+fn successors(block: &BasicBlock) -> Vec<&str> {
+    match block.content.last() {
+        Some(IRStatement::Jump(jump)) => vec![&jump.label],
+        Some(IRStatement::Branch(branch)) if branch.success_label == branch.failure_label => {
+            vec![&branch.success_label]
+        }
+        Some(IRStatement::Branch(branch)) => vec![&branch.success_label, &branch.failure_label],
+        _ => Vec::new(),
+    }
+}
+
+/// Indexes of the blocks which jump to `block_name`.
+fn predecessors(function: &FunctionDefinition, block_name: &str) -> Vec<usize> {
+    function
+        .content
+        .iter()
+        .enumerate()
+        .filter(|(_, block)| successors(block).contains(&block_name))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn block_index(function: &FunctionDefinition, block_name: &str) -> usize {
+    function
+        .content
+        .iter()
+        .position(|it| it.name.as_deref() == Some(block_name))
+        .unwrap()
+}
+
+fn name_of(function: &FunctionDefinition, block_index: usize) -> String {
+    function.content[block_index].name.clone().unwrap()
+}
+
+/// Make the terminator of the block indexed by `block_index` go to `to` instead of `from`.
+pub(super) fn retarget(editor: &mut Editor, block_index: usize, from: &str, to: &str) {
+    let block = &editor.content.content[block_index];
+    let terminator_index = block.content.len() - 1;
+    let replace = |label: &String| {
+        if label == from {
+            to.to_string()
+        } else {
+            label.clone()
+        }
+    };
+    let terminator: IRStatement = match &block.content[terminator_index] {
+        IRStatement::Jump(jump) => Jump {
+            label: replace(&jump.label),
+        }
+        .into(),
+        IRStatement::Branch(branch) => Branch {
+            success_label: replace(&branch.success_label),
+            failure_label: replace(&branch.failure_label),
+            ..branch.clone()
+        }
+        .into(),
+        _ => unreachable!(),
+    };
+    editor.replace_statement((block_index, terminator_index), terminator);
+}
+
+/// Replace the phi sources from `from` in the block indexed by `block_index` with sources from
+/// each of `to`, which have the same value.
+fn replace_phi_sources(editor: &mut Editor, block_index: usize, from: &str, to: &[String]) {
+    let block = &editor.content.content[block_index];
+    let mut to_replace = Vec::new();
+    for (statement_index, statement) in block.content.iter().enumerate() {
+        if let IRStatement::Phi(phi) = statement
+            && let Some(position) = phi.from.iter().position(|it| it.block == from)
+        {
+            let mut phi = phi.clone();
+            let value = phi.from.remove(position).value;
+            for block in to {
+                phi.from.push(PhiSource {
+                    value: value.clone(),
+                    block: block.clone(),
+                });
+            }
+            to_replace.push((statement_index, phi));
+        }
+    }
+    for (statement_index, phi) in to_replace {
+        editor.replace_statement((block_index, statement_index), phi);
+    }
+}
+
+/// Turn a `Branch` with identical targets into a `Jump`.
+fn fold_branch(editor: &mut Editor) -> bool {
+    for (block_index, block) in editor.content.content.iter().enumerate() {
+        if let Some(IRStatement::Branch(branch)) = block.content.last()
+            && branch.success_label == branch.failure_label
+        {
+            let jump = Jump {
+                label: branch.success_label.clone(),
+            };
+            editor.replace_statement((block_index, block.content.len() - 1), jump);
+            return true;
+        }
+    }
+    false
+}
+
+/// Let the predecessors of a block which only contains a `Jump` jump to its target directly.
+fn thread_jump(editor: &mut Editor) -> bool {
+    let function = &editor.content;
+    for (empty_index, block) in function.content.iter().enumerate().skip(1) {
+        let [IRStatement::Jump(Jump { label: target })] = &block.content[..] else {
+            continue;
+        };
+        let empty_name = name_of(function, empty_index);
+        let target_index = block_index(function, target);
+        if target_index == empty_index {
+            continue;
+        }
+        let predecessors = predecessors(function, &empty_name);
+        let target_has_phi = matches!(
+            function.content[target_index].content.first(),
+            Some(IRStatement::Phi(_))
+        );
+        // phis are lowered by setting the values at the end of the predecessors, so a
+        // predecessor which branches must not go to a block with phis directly
+        if target_has_phi
+            && predecessors.iter().any(|&it| {
+                matches!(
+                    function.content[it].content.last(),
+                    Some(IRStatement::Branch(_))
+                )
+            })
+        {
+            continue;
+        }
+        let target = target.clone();
+        let predecessor_names: Vec<_> = predecessors
+            .iter()
+            .map(|&it| name_of(function, it))
+            .collect();
+        for &predecessor in &predecessors {
+            retarget(editor, predecessor, &empty_name, &target);
+        }
+        replace_phi_sources(editor, target_index, &empty_name, &predecessor_names);
+        editor.remove_basic_block(empty_index);
+        return true;
+    }
+    false
+}
+
+/// Merge a block into its only predecessor, if the block is also the only successor of it.
+fn merge_block(editor: &mut Editor) -> bool {
+    let function = &editor.content;
+    for (block_index, block) in function.content.iter().enumerate().skip(1) {
+        let block_name = name_of(function, block_index);
+        let [predecessor] = predecessors(function, &block_name)[..] else {
+            continue;
+        };
+        if predecessor == block_index || successors(&function.content[predecessor]).len() != 1 {
+            continue;
+        }
+        let block = block.clone();
+        let predecessor_name = name_of(function, predecessor);
+        let successors: Vec<_> = successors(&block)
+            .into_iter()
+            .map(|it| self::block_index(function, it))
+            .collect();
+        // the terminator of the predecessor
+        editor.remove_statement((
+            predecessor,
+            editor.content.content[predecessor].content.len() - 1,
+        ));
+        let mut renames = Vec::new();
+        for statement in block.content {
+            match statement {
+                // the block has only one predecessor, so the phi has only one source
+                IRStatement::Phi(phi) => renames.push((phi.to, phi.from[0].value.clone())),
+                statement => editor.push_back_statement(predecessor, statement),
+            }
+        }
+        for successor in successors {
+            replace_phi_sources(editor, successor, &block_name, &[predecessor_name.clone()]);
+        }
+        editor.remove_basic_block(block_index);
+        for (from, to) in renames {
+            editor.rename_local(from, to);
+        }
+        return true;
+    }
+    false
+}
+
+impl IsPass for SimplifyCFG {
+    fn run(&self, editor: &mut Editor) {
+        while fold_branch(editor) || thread_jump(editor) || merge_block(editor) {}
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        Vec::new()
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        vec![TopologicalSort.into(), RemoveUnusedRegister.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::{self, statement};
+
+    fn simplify(code: &str) -> FunctionDefinition {
+        let function = ir::function::parse(code).unwrap().1;
+        let mut editor = Editor::new(function);
+        SimplifyCFG.run(&mut editor);
+        editor.content
+    }
+
+    #[test]
+    fn merge_and_fold() {
+        let result = simplify(
+            "fn f(i32 %a) -> i32 {
+  bb0:
+    %0 = add i32 %a, 1
+    j bb1
+  bb1:
+    %1 = phi i32 [%0, bb0]
+    %2 = add i32 %1, 1
+    bne %2, 0, bb2, bb2
+  bb2:
+    ret %2
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(result.content.len(), 1);
+        assert_eq!(
+            result.content[0].content,
+            vec![
+                parse("%0 = add i32 %a, 1"),
+                parse("%2 = add i32 %0, 1"),
+                parse("ret %2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn thread_jumps() {
+        let result = simplify(
+            "fn f(i32 %a) -> i32 {
+  bb0:
+    blt %a, 0, bb1, bb2
+  bb1:
+    j bb3
+  bb2:
+    %0 = add i32 %a, 1
+    j bb3
+  bb3:
+    ret %a
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        assert_eq!(result.content.len(), 3);
+        assert_eq!(
+            result.content[0].content,
+            vec![parse("blt %a, 0, bb3, bb2")]
+        );
+    }
+
+    #[test]
+    fn thread_jumps_with_phi() {
+        let result = simplify(
+            "fn f(i32 %a) -> i32 {
+  bb0:
+    blt %a, 0, bb1, bb2
+  bb1:
+    %0 = add i32 %a, 1
+    j bb3
+  bb3:
+    j bb4
+  bb2:
+    j bb4
+  bb4:
+    %1 = phi i32 [%0, bb3], [2, bb2]
+    ret %1
+}",
+        );
+        let parse = |code| statement::parse(code).unwrap().1;
+        // bb2 is kept, or the edge from bb0 to bb4 would be critical
+        assert_eq!(result.content.len(), 4);
+        assert_eq!(result.content[1].content[1], parse("j bb4"));
+        assert_eq!(
+            result.content[3].content[0],
+            parse("%1 = phi i32 [2, bb2], [%0, bb1]")
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    memory_to_register::MemoryToRegister, remove_unused_register::RemoveUnusedRegister, IsPass,
+};
+use crate::{
+    ir::{
+        editor::Editor,
+        function::FunctionDefinitionIndex,
+        quantity::Quantity,
+        statement::{
+            branch::BranchType,
+            calculate::{binary::BinaryOperation, unary::UnaryOperation},
+            BinaryCalculate, Branch, IRStatement, IsIRStatement, Jump, Phi, UnaryCalculate,
+        },
+        FunctionDefinition, RegisterName,
+    },
+    utility::data_type::{Integer, Type},
+};
+use serde::{Deserialize, Serialize};
+
+/// [`SparseConditionalConstantPropagation`] finds registers which always hold the same constant,
+/// and replaces them with the constant.
+///
+/// Branches whose condition is known are turned into jumps, and phi sources from the edges which
+/// are never taken are removed.
+/// See [Constant propagation with conditional branches](https://dl.acm.org/doi/10.1145/103135.103136).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SparseConditionalConstantPropagation;
+
+/// What we know about the value of a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    /// No definition of the register has been executed yet.
+    Undefined,
+    Constant(i64),
+    /// The register may hold different values.
+    Overdefined,
+}
+
+impl Value {
+    fn meet(self, other: Self) -> Self {
+        match (self, other) {
+            (Value::Undefined, it) | (it, Value::Undefined) => it,
+            (Value::Constant(a), Value::Constant(b)) if a == b => Value::Constant(a),
+            _ => Value::Overdefined,
+        }
+    }
+}
+
+fn bit_width(data_type: &Type) -> Option<(bool, usize)> {
+    match data_type {
+        Type::Integer(Integer { signed, width }) => Some((*signed, *width)),
+        Type::Address => Some((false, 32)),
+        _ => None,
+    }
+}
+
+fn sign_extend(value: i64, width: usize) -> i64 {
+    if width >= 64 {
+        value
+    } else {
+        (value << (64 - width)) >> (64 - width)
+    }
+}
+
+fn zero_extend(value: i64, width: usize) -> u64 {
+    if width >= 64 {
+        value as u64
+    } else {
+        ((value as u64) << (64 - width)) >> (64 - width)
+    }
+}
+
+/// Truncate `value` into `data_type`, and extend it back according to the signedness.
+fn wrap(value: i64, data_type: &Type) -> Option<i64> {
+    let (signed, width) = bit_width(data_type)?;
+    if signed {
+        Some(sign_extend(value, width))
+    } else {
+        Some(zero_extend(value, width) as i64)
+    }
+}
+
+fn evaluate_binary(
+    operation: BinaryOperation,
+    operand1: i64,
+    operand2: i64,
+    data_type: &Type,
+) -> Option<i64> {
+    let (_, width) = bit_width(data_type)?;
+    let (a, b) = (wrap(operand1, data_type)?, wrap(operand2, data_type)?);
+    let shift_amount = (zero_extend(b, width) % width as u64) as u32;
+    let result = match operation {
+        BinaryOperation::Add => a.wrapping_add(b),
+        BinaryOperation::Sub => a.wrapping_sub(b),
+        BinaryOperation::Mul => a.wrapping_mul(b),
+        BinaryOperation::And => a & b,
+        BinaryOperation::Or => a | b,
+        BinaryOperation::Xor => a ^ b,
+        BinaryOperation::LogicalShiftLeft => a.wrapping_shl(shift_amount),
+        BinaryOperation::LogicalShiftRight => (zero_extend(a, width) >> shift_amount) as i64,
+        BinaryOperation::AthematicShiftRight => sign_extend(a, width) >> shift_amount,
+        BinaryOperation::LessThan => (sign_extend(a, width) < sign_extend(b, width)) as i64,
+        BinaryOperation::LessOrEqualThan => (sign_extend(a, width) <= sign_extend(b, width)) as i64,
+        BinaryOperation::GreaterThan => (sign_extend(a, width) > sign_extend(b, width)) as i64,
+        BinaryOperation::GreaterOrEqualThan => {
+            (sign_extend(a, width) >= sign_extend(b, width)) as i64
+        }
+        BinaryOperation::UnsignedLessThan => (zero_extend(a, width) < zero_extend(b, width)) as i64,
+        BinaryOperation::UnsignedLessOrEqualThan => {
+            (zero_extend(a, width) <= zero_extend(b, width)) as i64
+        }
+        BinaryOperation::UnsignedGreaterThan => {
+            (zero_extend(a, width) > zero_extend(b, width)) as i64
+        }
+        BinaryOperation::UnsignedGreaterOrEqualThan => {
+            (zero_extend(a, width) >= zero_extend(b, width)) as i64
+        }
+        BinaryOperation::Equal => (a == b) as i64,
+        BinaryOperation::NotEqual => (a != b) as i64,
+        // leave dividing by zero to the runtime
+        BinaryOperation::SignedDiv
+        | BinaryOperation::SignedRem
+        | BinaryOperation::UnsignedDiv
+        | BinaryOperation::UnsignedRem
+            if b == 0 =>
+        {
+            return None
+        }
+        BinaryOperation::SignedDiv => sign_extend(a, width).wrapping_div(sign_extend(b, width)),
+        BinaryOperation::SignedRem => sign_extend(a, width).wrapping_rem(sign_extend(b, width)),
+        BinaryOperation::UnsignedDiv => (zero_extend(a, width) / zero_extend(b, width)) as i64,
+        BinaryOperation::UnsignedRem => (zero_extend(a, width) % zero_extend(b, width)) as i64,
+    };
+    wrap(result, data_type)
+}
+
+fn evaluate_unary(operation: UnaryOperation, operand: i64, data_type: &Type) -> Option<i64> {
+    let operand = wrap(operand, data_type)?;
+    let result = match operation {
+        UnaryOperation::Neg => operand.wrapping_neg(),
+        UnaryOperation::Not => !operand,
+    };
+    wrap(result, data_type)
+}
+
+fn evaluate_branch(
+    branch_type: BranchType,
+    operand1: i64,
+    operand2: i64,
+    data_type: &Type,
+) -> bool {
+    let (_, width) = bit_width(data_type).unwrap_or((true, 64));
+    let (a, b) = (sign_extend(operand1, width), sign_extend(operand2, width));
+    match branch_type {
+        BranchType::EQ => a == b,
+        BranchType::NE => a != b,
+        BranchType::LT => a < b,
+        BranchType::GE => a >= b,
+        BranchType::LTU => zero_extend(a, width) < zero_extend(b, width),
+        BranchType::GEU => zero_extend(a, width) >= zero_extend(b, width),
+    }
+}
+
+/// Result of the analysis.
+struct Lattice {
+    values: HashMap<RegisterName, Value>,
+    /// Types of the registers, used to decide the width of the operands of a branch.
+    types: HashMap<RegisterName, Type>,
+    executable_blocks: HashSet<usize>,
+    /// `(from, to)` block index pairs.
+    executable_edges: HashSet<(usize, usize)>,
+}
+
+impl Lattice {
+    fn value_of(&self, quantity: &Quantity) -> Value {
+        match quantity {
+            Quantity::NumberLiteral(value) => Value::Constant(*value),
+            Quantity::RegisterName(register) => self
+                .values
+                .get(register)
+                .cloned()
+                .unwrap_or(Value::Undefined),
+            Quantity::GlobalVariableName(_) => Value::Overdefined,
+        }
+    }
+
+    /// Type of the operands of `branch`, literals are treated as `i32`.
+    fn branch_operand_type(&self, branch: &Branch) -> Type {
+        [&branch.operand1, &branch.operand2]
+            .into_iter()
+            .filter_map(|it| it.as_local())
+            .find_map(|it| self.types.get(it))
+            .cloned()
+            .unwrap_or(Type::Integer(Integer {
+                signed: true,
+                width: 32,
+            }))
+    }
+
+    /// Returns whether anything changed.
+    fn update(&mut self, register: RegisterName, value: Value) -> bool {
+        let old = self.value_of(&register.clone().into());
+        let new = old.meet(value);
+        self.values.insert(register, new);
+        old != new
+    }
+
+    fn mark_edge(&mut self, from: usize, to: usize) -> bool {
+        self.executable_blocks.insert(to);
+        self.executable_edges.insert((from, to))
+    }
+
+    /// Which successors the branch may jump to, according to what we know now.
+    fn branch_targets<'a>(&self, branch: &'a Branch) -> Vec<&'a str> {
+        match (
+            self.value_of(&branch.operand1),
+            self.value_of(&branch.operand2),
+        ) {
+            (Value::Constant(a), Value::Constant(b)) => {
+                if evaluate_branch(branch.branch_type, a, b, &self.branch_operand_type(branch)) {
+                    vec![&branch.success_label]
+                } else {
+                    vec![&branch.failure_label]
+                }
+            }
+            (Value::Undefined, _) | (_, Value::Undefined) => Vec::new(),
+            _ => vec![&branch.success_label, &branch.failure_label],
+        }
+    }
+
+    fn evaluate(&self, statement: &IRStatement, block_index: usize, blocks: &[&str]) -> Value {
+        match statement {
+            IRStatement::Phi(Phi { from, .. }) => from
+                .iter()
+                .filter(|source| {
+                    let from_index = blocks.iter().position(|it| *it == source.block).unwrap();
+                    self.executable_edges.contains(&(from_index, block_index))
+                })
+                .fold(Value::Undefined, |acc, source| {
+                    acc.meet(self.value_of(&source.value))
+                }),
+            IRStatement::BinaryCalculate(BinaryCalculate {
+                operation,
+                operand1,
+                operand2,
+                data_type,
+                ..
+            }) => match (self.value_of(operand1), self.value_of(operand2)) {
+                (Value::Constant(a), Value::Constant(b)) => {
+                    evaluate_binary(*operation, a, b, data_type)
+                        .map_or(Value::Overdefined, Value::Constant)
+                }
+                (Value::Undefined, _) | (_, Value::Undefined) => Value::Undefined,
+                _ => Value::Overdefined,
+            },
+            IRStatement::UnaryCalculate(UnaryCalculate {
+                operation,
+                operand,
+                data_type,
+                ..
+            }) => match self.value_of(operand) {
+                Value::Constant(value) => evaluate_unary(*operation, value, data_type)
+                    .map_or(Value::Overdefined, Value::Constant),
+                other => other,
+            },
+            _ => Value::Overdefined,
+        }
+    }
+
+    fn new(function: &FunctionDefinition) -> Self {
+        let blocks: Vec<&str> = function
+            .content
+            .iter()
+            .map(|it| it.name.as_ref().unwrap().as_str())
+            .collect();
+        let mut lattice = Self {
+            values: function
+                .header
+                .parameters
+                .iter()
+                .map(|it| (it.name.clone(), Value::Overdefined))
+                .collect(),
+            types: function
+                .header
+                .parameters
+                .iter()
+                .map(|it| (it.name.clone(), it.data_type.clone()))
+                .chain(
+                    function
+                        .iter()
+                        .filter_map(|statement| statement.generate_register()),
+                )
+                .collect(),
+            executable_blocks: HashSet::from([0]),
+            executable_edges: HashSet::new(),
+        };
+        let block_index = |name: &str| blocks.iter().position(|it| *it == name).unwrap();
+        // all transfer functions are monotone, so iterating until nothing changes is enough
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (index, block) in function.content.iter().enumerate() {
+                if !lattice.executable_blocks.contains(&index) {
+                    continue;
+                }
+                for statement in &block.content {
+                    match statement {
+                        IRStatement::Jump(Jump { label }) => {
+                            changed |= lattice.mark_edge(index, block_index(label));
+                        }
+                        IRStatement::Branch(branch) => {
+                            for target in lattice.branch_targets(branch) {
+                                changed |= lattice.mark_edge(index, block_index(target));
+                            }
+                        }
+                        _ => {
+                            if let Some((register, _)) = statement.generate_register() {
+                                let value = lattice.evaluate(statement, index, &blocks);
+                                changed |= lattice.update(register, value);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        lattice
+    }
+}
+
+impl IsPass for SparseConditionalConstantPropagation {
+    fn run(&self, editor: &mut Editor) {
+        let lattice = Lattice::new(&editor.content);
+        let blocks: Vec<String> = editor
+            .content
+            .content
+            .iter()
+            .map(|it| it.name.clone().unwrap())
+            .collect();
+        let mut to_replace = Vec::new();
+        let mut to_remove = Vec::new();
+        let mut to_rename = HashMap::new();
+        for (index, statement) in editor.content.iter().function_definition_index_enumerate() {
+            let FunctionDefinitionIndex(block_index, _) = index;
+            if !lattice.executable_blocks.contains(&block_index) {
+                continue;
+            }
+            if let Some((register, _)) = statement.generate_register()
+                && let Value::Constant(value) = lattice.value_of(&register.clone().into())
+            {
+                to_remove.push(index);
+                to_rename.insert(register, Quantity::NumberLiteral(value));
+                continue;
+            }
+            match statement {
+                IRStatement::Phi(phi) => {
+                    let mut phi = phi.clone();
+                    let origin_len = phi.from.len();
+                    phi.from.retain(|source| {
+                        let from_index = blocks.iter().position(|it| it == &source.block).unwrap();
+                        lattice
+                            .executable_edges
+                            .contains(&(from_index, block_index))
+                    });
+                    if phi.from.len() == 1 {
+                        to_remove.push(index);
+                        to_rename.insert(phi.to, phi.from.pop().unwrap().value);
+                    } else if phi.from.len() != origin_len {
+                        to_replace.push((index, IRStatement::from(phi)));
+                    }
+                }
+                IRStatement::Branch(branch) => {
+                    if let [target] = lattice.branch_targets(branch)[..]
+                        && let (Value::Constant(_), Value::Constant(_)) = (
+                            lattice.value_of(&branch.operand1),
+                            lattice.value_of(&branch.operand2),
+                        )
+                    {
+                        let jump = Jump {
+                            label: target.to_string(),
+                        };
+                        to_replace.push((index, jump.into()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (index, statement) in to_replace {
+            editor.replace_statement(index, statement);
+        }
+        editor.remove_statements(to_remove);
+        for (from, mut to) in to_rename.clone() {
+            // the register we rename to may also be renamed
+            while let Some(next) = to.as_local().and_then(|it| to_rename.get(it)) {
+                to = next.clone();
+            }
+            editor.rename_local(from, to);
+        }
+    }
+
+    fn need(&self) -> Vec<super::Pass> {
+        vec![MemoryToRegister.into()]
+    }
+
+    fn invalidate(&self) -> Vec<super::Pass> {
+        vec![RemoveUnusedRegister.into()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::borrow_interior_mutable_const)]
+    use super::*;
+    use crate::{
+        ir::{self, editor::Editor},
+        utility::data_type,
+    };
+
+    #[test]
+    fn test_evaluate_binary() {
+        let i8 = Type::Integer(Integer {
+            signed: true,
+            width: 8,
+        });
+        let u8 = Type::Integer(Integer {
+            signed: false,
+            width: 8,
+        });
+        assert_eq!(
+            evaluate_binary(BinaryOperation::Add, 127, 1, &i8),
+            Some(-128)
+        );
+        assert_eq!(evaluate_binary(BinaryOperation::Add, 255, 1, &u8), Some(0));
+        assert_eq!(evaluate_binary(BinaryOperation::Sub, 0, 1, &u8), Some(255));
+        assert_eq!(
+            evaluate_binary(BinaryOperation::Mul, 0x10000, 0x10000, &data_type::I32),
+            Some(0)
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::LogicalShiftRight, -1, 28, &data_type::I32),
+            Some(15)
+        );
+        assert_eq!(
+            evaluate_binary(
+                BinaryOperation::AthematicShiftRight,
+                -16,
+                2,
+                &data_type::I32
+            ),
+            Some(-4)
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::LogicalShiftLeft, 1, 33, &data_type::I32),
+            Some(2)
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::SignedDiv, -7, 2, &data_type::I32),
+            Some(-3)
+        );
+        assert_eq!(
+            evaluate_binary(
+                BinaryOperation::SignedDiv,
+                i32::MIN as i64,
+                -1,
+                &data_type::I32
+            ),
+            Some(i32::MIN as i64)
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::UnsignedRem, -1, 10, &data_type::I32),
+            Some(5)
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::SignedRem, 1, 0, &data_type::I32),
+            None
+        );
+        assert_eq!(
+            evaluate_binary(BinaryOperation::LessThan, -1, 0, &data_type::I32),
+            Some(1)
+        );
+        assert_eq!(evaluate_unary(UnaryOperation::Not, 0, &u8), Some(255));
+        assert!(evaluate_branch(
+            BranchType::LT,
+            0xffff_ffff,
+            0,
+            &data_type::I32
+        ));
+        assert!(evaluate_branch(BranchType::LTU, 0, -1, &data_type::I32));
+        assert!(evaluate_branch(
+            BranchType::EQ,
+            0xffff_ffff,
+            -1,
+            &data_type::I32
+        ));
+        assert_eq!(evaluate_unary(UnaryOperation::Neg, -128, &i8), Some(-128));
+    }
+
+    #[test]
+    fn run() {
+        let function = ir::function::parse(
+            "fn f(i32 %p) -> i32 {
+  bb0:
+    %a = add i32 1, 2
+    %b = slt i32 %a, 5
+    bne %b, 0, bb1, bb2
+  bb1:
+    %c = mul i32 %a, 2
+    j bb3
+  bb2:
+    %d = add i32 %p, 1
+    j bb3
+  bb3:
+    %e = phi i32 [%c, bb1], [%d, bb2]
+    %f = add i32 %e, %p
+    ret %f
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        SparseConditionalConstantPropagation.run(&mut editor);
+        let content = &editor.content.content;
+        assert_eq!(
+            content[0].content,
+            vec![IRStatement::from(Jump {
+                label: "bb1".to_string()
+            })]
+        );
+        assert_eq!(content[1].content.len(), 1);
+        assert_eq!(
+            content[3].content[0],
+            ir::function::statement::parse("%f = add i32 6, %p")
+                .unwrap()
+                .1
+        );
+    }
+
+    #[test]
+    fn run_loop() {
+        // `%i` is not a constant, but `%k` is, since the branch in `bb2` always goes to `bb3`
+        let function = ir::function::parse(
+            "fn f() -> i32 {
+  bb0:
+    j bb1
+  bb1:
+    %i = phi i32 [0, bb0], [%i1, bb3], [%i2, bb4]
+    %k = phi i32 [1, bb0], [%k, bb3], [%k2, bb4]
+    %i1 = add i32 %i, 1
+    blt %i1, 10, bb2, bb5
+  bb2:
+    blt %k, 100, bb3, bb4
+  bb3:
+    j bb1
+  bb4:
+    %k2 = add i32 %k, 1
+    %i2 = add i32 %i1, 1
+    j bb1
+  bb5:
+    ret %k
+}",
+        )
+        .unwrap()
+        .1;
+        let mut editor = Editor::new(function);
+        SparseConditionalConstantPropagation.run(&mut editor);
+        let content = &editor.content.content;
+        assert_eq!(
+            content[1].content[0],
+            ir::function::statement::parse("%i = phi i32 [0, bb0], [%i1, bb3]")
+                .unwrap()
+                .1
+        );
+        assert_eq!(content[1].content.len(), 3);
+        assert_eq!(
+            content[2].content,
+            vec![IRStatement::from(Jump {
+                label: "bb3".to_string()
+            })]
+        );
+        assert_eq!(
+            content[5].content[0],
+            ir::function::statement::parse("ret 1").unwrap().1
+        );
+    }
+}
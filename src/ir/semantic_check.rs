@@ -0,0 +1,649 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    ast::{
+        self,
+        expression::{FieldAccess, LValue, RValue, Subscript},
+        statement::{compound::Compound, Statement},
+        ASTNode, Ast,
+    },
+    utility::data_type::{Integer, Type},
+};
+
+use super::{function, type_definition, FunctionHeader, IRGeneratingContext};
+
+/// Kinds of errors which can be found by [`check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticErrorKind {
+    /// A variable is used without being declared.
+    UndeclaredVariable(String),
+    /// A function is called without being defined.
+    UnknownFunction(String),
+    /// A struct type is used without being defined.
+    UnknownType(String),
+    /// The struct does not have such a field.
+    UnknownField { struct_name: String, field: String },
+    /// Try to access a field of a value which is not a struct.
+    FieldOfNonStruct { data_type: Type, field: String },
+    /// Try to subscript a value which is not an array.
+    SubscriptOfNonArray(Type),
+    /// A function is called with a wrong number of arguments.
+    ArgumentCountMismatch {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    /// The type of a value is not the expected one.
+    TypeMismatch { expected: Type, found: Type },
+    /// An integer is expected, but the value is not.
+    ExpectInteger(Type),
+    /// A struct, function or global variable is defined more than once.
+    Redefinition(String),
+    /// A global variable is initialized by something other than an integer literal.
+    NonConstantGlobalInitializer(String),
+}
+
+impl fmt::Display for SemanticErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticErrorKind::UndeclaredVariable(name) => {
+                write!(f, "use of undeclared variable `{name}`")
+            }
+            SemanticErrorKind::UnknownFunction(name) => {
+                write!(f, "call to unknown function `{name}`")
+            }
+            SemanticErrorKind::UnknownType(name) => write!(f, "unknown type `{name}`"),
+            SemanticErrorKind::UnknownField { struct_name, field } => {
+                write!(f, "struct `{struct_name}` has no field `{field}`")
+            }
+            SemanticErrorKind::FieldOfNonStruct { data_type, field } => {
+                write!(
+                    f,
+                    "cannot access field `{field}` of non-struct type `{data_type}`"
+                )
+            }
+            SemanticErrorKind::SubscriptOfNonArray(data_type) => {
+                write!(f, "cannot subscript non-array type `{data_type}`")
+            }
+            SemanticErrorKind::ArgumentCountMismatch {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "function `{function}` takes {expected} argument(s) but {found} were given"
+            ),
+            SemanticErrorKind::TypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types: expected `{expected}`, found `{found}`"
+                )
+            }
+            SemanticErrorKind::ExpectInteger(found) => {
+                write!(f, "expected an integer, found `{found}`")
+            }
+            SemanticErrorKind::Redefinition(name) => {
+                write!(f, "`{name}` is defined more than once")
+            }
+            SemanticErrorKind::NonConstantGlobalInitializer(name) => write!(
+                f,
+                "global variable `{name}` must be initialized with an integer literal"
+            ),
+        }
+    }
+}
+
+/// [`SemanticError`] represents an error found when checking the AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SemanticError {
+    /// The function in which the error is found, `None` if it is found in a top level definition.
+    pub function: Option<String>,
+    /// What is wrong.
+    pub kind: SemanticErrorKind,
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.kind)?;
+        if let Some(function) = &self.function {
+            write!(f, " in function `{function}`")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+/// Integers and addresses can be used interchangeably.
+fn is_integer(data_type: &Type) -> bool {
+    matches!(data_type, Type::Integer(_) | Type::Address)
+}
+
+fn compatible(expected: &Type, found: &Type) -> bool {
+    match (expected, found) {
+        (expected, found) if is_integer(expected) && is_integer(found) => true,
+        (Type::StructRef(expected), Type::StructRef(found)) => expected == found,
+        (Type::Array(expected, expected_length), Type::Array(found, found_length)) => {
+            expected_length == found_length && compatible(expected, found)
+        }
+        (Type::None, Type::None) => true,
+        _ => false,
+    }
+}
+
+struct Checker {
+    /// Known functions and structs, shared with the IR generator.
+    context: IRGeneratingContext,
+    global_variables: HashMap<String, Type>,
+    /// Types of local variables. The latter in the [`Vec`] has higher priority.
+    variable_types_stack: Vec<HashMap<String, Type>>,
+    current_function: Option<FunctionHeader>,
+    errors: Vec<SemanticError>,
+}
+
+impl Checker {
+    fn report(&mut self, kind: SemanticErrorKind) {
+        self.errors.push(SemanticError {
+            function: self
+                .current_function
+                .as_ref()
+                .map(|function| function.name.clone()),
+            kind,
+        });
+    }
+
+    fn check_type_exists(&mut self, data_type: &Type) {
+        match data_type {
+            Type::StructRef(name) if !self.context.type_definitions.contains_key(name) => {
+                self.report(SemanticErrorKind::UnknownType(name.clone()));
+            }
+            Type::Array(element_type, _) => self.check_type_exists(element_type),
+            _ => {}
+        }
+    }
+
+    fn variable_type(&self, name: &str) -> Option<Type> {
+        self.variable_types_stack
+            .iter()
+            .rev()
+            .find_map(|frame| frame.get(name))
+            .or_else(|| self.global_variables.get(name))
+            .cloned()
+    }
+
+    fn field_type(&mut self, field_access: &FieldAccess) -> Option<Type> {
+        let FieldAccess { from, name } = field_access;
+        match self.lvalue_type(from)? {
+            Type::StructRef(struct_name) => {
+                // unknown struct types are already reported when the variable is declared
+                let mapping = self.context.type_definitions.get(&struct_name)?;
+                if let Some(index) = mapping.field_names.get(name) {
+                    Some(mapping.field_types[*index].clone())
+                } else {
+                    self.report(SemanticErrorKind::UnknownField {
+                        struct_name,
+                        field: name.clone(),
+                    });
+                    None
+                }
+            }
+            data_type => {
+                self.report(SemanticErrorKind::FieldOfNonStruct {
+                    data_type,
+                    field: name.clone(),
+                });
+                None
+            }
+        }
+    }
+
+    fn subscript_type(&mut self, subscript: &Subscript) -> Option<Type> {
+        let Subscript { from, index } = subscript;
+        let array_type = self.lvalue_type(from);
+        self.expect_integer(index);
+        match array_type? {
+            Type::Array(element_type, _) => Some(*element_type),
+            data_type => {
+                self.report(SemanticErrorKind::SubscriptOfNonArray(data_type));
+                None
+            }
+        }
+    }
+
+    /// Decide the type of an [`LValue`], `None` if there are errors in it.
+    fn lvalue_type(&mut self, lvalue: &LValue) -> Option<Type> {
+        match lvalue {
+            LValue::VariableRef(variable) => {
+                let result = self.variable_type(&variable.0);
+                if result.is_none() {
+                    self.report(SemanticErrorKind::UndeclaredVariable(variable.0.clone()));
+                }
+                result
+            }
+            LValue::FieldAccess(field_access) => self.field_type(field_access),
+            LValue::Subscript(subscript) => self.subscript_type(subscript),
+        }
+    }
+
+    fn function_call_type(
+        &mut self,
+        function_call: &ast::expression::FunctionCall,
+    ) -> Option<Type> {
+        let ast::expression::FunctionCall { name, arguments } = function_call;
+        let Some(header) = self.context.function_definitions.get(name).cloned() else {
+            self.report(SemanticErrorKind::UnknownFunction(name.clone()));
+            for argument in arguments {
+                self.rvalue_type(argument);
+            }
+            return None;
+        };
+        if header.parameters.len() != arguments.len() {
+            self.report(SemanticErrorKind::ArgumentCountMismatch {
+                function: name.clone(),
+                expected: header.parameters.len(),
+                found: arguments.len(),
+            });
+        }
+        for (i, argument) in arguments.iter().enumerate() {
+            if let Some(parameter) = header.parameters.get(i) {
+                self.expect_type(&parameter.data_type, argument);
+            } else {
+                self.rvalue_type(argument);
+            }
+        }
+        Some(header.return_type)
+    }
+
+    /// Decide the type of an [`RValue`], `None` if there are errors in it.
+    fn rvalue_type(&mut self, rvalue: &RValue) -> Option<Type> {
+        match rvalue {
+            RValue::IntegerLiteral(_) => Some(Type::Integer(Integer {
+                signed: true,
+                width: 32,
+            })),
+            RValue::VariableRef(variable) => {
+                self.lvalue_type(&LValue::VariableRef(variable.clone()))
+            }
+            RValue::InBrackets(in_brackets) => self.rvalue_type(&in_brackets.0),
+            RValue::FieldAccess(field_access) => self.field_type(field_access),
+            RValue::Subscript(subscript) => self.subscript_type(subscript),
+            RValue::FunctionCall(function_call) => self.function_call_type(function_call),
+            RValue::UnaryOperatorResult(unary_operator_result) => {
+                self.expect_integer(&unary_operator_result.operand)
+            }
+            RValue::BinaryOperatorResult(binary_operator_result) => {
+                self.expect_integer(&binary_operator_result.lhs);
+                self.expect_integer(&binary_operator_result.rhs);
+                // keep the same as the ir generator
+                Some(Type::Integer(Integer {
+                    signed: true,
+                    width: 32,
+                }))
+            }
+        }
+    }
+
+    fn expect_type(&mut self, expected: &Type, rvalue: &RValue) {
+        if let Some(found) = self.rvalue_type(rvalue)
+            && !compatible(expected, &found)
+        {
+            self.report(SemanticErrorKind::TypeMismatch {
+                expected: expected.clone(),
+                found,
+            });
+        }
+    }
+
+    fn expect_integer(&mut self, rvalue: &RValue) -> Option<Type> {
+        let found = self.rvalue_type(rvalue)?;
+        if is_integer(&found) {
+            Some(found)
+        } else {
+            self.report(SemanticErrorKind::ExpectInteger(found));
+            None
+        }
+    }
+
+    fn check_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Declare(declare) => {
+                self.check_type_exists(&declare.data_type);
+                if let Some(init_value) = &declare.init_value {
+                    self.expect_type(&declare.data_type, init_value);
+                }
+                self.variable_types_stack
+                    .last_mut()
+                    .unwrap()
+                    .insert(declare.variable_name.clone(), declare.data_type.clone());
+            }
+            Statement::Assign(assign) => {
+                if let Some(lhs_type) = self.lvalue_type(&assign.lhs) {
+                    self.expect_type(&lhs_type, &assign.rhs);
+                } else {
+                    self.rvalue_type(&assign.rhs);
+                }
+            }
+            Statement::Return(ast::statement::Return(value)) => {
+                let return_type = self.current_function.as_ref().unwrap().return_type.clone();
+                match value {
+                    Some(value) => self.expect_type(&return_type, value),
+                    None if return_type != Type::None => {
+                        self.report(SemanticErrorKind::TypeMismatch {
+                            expected: return_type,
+                            found: Type::None,
+                        })
+                    }
+                    None => (),
+                }
+            }
+            Statement::If(if_statement) => {
+                self.expect_integer(&if_statement.condition);
+                self.check_compound(&if_statement.content);
+                if let Some(else_content) = &if_statement.else_content {
+                    self.check_compound(else_content);
+                }
+            }
+            Statement::While(while_statement) => {
+                self.expect_integer(&while_statement.condition);
+                self.check_compound(&while_statement.content);
+            }
+            Statement::FunctionCall(function_call) => {
+                self.function_call_type(&function_call.0);
+            }
+        }
+    }
+
+    fn check_compound(&mut self, compound: &Compound) {
+        self.variable_types_stack.push(HashMap::new());
+        for statement in &compound.0 {
+            self.check_statement(statement);
+        }
+        self.variable_types_stack.pop();
+    }
+
+    fn check_function(
+        &mut self,
+        function_definition: &ast::function_definition::FunctionDefinition,
+    ) {
+        self.current_function = Some(function::header_from_ast(function_definition));
+        let mut parameters = HashMap::new();
+        for parameter in &function_definition.parameters {
+            self.check_type_exists(&parameter.data_type);
+            parameters.insert(parameter.name.clone(), parameter.data_type.clone());
+        }
+        self.check_type_exists(&function_definition.return_type);
+        self.variable_types_stack = vec![parameters];
+        self.check_compound(&function_definition.content);
+        self.current_function = None;
+    }
+}
+
+/// Check the whole [`Ast`] before generating IR from it.
+/// All errors found are collected and returned together.
+pub fn check(ast: &Ast) -> Result<(), Vec<SemanticError>> {
+    let mut checker = Checker {
+        context: IRGeneratingContext::new(),
+        global_variables: HashMap::new(),
+        variable_types_stack: Vec::new(),
+        current_function: None,
+        errors: Vec::new(),
+    };
+    // collect all top level definitions first, so they can be used regardless of order
+    for node in ast {
+        let name = match node {
+            ASTNode::TypeDefinition(type_definition) => &type_definition.name,
+            ASTNode::FunctionDefinition(function_definition) => &function_definition.name,
+            ASTNode::GlobalVariableDefinition(global_definition) => {
+                &global_definition.0.variable_name
+            }
+        };
+        let redefined = checker.context.type_definitions.contains_key(name)
+            || checker.context.function_definitions.contains_key(name)
+            || checker.global_variables.contains_key(name);
+        if redefined {
+            checker.report(SemanticErrorKind::Redefinition(name.clone()));
+            continue;
+        }
+        match node {
+            ASTNode::TypeDefinition(type_definition) => {
+                type_definition::from_ast(type_definition, &mut checker.context);
+            }
+            ASTNode::FunctionDefinition(function_definition) => {
+                checker
+                    .context
+                    .function_definitions
+                    .insert(name.clone(), function::header_from_ast(function_definition));
+            }
+            ASTNode::GlobalVariableDefinition(global_definition) => {
+                checker
+                    .global_variables
+                    .insert(name.clone(), global_definition.0.data_type.clone());
+            }
+        }
+    }
+    for node in ast {
+        match node {
+            ASTNode::TypeDefinition(type_definition) => {
+                for field in &type_definition.fields {
+                    checker.check_type_exists(&field.data_type);
+                }
+            }
+            ASTNode::FunctionDefinition(function_definition) => {
+                checker.check_function(function_definition);
+            }
+            ASTNode::GlobalVariableDefinition(global_definition) => {
+                let ast::statement::Declare {
+                    variable_name,
+                    data_type,
+                    init_value,
+                } = &global_definition.0;
+                checker.check_type_exists(data_type);
+                match init_value {
+                    None => (),
+                    Some(RValue::IntegerLiteral(_)) if is_integer(data_type) => (),
+                    Some(RValue::IntegerLiteral(_)) => {
+                        checker.report(SemanticErrorKind::TypeMismatch {
+                            expected: data_type.clone(),
+                            found: Type::Integer(Integer {
+                                signed: true,
+                                width: 32,
+                            }),
+                        })
+                    }
+                    Some(_) => checker.report(SemanticErrorKind::NonConstantGlobalInitializer(
+                        variable_name.clone(),
+                    )),
+                }
+            }
+        }
+    }
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_source(source: &str) -> Result<(), Vec<SemanticErrorKind>> {
+        let ast = ast::from_source(source).unwrap();
+        check(&ast).map_err(|errors| errors.into_iter().map(|it| it.kind).collect())
+    }
+
+    #[test]
+    fn check_ok() {
+        assert_eq!(
+            check_source(
+                "struct Foo { a: i32, b: i32 }
+                fn f(foo: Foo) -> i32 {
+                    foo.a = foo.a + foo.b;
+                    return g(foo.a);
+                }
+                fn g(a: i32) -> i32 {
+                    let b: u32 = load_u32(0x80002000);
+                    if a == b {
+                        let a: Foo;
+                        return a.b;
+                    }
+                    return f_global;
+                }
+                let f_global: i32 = 1;"
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn check_variables() {
+        assert_eq!(
+            check_source(
+                "fn f() -> () {
+                    if 1 {
+                        let a: i32 = 1;
+                    }
+                    a = b;
+                }"
+            ),
+            Err(vec![
+                SemanticErrorKind::UndeclaredVariable("a".to_string()),
+                SemanticErrorKind::UndeclaredVariable("b".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn check_functions() {
+        let errors = check_source(
+            "fn f(a: i32, b: i32) -> i32 {
+                return a;
+            }
+            fn g() -> () {
+                let a: i32 = f(1);
+                h(a);
+                return 1;
+            }
+            fn g() -> () {}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                SemanticErrorKind::Redefinition("g".to_string()),
+                SemanticErrorKind::ArgumentCountMismatch {
+                    function: "f".to_string(),
+                    expected: 2,
+                    found: 1
+                },
+                SemanticErrorKind::UnknownFunction("h".to_string()),
+                SemanticErrorKind::TypeMismatch {
+                    expected: Type::None,
+                    found: Type::Integer(Integer {
+                        signed: true,
+                        width: 32
+                    })
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn check_types() {
+        let errors = check_source(
+            "struct Foo { a: i32, b: Bar }
+            struct Baz { a: i32 }
+            fn f(foo: Foo, baz: Baz) -> i32 {
+                foo = baz;
+                let x: i32 = foo.c;
+                x = x.a;
+                return foo + 1;
+            }",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                SemanticErrorKind::UnknownType("Bar".to_string()),
+                SemanticErrorKind::TypeMismatch {
+                    expected: Type::StructRef("Foo".to_string()),
+                    found: Type::StructRef("Baz".to_string())
+                },
+                SemanticErrorKind::UnknownField {
+                    struct_name: "Foo".to_string(),
+                    field: "c".to_string()
+                },
+                SemanticErrorKind::FieldOfNonStruct {
+                    data_type: Type::Integer(Integer {
+                        signed: true,
+                        width: 32
+                    }),
+                    field: "a".to_string()
+                },
+                SemanticErrorKind::ExpectInteger(Type::StructRef("Foo".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_arrays() {
+        assert_eq!(
+            check_source(
+                "fn f() -> i32 {
+                    let a: [[i32; 4]; 2];
+                    let i: u32 = 1;
+                    a[i][a[0][1]] = 2;
+                    return a[1][i + 1];
+                }"
+            ),
+            Ok(())
+        );
+        let errors = check_source(
+            "struct Foo { a: i32 }
+            fn f(foo: Foo) -> () {
+                let a: [Bar; 2];
+                let b: [i32; 2];
+                let c: [i32; 3] = b;
+                b[foo] = 1;
+                foo[0] = 1;
+            }",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                SemanticErrorKind::UnknownType("Bar".to_string()),
+                SemanticErrorKind::TypeMismatch {
+                    expected: Type::Array(
+                        Box::new(Type::Integer(Integer {
+                            signed: true,
+                            width: 32
+                        })),
+                        3
+                    ),
+                    found: Type::Array(
+                        Box::new(Type::Integer(Integer {
+                            signed: true,
+                            width: 32
+                        })),
+                        2
+                    )
+                },
+                SemanticErrorKind::ExpectInteger(Type::StructRef("Foo".to_string())),
+                SemanticErrorKind::SubscriptOfNonArray(Type::StructRef("Foo".to_string())),
+            ]
+        );
+        assert_eq!(
+            check_source(
+                "struct Buffer { data: [u8; 4] }
+                let global_buffer: [u8; 4];
+                fn f(buffer: Buffer) -> u8 {
+                    global_buffer[1] = 1;
+                    return buffer.data[global_buffer[1]];
+                }"
+            ),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt,
+};
+
+use petgraph::{
+    algo::dominators::{simple_fast, Dominators},
+    prelude::*,
+};
+
+use crate::{
+    ir::{
+        function::FunctionDefinitionIndex,
+        quantity::Quantity,
+        statement::{IRStatement, IsIRStatement},
+        FunctionDefinition, RegisterName, IR,
+    },
+    utility::data_type::Type,
+};
+
+/// Kinds of errors which can be found by [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+    /// A register is defined more than once.
+    Redefinition(RegisterName),
+    /// A register is used without being defined.
+    UndefinedRegister(RegisterName),
+    /// A register is used in a block where its definition may not have been executed.
+    NotDominated {
+        register: RegisterName,
+        block: String,
+    },
+    /// The blocks a phi gets values from are not the predecessors of its block.
+    PhiSourceMismatch {
+        register: RegisterName,
+        predecessors: Vec<String>,
+        sources: Vec<String>,
+    },
+    /// A block doesn't end with `j`, a branch or `ret`.
+    MissingTerminator(String),
+    /// A `j`, branch or `ret` is found before the end of a block.
+    MisplacedTerminator(String),
+    /// A `j` or branch goes to a block which doesn't exist.
+    UnknownLabel { block: String, label: String },
+    /// A register is used as a value of another type.
+    TypeMismatch {
+        register: RegisterName,
+        expected: Type,
+        found: Type,
+    },
+}
+
+impl fmt::Display for VerifyErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyErrorKind::Redefinition(register) => {
+                write!(f, "register `{register}` is defined more than once")
+            }
+            VerifyErrorKind::UndefinedRegister(register) => {
+                write!(f, "register `{register}` is used but never defined")
+            }
+            VerifyErrorKind::NotDominated { register, block } => write!(
+                f,
+                "definition of `{register}` does not dominate its use in block `{block}`"
+            ),
+            VerifyErrorKind::PhiSourceMismatch {
+                register,
+                predecessors,
+                sources,
+            } => write!(
+                f,
+                "phi `{register}` has sources from [{}], but the predecessors are [{}]",
+                sources.join(", "),
+                predecessors.join(", ")
+            ),
+            VerifyErrorKind::MissingTerminator(block) => {
+                write!(f, "block `{block}` does not end with a terminator")
+            }
+            VerifyErrorKind::MisplacedTerminator(block) => {
+                write!(f, "block `{block}` has a terminator before its end")
+            }
+            VerifyErrorKind::UnknownLabel { block, label } => {
+                write!(f, "block `{block}` goes to unknown block `{label}`")
+            }
+            VerifyErrorKind::TypeMismatch {
+                register,
+                expected,
+                found,
+            } => write!(
+                f,
+                "register `{register}` of type `{found}` is used as `{expected}`"
+            ),
+        }
+    }
+}
+
+/// [`VerifyError`] represents a broken invariant found in the ir.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    /// The function in which the error is found.
+    pub function: String,
+    /// What is wrong.
+    pub kind: VerifyErrorKind,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {} in function `{}`", self.kind, self.function)
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+fn is_terminator(statement: &IRStatement) -> bool {
+    matches!(
+        statement,
+        IRStatement::Jump(_) | IRStatement::Branch(_) | IRStatement::Ret(_)
+    )
+}
+
+fn successors(statement: &IRStatement) -> Vec<&str> {
+    match statement {
+        IRStatement::Jump(jump) => vec![&jump.label],
+        IRStatement::Branch(branch) => vec![&branch.success_label, &branch.failure_label],
+        _ => Vec::new(),
+    }
+}
+
+/// Integers and addresses can be used interchangeably.
+fn compatible(expected: &Type, found: &Type) -> bool {
+    let is_integer = |it: &Type| matches!(it, Type::Integer(_) | Type::Address);
+    (is_integer(expected) && is_integer(found)) || expected == found
+}
+
+/// Register operands of `statement` which must be of some type, with the type.
+fn typed_operands(statement: &IRStatement, return_type: &Type) -> Vec<(RegisterName, Type)> {
+    let address = || Type::Address;
+    let mut result = Vec::new();
+    let mut expect = |quantity: &Quantity, data_type: Type| {
+        if let Quantity::RegisterName(register) = quantity {
+            result.push((register.clone(), data_type));
+        }
+    };
+    match statement {
+        IRStatement::BinaryCalculate(binary) => {
+            expect(&binary.operand1, binary.data_type.clone());
+            expect(&binary.operand2, binary.data_type.clone());
+        }
+        IRStatement::UnaryCalculate(unary) => expect(&unary.operand, unary.data_type.clone()),
+        IRStatement::Store(store) => {
+            expect(&store.source, store.data_type.clone());
+            expect(&store.target, address());
+        }
+        IRStatement::Load(load) => expect(&load.from, address()),
+        IRStatement::LoadField(load_field) => expect(
+            &load_field.source.clone().into(),
+            load_field.field_chain[0].0.clone(),
+        ),
+        IRStatement::SetField(set_field) => {
+            expect(&set_field.source, set_field.final_type.clone());
+            expect(
+                &set_field.origin_root.clone().into(),
+                set_field.field_chain[0].0.clone(),
+            );
+        }
+        IRStatement::ElementAddress(element_address) => {
+            expect(&element_address.base, address());
+            expect(&element_address.index, address());
+        }
+        IRStatement::FieldAddress(field_address) => expect(&field_address.base, address()),
+        IRStatement::Phi(phi) => {
+            for source in &phi.from {
+                expect(&source.value, phi.data_type.clone());
+            }
+        }
+        IRStatement::Branch(branch) => {
+            expect(&branch.operand1, address());
+            expect(&branch.operand2, address());
+        }
+        IRStatement::Ret(ret) => {
+            if let Some(value) = &ret.value {
+                expect(value, return_type.clone());
+            }
+        }
+        _ => (),
+    }
+    result
+}
+
+/// Check that each block ends with exactly one terminator, and all jumps go to existing blocks.
+fn verify_structure(function: &FunctionDefinition, errors: &mut Vec<VerifyErrorKind>) {
+    let names: HashSet<_> = function
+        .content
+        .iter()
+        .map(|block| block.name.as_deref().unwrap())
+        .collect();
+    for block in &function.content {
+        let name = block.name.clone().unwrap();
+        let Some(last) = block.content.last() else {
+            errors.push(VerifyErrorKind::MissingTerminator(name));
+            continue;
+        };
+        if !is_terminator(last) {
+            errors.push(VerifyErrorKind::MissingTerminator(name.clone()));
+        }
+        if block.content[..block.content.len() - 1]
+            .iter()
+            .any(is_terminator)
+        {
+            errors.push(VerifyErrorKind::MisplacedTerminator(name.clone()));
+        }
+        for label in successors(last) {
+            if !names.contains(label) {
+                errors.push(VerifyErrorKind::UnknownLabel {
+                    block: name.clone(),
+                    label: label.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Check that registers are defined once with the right types, definitions dominate uses, and
+/// phi sources are the predecessors.
+fn verify_values(function: &FunctionDefinition, errors: &mut Vec<VerifyErrorKind>) {
+    let block_index: HashMap<_, _> = function
+        .content
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block.name.as_deref().unwrap(), index))
+        .collect();
+    let mut graph = DiGraph::<(), (), usize>::default();
+    for _ in &function.content {
+        graph.add_node(());
+    }
+    let mut predecessors = vec![Vec::new(); function.content.len()];
+    for (index, block) in function.content.iter().enumerate() {
+        for successor in successors(block.content.last().unwrap()) {
+            let successor = block_index[successor];
+            graph.add_edge(index.into(), successor.into(), ());
+            if !predecessors[successor].contains(&index) {
+                predecessors[successor].push(index);
+            }
+        }
+    }
+    let dominators: Dominators<NodeIndex<usize>> = simple_fast(&graph, 0.into());
+    let reachable = |block: usize| dominators.dominators(block.into()).is_some();
+    let dominates = |dominator: usize, block: usize| {
+        dominators
+            .dominators(block.into())
+            .is_some_and(|mut it| it.any(|it| it.index() == dominator))
+    };
+
+    // `None` for parameters, which are defined before all statements
+    let mut definitions: HashMap<RegisterName, (Option<FunctionDefinitionIndex>, Type)> =
+        HashMap::new();
+    for parameter in &function.header.parameters {
+        definitions.insert(parameter.name.clone(), (None, parameter.data_type.clone()));
+    }
+    for (index, statement) in function.iter().function_definition_index_enumerate() {
+        if let Some((register, data_type)) = statement.generate_register() {
+            match definitions.entry(register) {
+                Entry::Occupied(entry) => {
+                    errors.push(VerifyErrorKind::Redefinition(entry.key().clone()))
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((Some(index), data_type));
+                }
+            }
+        }
+    }
+
+    for (index, statement) in function.iter().function_definition_index_enumerate() {
+        for (register, expected) in typed_operands(statement, &function.header.return_type) {
+            if let Some((_, found)) = definitions.get(&register)
+                && !compatible(&expected, found)
+            {
+                errors.push(VerifyErrorKind::TypeMismatch {
+                    register,
+                    expected,
+                    found: found.clone(),
+                });
+            }
+        }
+        // where the value of each used register is needed, a phi needs it at the end of the
+        // source block
+        let uses: Vec<(RegisterName, usize, bool)> = if let IRStatement::Phi(phi) = statement {
+            phi.from
+                .iter()
+                .filter_map(|source| {
+                    let register = source.value.as_local()?.clone();
+                    let &block = block_index.get(source.block.as_str())?;
+                    Some((register, block, true))
+                })
+                .collect()
+        } else {
+            statement
+                .use_register()
+                .into_iter()
+                .map(|register| (register, index.0, false))
+                .collect()
+        };
+        for (register, used_in, at_end) in uses {
+            let Some((defined_at, _)) = definitions.get(&register) else {
+                errors.push(VerifyErrorKind::UndefinedRegister(register));
+                continue;
+            };
+            let Some(defined_at) = defined_at else {
+                continue;
+            };
+            let dominated = if defined_at.0 == used_in {
+                at_end || defined_at.1 < index.1
+            } else {
+                dominates(defined_at.0, used_in)
+            };
+            // nothing is executed in unreachable blocks
+            if !dominated && reachable(used_in) {
+                errors.push(VerifyErrorKind::NotDominated {
+                    register,
+                    block: function.content[used_in].name.clone().unwrap(),
+                });
+            }
+        }
+        if let IRStatement::Phi(phi) = statement {
+            let mut sources: Vec<_> = phi.from.iter().map(|it| it.block.clone()).collect();
+            sources.sort();
+            let predecessor_names = |only_reachable: bool| {
+                let mut names: Vec<_> = predecessors[index.0]
+                    .iter()
+                    .filter(|&&it| !only_reachable || reachable(it))
+                    .map(|&it| function.content[it].name.clone().unwrap())
+                    .collect();
+                names.sort();
+                names
+            };
+            // values from unreachable blocks are never used, so they may be omitted
+            let all_predecessors = predecessor_names(false);
+            let sources_are_predecessors = sources.iter().all(|it| all_predecessors.contains(it));
+            let mut distinct_sources = sources.clone();
+            distinct_sources.dedup();
+            let reachable_predecessors = predecessor_names(true);
+            let every_predecessor_once = distinct_sources.len() == sources.len()
+                && reachable_predecessors.iter().all(|it| sources.contains(it));
+            if !sources_are_predecessors || !every_predecessor_once {
+                errors.push(VerifyErrorKind::PhiSourceMismatch {
+                    register: phi.to.clone(),
+                    predecessors: all_predecessors,
+                    sources,
+                });
+            }
+        }
+    }
+}
+
+/// Check whether `function` is well-formed.
+pub fn verify_function(function: &FunctionDefinition) -> Result<(), Vec<VerifyError>> {
+    let mut errors = Vec::new();
+    verify_structure(function, &mut errors);
+    // the control flow graph cannot be built if the structure is broken
+    if errors.is_empty() {
+        verify_values(function, &mut errors);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors
+            .into_iter()
+            .map(|kind| VerifyError {
+                function: function.header.name.clone(),
+                kind,
+            })
+            .collect())
+    }
+}
+
+/// Check whether all functions in `ir` are well-formed.
+pub fn verify(ir: &[IR]) -> Result<(), Vec<VerifyError>> {
+    let errors: Vec<_> = ir
+        .iter()
+        .filter_map(|it| match it {
+            IR::FunctionDefinition(function) => verify_function(function).err(),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ir, utility::data_type::Integer};
+
+    fn errors(code: &str) -> Vec<VerifyErrorKind> {
+        let function = ir::function::parse(code).unwrap().1;
+        verify_function(&function)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|it| it.kind)
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let code = "fn f(i32 %a) -> i32 {
+  bb0:
+    blt %a, 0, bb1, bb2
+  bb1:
+    %b = sub i32 0, %a
+    j bb2
+  bb2:
+    %c = phi i32 [%a, bb0], [%b, bb1]
+    ret %c
+  bb3:
+    %d = add i32 %e, 1
+    %e = add i32 %d, 1
+    j bb3
+}";
+        assert_eq!(errors(code), vec![]);
+    }
+
+    #[test]
+    fn broken_structure() {
+        let code = "fn f(i32 %a) -> i32 {
+  bb0:
+    j bb1
+    %b = add i32 %a, 1
+  bb1:
+    bne %a, 0, bb0, bb2
+  bb3:
+    %c = add i32 %a, 1
+}";
+        assert_eq!(
+            errors(code),
+            vec![
+                VerifyErrorKind::MisplacedTerminator("bb0".to_string()),
+                VerifyErrorKind::UnknownLabel {
+                    block: "bb1".to_string(),
+                    label: "bb2".to_string()
+                },
+                VerifyErrorKind::MissingTerminator("bb3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_values() {
+        let code = "fn f(i32 %a) -> i32 {
+  bb0:
+    %b = add i32 %c, 1
+    %c = add i32 %a, 1
+    blt %a, 0, bb1, bb2
+  bb1:
+    %d = add i32 %a, 1
+    %d = add i32 %a, 2
+    j bb2
+  bb2:
+    %e = phi i32 [%a, bb0], [%d, bb3]
+    %f = add i32 %d, %x
+    %g = alloca i32
+    %h = add i32 %g, 1
+    %s = alloca S
+    %i = load S %s
+    %j = add i32 %i, 1
+    ret %j
+}";
+        let found = errors(code);
+        let expected = vec![
+            VerifyErrorKind::Redefinition(RegisterName("d".to_string())),
+            VerifyErrorKind::NotDominated {
+                register: RegisterName("c".to_string()),
+                block: "bb0".to_string(),
+            },
+            VerifyErrorKind::PhiSourceMismatch {
+                register: RegisterName("e".to_string()),
+                predecessors: vec!["bb0".to_string(), "bb1".to_string()],
+                sources: vec!["bb0".to_string(), "bb3".to_string()],
+            },
+            VerifyErrorKind::NotDominated {
+                register: RegisterName("d".to_string()),
+                block: "bb2".to_string(),
+            },
+            VerifyErrorKind::UndefinedRegister(RegisterName("x".to_string())),
+            VerifyErrorKind::TypeMismatch {
+                register: RegisterName("i".to_string()),
+                expected: Type::Integer(Integer {
+                    signed: true,
+                    width: 32,
+                }),
+                found: Type::StructRef("S".to_string()),
+            },
+        ];
+        assert_eq!(found, expected);
+    }
+}
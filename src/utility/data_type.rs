@@ -4,7 +4,7 @@ use nom::{
     bytes::complete::tag,
     character::complete::digit1,
     combinator::{map, recognize},
-    sequence::pair,
+    sequence::{delimited, pair, tuple},
     IResult,
 };
 use serde::{Deserialize, Serialize};
@@ -26,6 +26,8 @@ pub enum Type {
     StructRef(String),
     None,
     Address,
+    /// Fixed-size array, `.0` is the element type and `.1` is the length.
+    Array(Box<Type>, usize),
 }
 
 impl fmt::Display for Type {
@@ -35,6 +37,7 @@ impl fmt::Display for Type {
             Type::Address => write!(f, "address"),
             Type::StructRef(name) => write!(f, "{name}"),
             Type::None => write!(f, "()"),
+            Type::Array(element_type, length) => write!(f, "[{element_type}; {length}]"),
         }
     }
 }
@@ -63,9 +66,26 @@ pub fn parse_integer(code: &str) -> IResult<&str, Integer> {
     ))(code)
 }
 
+/// Parse source code to get an array [`Type`], eg. `[u8; 64]`.
+fn parse_array(code: &str) -> IResult<&str, Type> {
+    map(
+        delimited(
+            tag("["),
+            tuple((
+                parsing::in_multispace(parse),
+                tag(";"),
+                parsing::in_multispace(parsing::integer),
+            )),
+            tag("]"),
+        ),
+        |(element_type, _, length)| Type::Array(Box::new(element_type), length),
+    )(code)
+}
+
 /// Parse source code to get a [`Type`].
 pub fn parse(code: &str) -> IResult<&str, Type> {
     alt((
+        parse_array,
         map(
             alt((
                 recognize(pair(parse_integer, tag("*"))),
@@ -86,6 +106,29 @@ impl fmt::Display for Integer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_array() {
+        let (_, array) = parse("[u8; 64]").unwrap();
+        assert_eq!(
+            array,
+            Type::Array(
+                Box::new(Type::Integer(Integer {
+                    signed: false,
+                    width: 8
+                })),
+                64
+            )
+        );
+        assert_eq!(format!("{array}"), "[u8; 64]");
+        let (_, array) = parse("[[i32;2];3]").unwrap();
+        assert_eq!(format!("{array}"), "[[i32; 2]; 3]");
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::declare_interior_mutable_const)]
 pub const BOOL: std::cell::LazyCell<Type> = std::cell::LazyCell::new(|| {